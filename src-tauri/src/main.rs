@@ -4,18 +4,320 @@
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 use std::fs;
-use std::path::Path;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::io::{Read as _, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
 use uuid::Uuid;
 
 static WINDOW_COUNTER: AtomicU64 = AtomicU64::new(0);
+/// 为 true 时待办列表存储在数据目录下的 todos.db（SQLite），否则为 todos.json
+static USE_SQLITE: AtomicBool = AtomicBool::new(false);
+/// todos.json 是否以缩进格式写入（由配置 pretty_index 控制）
+static PRETTY_INDEX: AtomicBool = AtomicBool::new(false);
+/// 写入失败后的重试次数（由配置 write_retries 控制）
+static WRITE_RETRIES: AtomicU64 = AtomicU64::new(2);
+/// 写入待办与详情后是否读回校验（由配置 verify_writes 控制）
+static VERIFY_WRITES: AtomicBool = AtomicBool::new(false);
+/// 标题的最大字符数（由配置 max_title_length 控制），0 表示不限制
+static MAX_TITLE_LENGTH: AtomicU64 = AtomicU64::new(200);
+/// 待办文件夹中存放附件的子目录名（由配置 assets_dir_name 控制），为空时为 assets
+static ASSETS_DIR_NAME: Mutex<String> = Mutex::new(String::new());
+/// 界面强调色是否跟随系统（由配置 use_system_accent 控制）
+static USE_SYSTEM_ACCENT: AtomicBool = AtomicBool::new(false);
+/// 详情 content.json 的最大字节数（由配置 max_detail_bytes 控制），0 表示不限制
+static MAX_DETAIL_BYTES: AtomicU64 = AtomicU64::new(0);
+/// 单个待办文件夹的最大总字节数（由配置 max_folder_bytes 控制），0 表示不限制
+static MAX_FOLDER_BYTES: AtomicU64 = AtomicU64::new(0);
+/// 写入标签时的大小写规则（由配置 tag_case_policy 控制），为空时同 preserve
+static TAG_CASE_POLICY: Mutex<String> = Mutex::new(String::new());
+/// 是否统计各待办详情的打开与编辑次数（由配置 track_access 控制）
+static TRACK_ACCESS: AtomicBool = AtomicBool::new(false);
+/// 归档时是否将待办文件夹移入 archive/<年份>/（由配置 archive_year_layout 控制）
+static ARCHIVE_YEAR_LAYOUT: AtomicBool = AtomicBool::new(false);
+/// 是否将修改类命令记入数据目录下的审计日志（由配置 enable_audit 控制）
+static AUDIT_ENABLED: AtomicBool = AtomicBool::new(false);
+/// 当前配置的数据目录，命令参数中未带 dataPath 时审计日志写入此处
+static AUDIT_DATA_PATH: Mutex<String> = Mutex::new(String::new());
+/// 保存待办后执行的命令（由配置 post_save_hook 控制），为空时不执行
+static POST_SAVE_HOOK: Mutex<String> = Mutex::new(String::new());
+/// 每次保存递增；延迟结束时若已有更新的保存则放弃本次执行，使连续保存只触发一次
+static POST_SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+/// 最后一次保存后等待多久再执行 post_save_hook
+const POST_SAVE_HOOK_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+const SQLITE_FILE: &str = "todos.db";
+/// 数据目录下存放备份的子目录
+const BACKUPS_DIR: &str = "backups";
+/// 系统回收站不可用时使用的应用内回收站，位于数据目录下
+const TRASH_DIR: &str = ".trash";
+
+/// 开启 archive_year_layout 时存放归档待办的子目录，其下按完成年份再分一层
+const ARCHIVE_DIR: &str = "archive";
+
+/// 数据目录下由应用自身管理、不属于任何待办的目录（备份、归档、以 . 开头的隐藏目录等）
+fn is_reserved_dir(name: &str) -> bool {
+    name == BACKUPS_DIR || name == ARCHIVE_DIR || name.starts_with('.')
+}
+
+/// 按内容哈希命名、供多个待办共用的资源池，位于数据目录下
+const SHARED_ASSETS_DIR: &str = ".assets";
+/// 详情中引用共用资源时 assetPath 的前缀（相对待办文件夹）
+const SHARED_ASSET_PREFIX: &str = "../.assets/";
+
+/// 数据目录锁文件，内容为持有者的进程号
+const LOCK_FILE: &str = ".lock";
+/// 当前进程持有的锁文件路径
+static DATA_LOCK: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// 正在监视 assets 目录的待办（文件夹路径与对应的监视器）
+static ASSET_WATCHERS: Mutex<Vec<(PathBuf, notify::RecommendedWatcher)>> = Mutex::new(Vec::new());
+
+/// 应用锁状态：锁定时拒绝 MUTATING_COMMANDS 中的命令
+static APP_LOCKED: AtomicBool = AtomicBool::new(false);
+/// 最近一次调用命令的时间（Unix 秒），用于空闲自动锁定
+static LAST_ACTIVITY: AtomicU64 = AtomicU64::new(0);
+static AUTOLOCK_MINUTES: AtomicU64 = AtomicU64::new(0);
+/// 自动备份间隔小时数（由配置 auto_backup_interval_hours 控制），0 表示不自动备份
+static AUTO_BACKUP_HOURS: AtomicU64 = AtomicU64::new(0);
+/// 本次运行中已提醒过的（待办 id, 提醒时间或截止日期），避免重复提醒
+static FIRED_REMINDERS: Mutex<std::collections::BTreeSet<(String, String)>> = Mutex::new(std::collections::BTreeSet::new());
+/// 会修改数据或配置的命令，应用锁定时一律拒绝
+const MUTATING_COMMANDS: &[&str] = &[
+    "save_app_config",
+    "save_todos",
+    "create_todo_folder",
+    "delete_todo_folder",
+    "save_todo_detail",
+    "move_data",
+    "rebuild_index",
+    "migrate_json_to_sqlite",
+    "backup_data",
+    "save_scratchpad",
+    "merge_todos",
+    "record_daily_stats",
+    "snooze_todo",
+    "import_workspace",
+    "normalize_line_endings",
+    "normalize_all_line_endings",
+    "restore_backup",
+    "optimize_folder",
+    "import_text_folder",
+    "delete_todos",
+    "rename_folders_by_title",
+    "set_app_lock",
+    "cycle_status",
+    "migrate_content_schema",
+    "global_dedupe_attachments",
+    "delete_todo_folder_to_os_trash",
+    "bulk_set_priority",
+    "set_todo_status",
+    "restore_from_trash",
+    "compact_index",
+    "rename_todo",
+    "add_dependency",
+    "remove_dependency",
+    "set_archived",
+    "repair_data_path",
+    "import_settings",
+    "reconcile_titles",
+    "convert_assets_web_safe",
+    "set_language",
+    "split_todo",
+    "purge_todo_history",
+    "normalize_timestamps",
+    "set_reminder",
+    "move_attachments",
+    "save_config_preset",
+    "apply_config_preset",
+    "import_bookmarks",
+    "rename_tag",
+    "delete_tag",
+    "generate_manifest",
+    "set_effort",
+    "migrate_to_shared_assets",
+    "move_todo_to_board",
+    "restore_todo_from_backup",
+    "import_markdown_tree",
+    "repair_permissions",
+    "auto_archive",
+    "approve_attachment",
+    "create_todos_bulk",
+    "repair_bom_files",
+    "set_active_config",
+    "migrate_assets_dir",
+    "repair_mojibake",
+    "import_workspace_encrypted",
+    "set_status_color",
+    "import_ical",
+    "register_quick_capture_hotkey",
+    "compress_old_backups",
+    "merge_sync_conflict",
+    "set_editor_theme",
+    "normalize_tags",
+    "save_query",
+    "save_detail_draft",
+    "discard_draft",
+    "commit_draft",
+    "save_config_partial",
+    "resave_all",
+    "reset_config_keep_data_path",
+    "migrate_archive_layout",
+];
+
+/// 单个日志文件的最大字节数，超过后轮转为 app.log.1
+const LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum LogLevel {
+    Off,
+    Error,
+    Info,
+}
+
+impl LogLevel {
+    fn parse(value: &str) -> Option<LogLevel> {
+        match value {
+            "off" => Some(LogLevel::Off),
+            "error" => Some(LogLevel::Error),
+            "info" => Some(LogLevel::Info),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Off => "OFF",
+            LogLevel::Error => "ERROR",
+            LogLevel::Info => "INFO",
+        }
+    }
+}
+
+struct LogState {
+    path: Option<PathBuf>,
+    level: LogLevel,
+}
+
+static LOG_STATE: Mutex<LogState> = Mutex::new(LogState { path: None, level: LogLevel::Error });
+
+const STATUS_PENDING: &str = "pending";
+const STATUS_COMPLETED: &str = "completed";
+const STATUS_IN_PROGRESS: &str = "in_progress";
+/// 待办优先级上限（0 无、1 低、2 中、3 高）
+const MAX_PRIORITY: u8 = 3;
+/// 所有已知的待办状态，按工作流顺序排列
+const KNOWN_STATUSES: &[&str] = &["backlog", STATUS_PENDING, STATUS_IN_PROGRESS, STATUS_COMPLETED];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 struct TodoItem {
     id: String,
     title: String,
     status: String,
     folder_name: String,
+    /// 截止日期，YYYY-MM-DD 或 RFC 3339
+    #[serde(default)]
+    due_date: Option<String>,
+    /// 优先级，0 为无，数值越大越优先（最大为 MAX_PRIORITY）
+    #[serde(default)]
+    priority: u8,
+    /// 创建时间（RFC 3339），由后端在首次保存时写入
+    #[serde(default)]
+    created_at: Option<String>,
+    /// 最近修改时间（RFC 3339），由后端在索引项内容变化时写入
+    #[serde(default)]
+    updated_at: Option<String>,
+    /// 完成时间（RFC 3339），状态变为已完成时写入，移出已完成时清空
+    #[serde(default)]
+    completed_at: Option<String>,
+    /// 推迟到该时间之前不在默认列表中显示
+    #[serde(default)]
+    snoozed_until: Option<String>,
+    /// 提醒时间（RFC 3339），与截止日期相互独立
+    #[serde(default)]
+    remind_at: Option<String>,
+    /// 标签，一个待办可属于多个标签
+    #[serde(default)]
+    tags: Vec<String>,
+    /// 手动标记为今天要做
+    #[serde(default)]
+    today: bool,
+    /// 重复周期："daily" / "weekly" / "monthly" / "yearly"，为空表示不重复
+    #[serde(default)]
+    recurrence: Option<String>,
+    /// 前置待办的 folder_name，全部完成前本待办视为被阻塞
+    #[serde(default)]
+    depends_on: Vec<String>,
+    /// 已归档，默认列表中不显示
+    #[serde(default)]
+    archived: bool,
+    /// 预估工作量（分钟或点数，单位由用户自行约定），为空表示未估算
+    #[serde(default)]
+    effort: Option<u32>,
+    /// 所属看板，同一数据目录下的轻量分组，旧数据默认为 "default"
+    #[serde(default = "default_board")]
+    board: String,
+}
+
+/// 未指定看板的待办所属的看板
+const DEFAULT_BOARD: &str = "default";
+
+fn default_board() -> String {
+    DEFAULT_BOARD.to_string()
+}
+
+/// 当前时间，以带时区偏移的 UTC RFC 3339 格式写入待办的时间字段
+fn utc_now_rfc3339() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+/// 解析不带时区的本地时间（如旧数据中的 "2024-01-01T09:30:00"）
+fn parse_naive_local(value: &str) -> Option<chrono::DateTime<chrono::Local>> {
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|f| chrono::NaiveDateTime::parse_from_str(value, f).ok())
+        .and_then(|d| d.and_local_timezone(chrono::Local).earliest())
+}
+
+/// 解析 RFC 3339 时间、不带时区的本地时间或 YYYY-MM-DD 日期（视为当天本地零点）
+fn parse_timestamp(value: &str) -> Option<chrono::DateTime<chrono::Local>> {
+    let value = value.trim();
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(t.with_timezone(&chrono::Local));
+    }
+    if let Some(t) = parse_naive_local(value) {
+        return Some(t);
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .and_then(|d| d.and_local_timezone(chrono::Local).earliest())
+}
+
+/// 将带时间的时间戳统一为 UTC RFC 3339；纯日期（YYYY-MM-DD）保持不变。已是该格式或无法解析时返回 None
+fn normalize_timestamp(value: &str) -> Option<String> {
+    let value = value.trim();
+    if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+        return None;
+    }
+    let utc = parse_timestamp(value)?.with_timezone(&chrono::Utc).to_rfc3339();
+    (utc != value).then_some(utc)
+}
+
+fn is_snoozed(todo: &TodoItem, now: &chrono::DateTime<chrono::Local>) -> bool {
+    todo.snoozed_until
+        .as_deref()
+        .and_then(parse_timestamp)
+        .map(|until| until > *now)
+        .unwrap_or(false)
+}
+
+/// 解析截止日期为本地日期；带时区的时间先换算到本地时区，避免跨时区后日期错位
+fn parse_due_date(value: &str) -> Option<chrono::NaiveDate> {
+    let value = value.trim();
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| parse_timestamp(value).map(|d| d.date_naive()))
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -27,8 +329,377 @@ struct AppConfig {
     font_size: u32,
     text_color_light: String,
     text_color_dark: String,
+    #[serde(default = "default_accent_color_light")]
+    accent_color_light: String,
+    #[serde(default = "default_accent_color_dark")]
+    accent_color_dark: String,
+    #[serde(default = "default_bg_color_light")]
+    bg_color_light: String,
+    #[serde(default = "default_bg_color_dark")]
+    bg_color_dark: String,
+    #[serde(default = "default_border_color_light")]
+    border_color_light: String,
+    #[serde(default = "default_border_color_dark")]
+    border_color_dark: String,
     #[serde(default)]
     launch_at_login: bool,
+    /// 迁移数据目录与备份时跳过的文件/文件夹（glob 模式，匹配顶层名称）
+    #[serde(default = "default_move_exclude")]
+    move_exclude: Vec<String>,
+    /// 日志级别：off / error / info
+    #[serde(default = "default_log_level")]
+    log_level: String,
+    /// 待办列表存储后端：json（默认）/ sqlite
+    #[serde(default = "default_storage_backend")]
+    storage_backend: String,
+    /// 新建待办的初始状态，须为 KNOWN_STATUSES 之一
+    #[serde(default = "default_status")]
+    default_status: String,
+    /// content.json 中文本使用的换行符："lf" 或 "crlf"
+    #[serde(default = "default_line_ending")]
+    line_ending: String,
+    /// 备份存放目录，留空时使用 data_path/backups
+    #[serde(default)]
+    backup_path: String,
+    /// 删除数量不超过该值时无需确认；批量删除超过时必须显式确认，默认 10
+    #[serde(default = "default_confirm_delete_threshold")]
+    confirm_delete_threshold: usize,
+    /// 空闲多少分钟后自动锁定（需已设置应用锁），0 为不自动锁定
+    #[serde(default)]
+    autolock_minutes: u32,
+    /// 状态流转规则：状态 -> 允许转到的状态；未设置时允许任意流转
+    #[serde(default)]
+    allowed_transitions: Option<std::collections::BTreeMap<String, Vec<String>>>,
+    /// zip 导出的压缩级别，0（不压缩）到 9（最小体积），默认 6
+    #[serde(default = "default_export_compression")]
+    export_compression: u8,
+    /// 指定颜色的标签（标签 -> 十六进制颜色），未指定的由 get_tag_color 按标签名生成
+    #[serde(default)]
+    tag_colors: std::collections::BTreeMap<String, String>,
+    /// 各状态的显示颜色（状态 -> 十六进制颜色），缺少的状态使用默认颜色
+    #[serde(default = "default_status_colors")]
+    status_colors: std::collections::BTreeMap<String, String>,
+    /// todos.json 以缩进格式写入，便于手动查看；默认紧凑格式
+    #[serde(default)]
+    pretty_index: bool,
+    /// 写入待办、详情与配置失败时的重试次数（0-10），用于网络盘上的偶发 IO 错误，默认 2
+    #[serde(default = "default_write_retries")]
+    write_retries: u32,
+    /// 保存待办后执行的命令（数据目录作为最后一个参数传入），默认为空即不执行
+    #[serde(default)]
+    post_save_hook: String,
+    /// 保存待办与详情后读回文件，确认可解析且与写入内容一致，用于发现不可靠存储上的静默截断
+    #[serde(default)]
+    verify_writes: bool,
+    /// 将每次修改类命令的时间、操作名与待办文件夹追加到数据目录下的 audit.log（不记录笔记内容）
+    #[serde(default)]
+    enable_audit: bool,
+    /// 标题的最大字符数，保存时超出部分被截断、改名时超出则拒绝；0 表示不限制，默认 200
+    #[serde(default = "default_max_title_length")]
+    max_title_length: usize,
+    /// 详情内容的最大字节数，保存超出时拒绝并提示拆分或改为附件；0 表示不限制，默认 0
+    #[serde(default)]
+    max_detail_bytes: u64,
+    /// 启动时自动归档完成超过多少天的待办，0 表示不自动归档
+    #[serde(default)]
+    auto_archive_days: u32,
+    /// 打印卡片时每页的卡片数（1-12），默认 4
+    #[serde(default = "default_cards_per_page")]
+    cards_per_page: u32,
+    /// 界面强调色跟随系统强调色，系统不提供时仍使用 accent_color_light / accent_color_dark
+    #[serde(default)]
+    use_system_accent: bool,
+    /// 新添加的附件先放入 assets/quarantine（视为未确认），经 approve_attachment 确认后才移入 assets
+    #[serde(default)]
+    quarantine_imports: bool,
+    /// 待办文件夹中存放附件的子目录名，默认 assets；修改后已有文件夹需用 migrate_assets_dir 迁移
+    #[serde(default = "default_assets_dir_name")]
+    assets_dir_name: String,
+    /// 应用运行期间每隔多少小时自动备份一次，0 表示不自动备份
+    #[serde(default)]
+    auto_backup_interval_hours: u32,
+    /// 最多保留的自动备份数，超出时删除最旧的自动备份（手动备份不受影响）；0 表示不限制
+    #[serde(default)]
+    max_backups: usize,
+    /// 紧迫度中截止日期临近程度的权重，默认 12
+    #[serde(default = "default_urgency_due_weight")]
+    urgency_due_weight: f64,
+    /// 紧迫度中优先级的权重，默认 6
+    #[serde(default = "default_urgency_priority_weight")]
+    urgency_priority_weight: f64,
+    /// 紧迫度中创建时长的权重，默认 2
+    #[serde(default = "default_urgency_age_weight")]
+    urgency_age_weight: f64,
+    /// 快速记录待办的全局快捷键（如 CommandOrControl+Shift+Space），为空表示不使用
+    #[serde(default)]
+    quick_capture_hotkey: String,
+    /// compress_old_backups 压缩早于多少天的备份（最近一次备份始终保持未压缩），默认 30
+    #[serde(default = "default_compress_backups_after_days")]
+    compress_backups_after_days: u32,
+    /// 单个待办文件夹（详情与附件）的最大总字节数，添加附件后超出时拒绝；0 表示不限制，默认 0
+    #[serde(default)]
+    max_folder_bytes: u64,
+    /// 写入标签时的大小写规则："preserve"（保持原样）、"lower"（全部小写）或 "title"（每个词首字母大写），默认 preserve
+    #[serde(default = "default_tag_case_policy")]
+    tag_case_policy: String,
+    /// 同时处于进行中的待办数上限（不含已归档的），达到上限后不能再将待办改为进行中；0 表示不限制
+    #[serde(default)]
+    wip_limit: usize,
+    /// 在各待办的 meta.json 中记录详情被打开与编辑的次数，供 get_access_stats 排出常用笔记；默认关闭
+    #[serde(default)]
+    track_access: bool,
+    /// 归档的待办按完成年份移入 archive/<年份>/ 子目录，其 folder_name 随之变为 archive/<年份>/<原文件夹名>；默认关闭
+    #[serde(default)]
+    archive_year_layout: bool,
+}
+
+fn default_tag_case_policy() -> String {
+    "preserve".to_string()
+}
+
+fn default_compress_backups_after_days() -> u32 {
+    30
+}
+
+fn default_export_compression() -> u8 {
+    6
+}
+
+fn default_write_retries() -> u32 {
+    2
+}
+
+fn default_max_title_length() -> usize {
+    200
+}
+
+fn default_status_colors() -> std::collections::BTreeMap<String, String> {
+    [("backlog", "#909399"), (STATUS_PENDING, "#409eff"), ("in_progress", "#e6a23c"), (STATUS_COMPLETED, "#999999")]
+        .into_iter()
+        .map(|(status, color)| (status.to_string(), color.to_string()))
+        .collect()
+}
+
+fn default_assets_dir_name() -> String {
+    "assets".to_string()
+}
+
+fn default_cards_per_page() -> u32 {
+    4
+}
+
+fn default_urgency_due_weight() -> f64 {
+    12.0
+}
+
+fn default_urgency_priority_weight() -> f64 {
+    6.0
+}
+
+fn default_urgency_age_weight() -> f64 {
+    2.0
+}
+
+fn default_confirm_delete_threshold() -> usize {
+    10
+}
+
+fn default_line_ending() -> String {
+    "lf".to_string()
+}
+
+fn default_status() -> String {
+    STATUS_PENDING.to_string()
+}
+
+fn default_storage_backend() -> String {
+    "json".to_string()
+}
+
+fn default_log_level() -> String {
+    "error".to_string()
+}
+
+fn default_accent_color_light() -> String {
+    "#409eff".to_string()
+}
+
+fn default_accent_color_dark() -> String {
+    "#409eff".to_string()
+}
+
+fn default_bg_color_light() -> String {
+    "#ffffff".to_string()
+}
+
+fn default_bg_color_dark() -> String {
+    "#1a1a1a".to_string()
+}
+
+fn default_border_color_light() -> String {
+    "#e6e6e6".to_string()
+}
+
+fn default_border_color_dark() -> String {
+    "#444444".to_string()
+}
+
+/// 是否为 #rgb / #rrggbb / #rrggbbaa 形式的十六进制颜色
+fn is_hex_color(value: &str) -> bool {
+    match value.strip_prefix('#') {
+        Some(hex) => matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
+/// 将快捷键组合规范为 修饰键+按键 的形式（修饰键按固定顺序），须至少包含一个修饰键与一个普通按键
+fn normalize_hotkey(combo: &str) -> Result<String, String> {
+    let mut modifiers: Vec<&str> = Vec::new();
+    let mut key: Option<String> = None;
+    for part in combo.split('+').map(str::trim) {
+        let modifier = match part.to_lowercase().as_str() {
+            "commandorcontrol" | "cmdorctrl" | "cmdorcontrol" | "commandorctrl" => Some("CommandOrControl"),
+            "control" | "ctrl" => Some("Control"),
+            "alt" | "option" => Some("Alt"),
+            "shift" => Some("Shift"),
+            "super" | "cmd" | "command" | "meta" | "win" => Some("Super"),
+            _ => None,
+        };
+        if let Some(modifier) = modifier {
+            if modifiers.contains(&modifier) {
+                return Err(format!("快捷键中重复的修饰键: {}", part));
+            }
+            modifiers.push(modifier);
+            continue;
+        }
+        let upper = part.to_uppercase();
+        let valid = (upper.len() == 1 && upper.chars().all(|c| c.is_ascii_alphanumeric()))
+            || upper.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()).is_some_and(|n| (1..=24).contains(&n))
+            || ["SPACE", "ENTER", "TAB", "BACKSPACE", "DELETE", "INSERT", "HOME", "END", "PAGEUP", "PAGEDOWN", "UP", "DOWN", "LEFT", "RIGHT"]
+                .contains(&upper.as_str());
+        if !valid || key.is_some() {
+            return Err(format!("无效的快捷键: {}", combo));
+        }
+        key = Some(if upper.len() == 1 { upper } else { part[..1].to_uppercase() + &part[1..].to_lowercase() });
+    }
+    let key = key.ok_or_else(|| format!("快捷键缺少按键: {}", combo))?;
+    if modifiers.is_empty() {
+        return Err(format!("快捷键须包含修饰键（Ctrl / Alt / Shift 等）: {}", combo));
+    }
+    const ORDER: [&str; 5] = ["CommandOrControl", "Control", "Alt", "Shift", "Super"];
+    modifiers.sort_by_key(|m| ORDER.iter().position(|o| o == m));
+    Ok(format!("{}+{}", modifiers.join("+"), key))
+}
+
+fn fallback_color(value: &mut String, default: String) {
+    if !is_hex_color(value) {
+        *value = default;
+    }
+}
+
+/// 将无效的颜色配置项回退为默认值
+fn sanitize_theme_colors(config: &mut AppConfig) {
+    fallback_color(&mut config.text_color_light, "#333333".to_string());
+    fallback_color(&mut config.text_color_dark, "#e5e5e5".to_string());
+    fallback_color(&mut config.accent_color_light, default_accent_color_light());
+    fallback_color(&mut config.accent_color_dark, default_accent_color_dark());
+    fallback_color(&mut config.bg_color_light, default_bg_color_light());
+    fallback_color(&mut config.bg_color_dark, default_bg_color_dark());
+    fallback_color(&mut config.border_color_light, default_border_color_light());
+    fallback_color(&mut config.border_color_dark, default_border_color_dark());
+}
+
+/// WCAG 定义的相对亮度（忽略透明度）
+fn relative_luminance(color: &str) -> f64 {
+    let image::Rgba([r, g, b, _]) = parse_hex_rgba(color);
+    let channel = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+}
+
+fn contrast_ratio(foreground: &str, background: &str) -> f64 {
+    let (a, b) = (relative_luminance(foreground), relative_luminance(background));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// WCAG 正文文字的对比度要求：AA 为 4.5，AAA 为 7；大号文字 AA 为 3
+const CONTRAST_AA: f64 = 4.5;
+const CONTRAST_AAA: f64 = 7.0;
+const CONTRAST_AA_LARGE: f64 = 3.0;
+
+#[derive(Serialize, Debug)]
+struct ContrastReport {
+    /// 对比度，范围 1 ~ 21，保留两位小数
+    ratio: f64,
+    aa: bool,
+    aaa: bool,
+    aa_large: bool,
+}
+
+/// 计算文字色与背景色的 WCAG 对比度，并给出是否达到 AA / AAA 要求
+#[tauri::command]
+fn check_contrast(foreground: String, background: String) -> Result<ContrastReport, String> {
+    for color in [&foreground, &background] {
+        if !is_hex_color(color) {
+            return Err(format!("无效的颜色: {}", color));
+        }
+    }
+    let ratio = contrast_ratio(&foreground, &background);
+    Ok(ContrastReport {
+        ratio: (ratio * 100.0).round() / 100.0,
+        aa: ratio >= CONTRAST_AA,
+        aaa: ratio >= CONTRAST_AAA,
+        aa_large: ratio >= CONTRAST_AA_LARGE,
+    })
+}
+
+/// 浅色或深色主题的文字色与背景色对比度不足 AA 时的警告，由 save_app_config 返回给界面提示，不阻止保存
+fn low_contrast_warnings(config: &AppConfig) -> Vec<String> {
+    [
+        ("light", &config.text_color_light, &config.bg_color_light),
+        ("dark", &config.text_color_dark, &config.bg_color_dark),
+    ]
+    .into_iter()
+    .filter_map(|(theme, text, bg)| {
+        let ratio = contrast_ratio(text, bg);
+        (ratio < CONTRAST_AA).then(|| {
+            format!("{} 主题文字色 {} 与背景色 {} 对比度仅 {:.2}，低于 WCAG AA 要求的 {}，文字可能难以辨认", theme, text, bg, ratio, CONTRAST_AA)
+        })
+    })
+    .collect()
+}
+
+/// 界面已提供完整翻译的语言，与 src/i18n 下的语言文件一致
+const SUPPORTED_LANGUAGES: &[&str] = &["zh-CN", "en-US"];
+
+/// 不支持的语言回退为简体中文，返回需提示用户的警告
+fn sanitize_language(config: &mut AppConfig) -> Option<String> {
+    if SUPPORTED_LANGUAGES.contains(&config.language.as_str()) {
+        return None;
+    }
+    let warning = format!("不支持的语言 {}，已回退为 {}", config.language, SUPPORTED_LANGUAGES[0]);
+    config.language = SUPPORTED_LANGUAGES[0].to_string();
+    Some(warning)
+}
+
+fn default_move_exclude() -> Vec<String> {
+    vec!["config.json".to_string(), ".git".to_string()]
+}
+
+fn validate_exclude_patterns(patterns: &[String]) -> Result<(), String> {
+    for p in patterns {
+        glob::Pattern::new(p).map_err(|e| format!("无效的排除模式 \"{}\": {}", p, e))?;
+    }
+    Ok(())
+}
+
+fn is_excluded(name: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .filter_map(|p| glob::Pattern::new(p).ok())
+        .any(|p| p.matches(name))
 }
 
 fn default_config(handle: &tauri::AppHandle) -> AppConfig {
@@ -40,217 +711,8677 @@ fn default_config(handle: &tauri::AppHandle) -> AppConfig {
         font_size: 14,
         text_color_light: "#333333".to_string(),
         text_color_dark: "#e5e5e5".to_string(),
+        accent_color_light: default_accent_color_light(),
+        accent_color_dark: default_accent_color_dark(),
+        bg_color_light: default_bg_color_light(),
+        bg_color_dark: default_bg_color_dark(),
+        border_color_light: default_border_color_light(),
+        border_color_dark: default_border_color_dark(),
         launch_at_login: false,
+        move_exclude: default_move_exclude(),
+        log_level: default_log_level(),
+        storage_backend: default_storage_backend(),
+        default_status: default_status(),
+        line_ending: default_line_ending(),
+        backup_path: String::new(),
+        confirm_delete_threshold: default_confirm_delete_threshold(),
+        autolock_minutes: 0,
+        allowed_transitions: None,
+        export_compression: default_export_compression(),
+        tag_colors: Default::default(),
+        status_colors: default_status_colors(),
+        pretty_index: false,
+        write_retries: default_write_retries(),
+        post_save_hook: String::new(),
+        verify_writes: false,
+        enable_audit: false,
+        max_title_length: default_max_title_length(),
+        max_detail_bytes: 0,
+        auto_archive_days: 0,
+        cards_per_page: default_cards_per_page(),
+        use_system_accent: false,
+        quarantine_imports: false,
+        assets_dir_name: default_assets_dir_name(),
+        auto_backup_interval_hours: 0,
+        max_backups: 0,
+        urgency_due_weight: default_urgency_due_weight(),
+        urgency_priority_weight: default_urgency_priority_weight(),
+        urgency_age_weight: default_urgency_age_weight(),
+        quick_capture_hotkey: String::new(),
+        compress_backups_after_days: default_compress_backups_after_days(),
+        max_folder_bytes: 0,
+        tag_case_policy: default_tag_case_policy(),
+        wip_limit: 0,
+        track_access: false,
+        archive_year_layout: false,
     }
 }
 
-#[tauri::command]
-fn get_app_config(handle: tauri::AppHandle) -> AppConfig {
-    let config_path = handle.path().app_config_dir().unwrap().join("config.json");
-    if !config_path.exists() {
-        return default_config(&handle);
+fn home_dir() -> Option<String> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+        .filter(|h| !h.is_empty())
+}
+
+/// 展开路径中的 ~、$VAR、${VAR} 与 %VAR%，未定义的变量原样保留
+fn expand_data_path(raw: &str) -> String {
+    let mut input = raw.trim().to_string();
+    if input == "~" || input.starts_with("~/") || input.starts_with("~\\") {
+        if let Some(home) = home_dir() {
+            input = format!("{}{}", home, &input[1..]);
+        }
     }
-    let content = match fs::read_to_string(&config_path) {
-        Ok(c) => c,
-        Err(_) => return default_config(&handle),
-    };
-    serde_json::from_str(&content).unwrap_or_else(|_| default_config(&handle))
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '%' {
+            if let Some(len) = chars[i + 1..].iter().position(|&ch| ch == '%') {
+                let name: String = chars[i + 1..i + 1 + len].iter().collect();
+                if !name.is_empty() {
+                    if let Ok(value) = std::env::var(&name) {
+                        out.push_str(&value);
+                        i += len + 2;
+                        continue;
+                    }
+                }
+            }
+        } else if c == '$' {
+            let (name, consumed) = if chars.get(i + 1) == Some(&'{') {
+                match chars[i + 2..].iter().position(|&ch| ch == '}') {
+                    Some(len) => (chars[i + 2..i + 2 + len].iter().collect::<String>(), len + 3),
+                    None => (String::new(), 0),
+                }
+            } else {
+                let name: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|ch| ch.is_ascii_alphanumeric() || **ch == '_')
+                    .collect();
+                let len = name.chars().count();
+                (name, len + 1)
+            };
+            if !name.is_empty() {
+                if let Ok(value) = std::env::var(&name) {
+                    out.push_str(&value);
+                    i += consumed;
+                    continue;
+                }
+            }
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
 }
 
+/// 返回展开变量后的绝对数据路径，供界面展示实际指向的位置
 #[tauri::command]
-fn save_app_config(handle: tauri::AppHandle, config: AppConfig) -> Result<(), String> {
-    let config_dir = handle.path().app_config_dir().unwrap();
-    if !config_dir.exists() {
-        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+fn resolve_data_path(raw: String) -> Result<String, String> {
+    let expanded = expand_data_path(&raw);
+    if expanded.is_empty() {
+        return Ok(expanded);
     }
-    let config_path = config_dir.join("config.json");
-    let content = serde_json::to_string(&config).map_err(|e| e.to_string())?;
-    fs::write(config_path, content).map_err(|e| e.to_string())?;
-    let _ = handle.emit("config-changed", ());
-    Ok(())
+    let absolute = std::path::absolute(&expanded).map_err(|e| e.to_string())?;
+    Ok(absolute.to_string_lossy().into_owned())
 }
 
+/// 指向待办的链接形如 simpletodo://todo/<folder_name>
+const TODO_LINK_PREFIX: &str = "simpletodo://todo/";
+/// 启动参数中带有的待办链接，等界面就绪后由 take_pending_todo_link 取走
+static PENDING_TODO_LINK: Mutex<Option<String>> = Mutex::new(None);
+
+/// 返回指向待办的链接，可用于笔记间互相引用或外部书签
 #[tauri::command]
-fn get_todos(data_path: String) -> Vec<TodoItem> {
-    let todos_path = Path::new(&data_path).join("todos.json");
-    if todos_path.exists() {
-        let content = fs::read_to_string(todos_path).unwrap();
-        serde_json::from_str(&content).unwrap_or_else(|_| vec![])
-    } else {
-        vec![]
+fn get_todo_link(folder_name: String, anchor: Option<String>) -> Result<String, String> {
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    match anchor.as_deref().map(str::trim).filter(|a| !a.is_empty()) {
+        Some(anchor) => Ok(format!("{}{}#{}", TODO_LINK_PREFIX, folder_name, encode_uri_component(anchor))),
+        None => Ok(format!("{}{}", TODO_LINK_PREFIX, folder_name)),
     }
 }
 
-#[tauri::command]
-fn save_todos(app: tauri::AppHandle, data_path: String, todos: Vec<TodoItem>) -> Result<(), String> {
-    let data_dir = Path::new(&data_path);
-    if !data_dir.exists() {
-        fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+/// 解码 encode_uri_component 编码的文本，无效的转义原样保留
+fn decode_uri_component(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
     }
-    let todos_path = data_dir.join("todos.json");
-    let content = serde_json::to_string(&todos).map_err(|e| e.to_string())?;
-    fs::write(todos_path, content).map_err(|e| e.to_string())?;
-    let _ = app.emit("todos-changed", ());
-    Ok(())
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// 从命令行参数中找出待办链接
+fn find_todo_link(args: &[String]) -> Option<String> {
+    args.iter().find(|a| a.starts_with(TODO_LINK_PREFIX)).cloned()
 }
 
+/// 取走启动时传入的待办链接（只返回一次）
 #[tauri::command]
-fn create_todo_folder(data_path: String) -> Result<String, String> {
-    let folder_name = Uuid::new_v4().to_string();
-    let folder_path = Path::new(&data_path).join(&folder_name);
-    fs::create_dir_all(&folder_path).map_err(|e| e.to_string())?;
-    fs::create_dir_all(folder_path.join("assets")).map_err(|e| e.to_string())?;
-    Ok(folder_name)
+fn take_pending_todo_link() -> Option<String> {
+    PENDING_TODO_LINK.lock().ok()?.take()
+}
+
+#[derive(Serialize, Debug)]
+struct TodoLinkTarget {
+    todo: TodoItem,
+    /// 链接中 # 之后的锚点
+    anchor: Option<String>,
+    /// 锚点对应标题的节点 id 与所在顶层块序号，供界面滚动定位；锚点不存在时为空
+    block_id: Option<String>,
+    position: Option<usize>,
 }
 
+/// 解析待办链接（可带 #锚点）并返回对应的待办与锚点所在位置；待办已删除或链接无效时返回可直接展示的提示
 #[tauri::command]
-fn delete_todo_folder(data_path: String, folder_name: String) -> Result<(), String> {
-    let folder_path = Path::new(&data_path).join(&folder_name);
-    if folder_path.exists() {
-        fs::remove_dir_all(&folder_path).map_err(|e| format!("删除文件夹失败: {}", e))?;
+fn resolve_todo_link(data_path: String, link: String) -> Result<TodoLinkTarget, String> {
+    let data_path = expand_data_path(&data_path);
+    let rest = link
+        .trim()
+        .strip_prefix(TODO_LINK_PREFIX)
+        .ok_or_else(|| format!("无效的待办链接: {}", link))?;
+    let (folder_name, anchor) = match rest.split_once('#') {
+        Some((folder, anchor)) => (folder, Some(decode_uri_component(anchor)).filter(|a| !a.is_empty())),
+        None => (rest, None),
+    };
+    let folder_name = sanitize_folder_name(folder_name.trim_end_matches('/'))?;
+    let todo = load_todos(&data_path)
+        .into_iter()
+        .find(|t| t.folder_name == folder_name && Path::new(&data_path).join(&folder_name).is_dir())
+        .ok_or_else(|| "链接指向的待办不存在，可能已被删除".to_string())?;
+    let heading = anchor.as_ref().and_then(|anchor| {
+        get_detail_outline(data_path.clone(), folder_name.clone())
+            .ok()?
+            .into_iter()
+            .find(|e| e.anchor == *anchor)
+    });
+    Ok(TodoLinkTarget {
+        todo,
+        anchor,
+        block_id: heading.as_ref().and_then(|h| h.id.clone()),
+        position: heading.map(|h| h.position),
+    })
+}
+
+/// 返回待办文件夹的绝对路径；文件夹名非法、待办不在索引中或文件夹不存在时返回错误
+fn todo_folder_path(data_path: &str, folder_name: &str) -> Result<PathBuf, String> {
+    let folder_name = sanitize_folder_name(folder_name)?;
+    if !load_todos(data_path).iter().any(|t| t.folder_name == folder_name) {
+        return Err(format!("待办不存在: {}", folder_name));
     }
-    Ok(())
+    let folder = std::path::absolute(Path::new(data_path).join(&folder_name)).map_err(|e| e.to_string())?;
+    if !folder.is_dir() {
+        return Err(format!("待办文件夹不存在: {}", folder.display()));
+    }
+    Ok(folder)
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct OrphanFolder {
-    folder_name: String,
-    size: u64,
+/// 待办文件夹的绝对路径，供外部脚本直接处理笔记文件
+#[tauri::command]
+fn get_todo_folder_path(data_path: String, folder_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    Ok(todo_folder_path(&data_path, &folder_name)?.to_string_lossy().into_owned())
 }
 
-fn calculate_dir_size(path: &Path) -> u64 {
-    let mut total_size = 0u64;
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let entry_path = entry.path();
-                if entry_path.is_dir() {
-                    total_size += calculate_dir_size(&entry_path);
-                } else if let Ok(metadata) = entry_path.metadata() {
-                    total_size += metadata.len();
-                }
+/// 待办 content.json 的绝对路径（文件可能尚未创建）
+#[tauri::command]
+fn get_todo_content_path(data_path: String, folder_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    Ok(todo_folder_path(&data_path, &folder_name)?.join("content.json").to_string_lossy().into_owned())
+}
+
+/// 待办 assets 目录的绝对路径（目录可能尚未创建）
+#[tauri::command]
+fn get_todo_assets_path(data_path: String, folder_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    Ok(todo_folder_path(&data_path, &folder_name)?.join(assets_dir_name()).to_string_lossy().into_owned())
+}
+
+/// 先写入同目录下的临时文件再重命名覆盖，避免写入中途崩溃留下半截文件
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
+/// 执行写操作，失败时按 WRITE_RETRIES 重试，间隔从 100ms 起逐次翻倍（最长 1.6s）；重试仍失败时返回最后一次的错误
+fn with_write_retry<T, E: std::fmt::Display>(op: &str, mut write: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    let retries = WRITE_RETRIES.load(Ordering::Relaxed);
+    let mut attempt = 0;
+    loop {
+        match write() {
+            Err(e) if attempt < retries => {
+                write_log(LogLevel::Info, op, &format!("写入失败，第 {} 次重试: {}", attempt + 1, e));
+                std::thread::sleep(std::time::Duration::from_millis(100 << attempt.min(4)));
+                attempt += 1;
             }
+            result => return result,
         }
     }
-    total_size
 }
 
-#[tauri::command]
-fn find_orphan_todo_folders(data_path: String) -> Result<Vec<OrphanFolder>, String> {
-    let data_dir = Path::new(&data_path);
-    if !data_dir.exists() {
-        return Ok(vec![]);
+/// 开启 verify_writes 时读回 path，确认内容与 expected 一致且为合法 JSON
+fn verify_written_file(path: &Path, expected: &[u8]) -> Result<(), String> {
+    if !VERIFY_WRITES.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let actual = fs::read(path).map_err(|e| format!("写入校验失败，无法读回 {}: {}", path.display(), e))?;
+    if actual != expected {
+        return Err(format!("写入校验失败，读回内容与写入内容不一致: {}", path.display()));
     }
+    serde_json::from_slice::<serde_json::Value>(&actual)
+        .map_err(|e| format!("写入校验失败，读回内容无法解析 {}: {}", path.display(), e))?;
+    Ok(())
+}
 
-    // 获取所有有效的待办文件夹名
-    let todos = get_todos(data_path.clone());
-    let valid_folders: std::collections::HashSet<String> = todos
-        .iter()
-        .map(|todo| todo.folder_name.clone())
-        .collect();
+fn log_file_path(handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let config_dir = handle.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(config_dir.join("logs").join("app.log"))
+}
 
-    let mut orphan_folders = Vec::new();
+fn init_logging(handle: &tauri::AppHandle) {
+    if let Ok(mut state) = LOG_STATE.lock() {
+        state.path = log_file_path(handle).ok();
+    }
+}
 
-    // 扫描数据目录下的所有文件夹
-    if let Ok(entries) = fs::read_dir(data_dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_dir() {
-                    if let Some(folder_name) = path.file_name().and_then(|n| n.to_str()) {
-                        // 检查是否在有效文件夹列表中
-                        if !valid_folders.contains(folder_name) {
-                            let size = calculate_dir_size(&path);
-                            orphan_folders.push(OrphanFolder {
-                                folder_name: folder_name.to_string(),
-                                size,
-                            });
-                        }
+/// 将配置中影响后端运行时行为的项（日志级别、存储后端）同步到全局状态
+fn apply_runtime_config(config: &AppConfig) {
+    let level = LogLevel::parse(&config.log_level).unwrap_or(LogLevel::Error);
+    if let Ok(mut state) = LOG_STATE.lock() {
+        state.level = level;
+    }
+    USE_SQLITE.store(config.storage_backend == "sqlite", Ordering::Relaxed);
+    PRETTY_INDEX.store(config.pretty_index, Ordering::Relaxed);
+    AUTOLOCK_MINUTES.store(config.autolock_minutes as u64, Ordering::Relaxed);
+    AUTO_BACKUP_HOURS.store(config.auto_backup_interval_hours as u64, Ordering::Relaxed);
+    WRITE_RETRIES.store(config.write_retries as u64, Ordering::Relaxed);
+    VERIFY_WRITES.store(config.verify_writes, Ordering::Relaxed);
+    AUDIT_ENABLED.store(config.enable_audit, Ordering::Relaxed);
+    MAX_TITLE_LENGTH.store(config.max_title_length as u64, Ordering::Relaxed);
+    MAX_DETAIL_BYTES.store(config.max_detail_bytes, Ordering::Relaxed);
+    MAX_FOLDER_BYTES.store(config.max_folder_bytes, Ordering::Relaxed);
+    TRACK_ACCESS.store(config.track_access, Ordering::Relaxed);
+    ARCHIVE_YEAR_LAYOUT.store(config.archive_year_layout, Ordering::Relaxed);
+    USE_SYSTEM_ACCENT.store(config.use_system_accent, Ordering::Relaxed);
+    if let Ok(mut policy) = TAG_CASE_POLICY.lock() {
+        *policy = config.tag_case_policy.clone();
+    }
+    if let Ok(mut name) = ASSETS_DIR_NAME.lock() {
+        *name = config.assets_dir_name.trim().to_string();
+    }
+    if let Ok(mut path) = AUDIT_DATA_PATH.lock() {
+        *path = expand_data_path(&config.data_path);
+    }
+    if let Ok(mut hook) = POST_SAVE_HOOK.lock() {
+        *hook = config.post_save_hook.trim().to_string();
+    }
+}
+
+/// 追加一行日志；只记录操作名与错误类型，不记录任何笔记内容
+fn write_log(level: LogLevel, op: &str, message: &str) {
+    let state = match LOG_STATE.lock() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if level == LogLevel::Off || level > state.level {
+        return;
+    }
+    let path = match &state.path {
+        Some(p) => p,
+        None => return,
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if path.metadata().map(|m| m.len() > LOG_MAX_BYTES).unwrap_or(false) {
+        let _ = fs::rename(path, path.with_extension("log.1"));
+    }
+    if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(
+            file,
+            "{} [{}] {}: {}",
+            chrono::Local::now().to_rfc3339(),
+            level.label(),
+            op,
+            message
+        );
+    }
+}
+
+/// 数据目录下的审计日志，每行一条 JSON 记录，只追加不改写
+const AUDIT_LOG_FILE: &str = "audit.log";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct AuditEntry {
+    timestamp: String,
+    operation: String,
+    /// 命令参数中的 folderName（批量命令为 folderNames，以逗号分隔），没有时为空
+    #[serde(default)]
+    folder_name: Option<String>,
+}
+
+/// 开启 track_access 时，界面打开（get_todo_detail）或保存（save_todo_detail）详情后累加该待办 meta.json 中的计数；
+/// 只统计来自界面的调用，其他命令内部读写详情不计入
+fn record_access(command: &str, args: &serde_json::Value) {
+    if !TRACK_ACCESS.load(Ordering::Relaxed) || !matches!(command, "get_todo_detail" | "save_todo_detail") {
+        return;
+    }
+    let (Some(data_path), Some(folder_name)) = (
+        args.get("dataPath").and_then(|p| p.as_str()),
+        args.get("folderName").and_then(|f| f.as_str()),
+    ) else {
+        return;
+    };
+    let Ok(folder_name) = sanitize_folder_name(folder_name) else { return };
+    let folder = Path::new(&expand_data_path(data_path)).join(folder_name);
+    if !folder.is_dir() {
+        return;
+    }
+    let mut meta = read_todo_meta(&folder);
+    if command == "get_todo_detail" {
+        meta.open_count += 1;
+        meta.last_opened_at = Some(utc_now_rfc3339());
+    } else {
+        meta.edit_count += 1;
+    }
+    if let Err(e) = write_todo_meta(&folder, &meta) {
+        log_error("record_access", e);
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct AccessStat {
+    folder_name: String,
+    title: String,
+    open_count: u64,
+    edit_count: u64,
+    last_opened_at: Option<String>,
+}
+
+/// 按打开与编辑次数之和从高到低列出有记录的待办（需开启 track_access 才会累计）
+#[tauri::command]
+fn get_access_stats(data_path: String) -> Vec<AccessStat> {
+    let data_path = expand_data_path(&data_path);
+    let mut stats: Vec<AccessStat> = load_todos(&data_path)
+        .into_iter()
+        .filter_map(|todo| {
+            let meta = read_todo_meta(&Path::new(&data_path).join(&todo.folder_name));
+            (meta.open_count + meta.edit_count > 0).then_some(AccessStat {
+                folder_name: todo.folder_name,
+                title: todo.title,
+                open_count: meta.open_count,
+                edit_count: meta.edit_count,
+                last_opened_at: meta.last_opened_at,
+            })
+        })
+        .collect();
+    stats.sort_by_key(|s| std::cmp::Reverse(s.open_count + s.edit_count));
+    stats
+}
+
+/// 开启 enable_audit 时为修改类命令追加一条审计记录；只取命令名与参数中的文件夹名，不记录内容
+fn record_audit(operation: &str, args: &serde_json::Value) {
+    if !AUDIT_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let data_path = match args.get("dataPath").and_then(|p| p.as_str()) {
+        Some(p) => expand_data_path(p),
+        None => match AUDIT_DATA_PATH.lock() {
+            Ok(p) => p.clone(),
+            Err(_) => return,
+        },
+    };
+    if data_path.is_empty() {
+        return;
+    }
+    let folder_name = args.get("folderName").and_then(|f| f.as_str()).map(str::to_string).or_else(|| {
+        args.get("folderNames")
+            .and_then(|f| f.as_array())
+            .map(|names| names.iter().filter_map(|n| n.as_str()).collect::<Vec<_>>().join(","))
+    });
+    let entry = AuditEntry {
+        timestamp: utc_now_rfc3339(),
+        operation: operation.to_string(),
+        folder_name,
+    };
+    let Ok(line) = serde_json::to_string(&entry) else { return };
+    let path = Path::new(&data_path).join(AUDIT_LOG_FILE);
+    match fs::OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(mut file) => {
+            if let Err(e) = writeln!(file, "{}", line) {
+                log_error("record_audit", e);
+            }
+        }
+        Err(e) => {
+            log_error("record_audit", e);
+        }
+    }
+}
+
+/// 读取审计日志中最近的 limit 条记录，按时间从新到旧；无法解析的行跳过
+#[tauri::command]
+fn get_audit_log(data_path: String, limit: usize) -> Vec<AuditEntry> {
+    let data_path = expand_data_path(&data_path);
+    let content = fs::read_to_string(Path::new(&data_path).join(AUDIT_LOG_FILE)).unwrap_or_default();
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect()
+}
+
+/// 记录命令错误并原样返回，便于在 map_err 中使用
+fn log_error(op: &str, err: impl std::fmt::Display) -> String {
+    let message = err.to_string();
+    write_log(LogLevel::Error, op, &message);
+    message
+}
+
+#[tauri::command]
+fn get_log_path(handle: tauri::AppHandle) -> Result<String, String> {
+    Ok(log_file_path(&handle)?.to_string_lossy().into_owned())
+}
+
+#[tauri::command]
+fn open_log_file(handle: tauri::AppHandle) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+    let path = log_file_path(&handle)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    if !path.exists() {
+        fs::write(&path, "").map_err(|e| e.to_string())?;
+    }
+    handle
+        .opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .creation_flags(0x08000000)
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+            .unwrap_or(false)
+    }
+}
+
+/// 查询 path 所在文件系统的可用字节数（path 不存在时取最近的已存在上级目录）
+fn free_space(path: &Path) -> Result<u64, String> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("路径不存在: {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        let output = std::process::Command::new("df")
+            .arg("-Pk")
+            .arg(existing)
+            .output()
+            .map_err(|e| e.to_string())?;
+        // 输出第二行第四列为可用的 1K 块数
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .nth(1)
+            .and_then(|line| line.split_whitespace().nth(3))
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+            .ok_or_else(|| format!("无法获取可用空间: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // 路径经环境变量传入，避免拼接到命令中
+        let output = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", "(Get-Item -LiteralPath $env:SIMPLE_TODO_PATH).PSDrive.Free"])
+            .env("SIMPLE_TODO_PATH", existing)
+            .creation_flags(0x08000000)
+            .output()
+            .map_err(|e| e.to_string())?;
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| format!("无法获取可用空间: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// 读取系统强调色（#rrggbb）；Windows 读 DWM 的 AccentColor，macOS 读 AppleAccentColor，
+/// Linux 读 GNOME 的 accent-color，系统不提供时返回 None
+fn system_accent_color() -> Option<String> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let output = std::process::Command::new("reg")
+            .args(["query", r"HKCU\Software\Microsoft\Windows\DWM", "/v", "AccentColor"])
+            .creation_flags(0x08000000)
+            .output()
+            .ok()?;
+        // 值为 0xAABBGGRR
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let value = stdout.split_whitespace().find_map(|w| w.strip_prefix("0x"))?;
+        let abgr = u32::from_str_radix(value, 16).ok()?;
+        Some(format!("#{:02x}{:02x}{:02x}", abgr & 0xff, (abgr >> 8) & 0xff, (abgr >> 16) & 0xff))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("defaults")
+            .args(["read", "-g", "AppleAccentColor"])
+            .output()
+            .ok()?;
+        // 未设置该键时为默认的蓝色
+        let index = String::from_utf8_lossy(&output.stdout).trim().parse::<i32>().unwrap_or(4);
+        let color = match index {
+            -1 => "#8c8c8c",
+            0 => "#ff5257",
+            1 => "#f7821b",
+            2 => "#ffc600",
+            3 => "#62ba46",
+            5 => "#a550a7",
+            6 => "#f74f9e",
+            _ => "#007aff",
+        };
+        Some(color.to_string())
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let output = std::process::Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.interface", "accent-color"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())?;
+        let color = match String::from_utf8_lossy(&output.stdout).trim().trim_matches('\'') {
+            "blue" => "#3584e4",
+            "teal" => "#2190a4",
+            "green" => "#3a944a",
+            "yellow" => "#c88800",
+            "orange" => "#ed5b00",
+            "red" => "#e62d42",
+            "pink" => "#d56199",
+            "purple" => "#9141ac",
+            "slate" => "#6f8396",
+            _ => return None,
+        };
+        Some(color.to_string())
+    }
+}
+
+/// 返回系统强调色，系统不提供时为 null，界面此时使用配置中的强调色
+#[tauri::command]
+fn get_system_accent_color() -> Option<String> {
+    system_accent_color()
+}
+
+/// 开启 use_system_accent 时定期读取系统强调色，变化时发送 system-accent-changed 事件
+fn spawn_accent_watcher(handle: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut last = None;
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(10));
+            if !USE_SYSTEM_ACCENT.load(Ordering::Relaxed) {
+                last = None;
+                continue;
+            }
+            let color = system_accent_color();
+            if last.as_ref() != Some(&color) {
+                if last.is_some() {
+                    let _ = handle.emit("system-accent-changed", &color);
+                }
+                last = Some(color);
+            }
+        }
+    });
+}
+
+/// 返回数据目录所在文件系统的可用字节数，供界面在导入或添加附件前检查空间是否足够
+#[tauri::command]
+fn get_free_space(data_path: String) -> Result<u64, String> {
+    let data_path = expand_data_path(&data_path);
+    free_space(Path::new(&data_path)).map_err(|e| log_error("get_free_space", e))
+}
+
+/// 待办文件夹（详情与附件）当前占用的总字节数，供界面显示各笔记的空间占用
+#[tauri::command]
+fn get_folder_size(data_path: String, folder_name: String) -> Result<u64, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let folder = Path::new(&data_path).join(&folder_name);
+    if !folder.is_dir() {
+        return Err(format!("待办不存在: {}", folder_name));
+    }
+    Ok(calculate_dir_size(&folder))
+}
+
+/// 添加附件前检查：文件夹当前大小加上 incoming_bytes 超出 max_folder_bytes 时返回错误（含两者的大小）
+#[tauri::command]
+fn check_folder_quota(data_path: String, folder_name: String, incoming_bytes: u64) -> Result<(), String> {
+    let max = MAX_FOLDER_BYTES.load(Ordering::Relaxed);
+    if max == 0 {
+        return Ok(());
+    }
+    let used = get_folder_size(data_path, folder_name)?;
+    if used.saturating_add(incoming_bytes) > max {
+        return Err(format!(
+            "超出单个待办的空间上限：已用 {} 字节，添加 {} 字节后将超过上限 {} 字节",
+            used, incoming_bytes, max
+        ));
+    }
+    Ok(())
+}
+
+/// 获取数据目录的锁；已被其他存活进程持有时返回错误，持有者已退出（崩溃残留）时接管
+fn acquire_data_lock(data_path: &str) -> Result<(), String> {
+    let data_dir = Path::new(data_path);
+    fs::create_dir_all(data_dir).map_err(|e| log_error("acquire_data_lock", e))?;
+    let lock_path = data_dir.join(LOCK_FILE);
+    let own_pid = std::process::id();
+    for _ in 0..2 {
+        match fs::OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+            Ok(mut file) => {
+                file.write_all(own_pid.to_string().as_bytes())
+                    .map_err(|e| log_error("acquire_data_lock", e))?;
+                if let Ok(mut held) = DATA_LOCK.lock() {
+                    *held = Some(lock_path);
+                }
+                return Ok(());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let holder = fs::read_to_string(&lock_path)
+                    .ok()
+                    .and_then(|c| c.trim().parse::<u32>().ok());
+                match holder {
+                    Some(pid) if pid == own_pid => {
+                        if let Ok(mut held) = DATA_LOCK.lock() {
+                            *held = Some(lock_path);
+                        }
+                        return Ok(());
+                    }
+                    Some(pid) if process_alive(pid) => {
+                        return Err(format!(
+                            "应用已在运行，数据目录正被其他实例使用（进程 {}）: {}",
+                            pid, data_path
+                        ));
                     }
+                    _ => {
+                        write_log(LogLevel::Info, "acquire_data_lock", "清理残留的锁文件");
+                        let _ = fs::remove_file(&lock_path);
+                    }
+                }
+            }
+            Err(e) => return Err(log_error("acquire_data_lock", e)),
+        }
+    }
+    Err(format!("无法获取数据目录锁: {}", data_path))
+}
+
+/// 释放当前进程持有的锁（仅删除内容为本进程号的锁文件）
+fn release_data_lock() {
+    if let Some(lock_path) = DATA_LOCK.lock().ok().and_then(|mut held| held.take()) {
+        let own = fs::read_to_string(&lock_path)
+            .map(|c| c.trim() == std::process::id().to_string())
+            .unwrap_or(false);
+        if own {
+            let _ = fs::remove_file(lock_path);
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct AutostartSupport {
+    supported: bool,
+    /// 不支持时的原因
+    reason: Option<String>,
+}
+
+/// 检查当前平台能否开机自启：Linux 上插件依赖 XDG 自启动目录，在沙箱或无桌面环境时不起作用
+#[tauri::command]
+fn is_autostart_supported(app: tauri::AppHandle) -> AutostartSupport {
+    use tauri_plugin_autostart::ManagerExt;
+    let unsupported = |reason: String| AutostartSupport { supported: false, reason: Some(reason) };
+    if cfg!(target_os = "linux") {
+        if std::env::var_os("FLATPAK_ID").is_some() {
+            return unsupported("Flatpak 沙箱中无法写入系统自启动项".to_string());
+        }
+        if std::env::var_os("XDG_CURRENT_DESKTOP").is_none() && std::env::var_os("DESKTOP_SESSION").is_none() {
+            return unsupported("未检测到支持 XDG 自启动的桌面环境".to_string());
+        }
+        let autostart_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|h| Path::new(&h).join(".config")))
+            .map(|d| d.join("autostart"));
+        match autostart_dir {
+            Some(dir) => {
+                if let Err(e) = fs::create_dir_all(&dir) {
+                    return unsupported(format!("自启动目录不可写 ({}): {}", dir.display(), e));
                 }
             }
+            None => return unsupported("无法确定自启动目录".to_string()),
+        }
+    }
+    if let Err(e) = app.autolaunch().is_enabled() {
+        return unsupported(format!("无法读取自启动状态: {}", e));
+    }
+    AutostartSupport { supported: true, reason: None }
+}
+
+/// 本次运行中是否暂停了开机自启（不写入配置，重启应用后恢复为配置中的设置）
+static AUTOSTART_SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// 临时取消开机自启的系统注册，配置中的 launch_at_login 保持不变
+#[tauri::command]
+fn suspend_autostart(app: tauri::AppHandle) -> Result<(), String> {
+    use tauri_plugin_autostart::ManagerExt;
+    AUTOSTART_SUSPENDED.store(true, Ordering::Relaxed);
+    let launcher = app.autolaunch();
+    if launcher.is_enabled().map_err(|e| e.to_string())? {
+        launcher.disable().map_err(|e| log_error("suspend_autostart", e))?;
+    }
+    Ok(())
+}
+
+/// 结束暂停，按配置中的 launch_at_login 重新注册或取消开机自启，返回当前是否已注册
+#[tauri::command]
+fn resume_autostart(app: tauri::AppHandle) -> Result<bool, String> {
+    use tauri_plugin_autostart::ManagerExt;
+    AUTOSTART_SUSPENDED.store(false, Ordering::Relaxed);
+    let wanted = get_app_config(app.clone()).launch_at_login;
+    let launcher = app.autolaunch();
+    let enabled = launcher.is_enabled().map_err(|e| e.to_string())?;
+    if wanted && !enabled {
+        launcher.enable().map_err(|e| log_error("resume_autostart", e))?;
+    } else if !wanted && enabled {
+        launcher.disable().map_err(|e| log_error("resume_autostart", e))?;
+    }
+    Ok(wanted)
+}
+
+#[tauri::command]
+fn is_autostart_suspended() -> bool {
+    AUTOSTART_SUSPENDED.load(Ordering::Relaxed)
+}
+
+/// 列出系统中已安装的字体族名（去重并按字母排序），供设置中的字体下拉框使用
+#[tauri::command]
+async fn list_system_fonts() -> Vec<String> {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+    let families: std::collections::BTreeSet<String> = db
+        .faces()
+        .filter_map(|face| face.families.first().map(|(name, _)| name.trim().to_string()))
+        .filter(|name| !name.is_empty() && !name.starts_with('.'))
+        .collect();
+    families.into_iter().collect()
+}
+
+/// 应用锁密码，存放在配置目录的 lock.json 中，不随 AppConfig 传给前端
+#[derive(Serialize, Deserialize, Debug)]
+struct AppLock {
+    /// 旧版 SHA-256 哈希的盐；Argon2id 哈希（PHC 字符串）自带盐，此项为空
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    salt: String,
+    hash: String,
+}
+
+fn app_lock_path(handle: &tauri::AppHandle) -> PathBuf {
+    handle.path().app_config_dir().unwrap().join("lock.json")
+}
+
+fn read_app_lock(handle: &tauri::AppHandle) -> Option<AppLock> {
+    let content = fs::read_to_string(app_lock_path(handle)).ok()?;
+    serde_json::from_str(strip_bom(&content)).ok()
+}
+
+/// 用 Argon2id 计算密码哈希，返回带参数与盐的 PHC 字符串
+fn hash_lock_password(password: &str) -> Result<String, String> {
+    use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+    argon2::Argon2::default()
+        .hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// 校验密码（比较为常数时间）；兼容旧版 lock.json 中的 SHA-256(盐 + 密码)
+fn verify_lock_password(lock: &AppLock, password: &str) -> bool {
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+    if lock.salt.is_empty() {
+        return PasswordHash::new(&lock.hash)
+            .is_ok_and(|hash| argon2::Argon2::default().verify_password(password.as_bytes(), &hash).is_ok());
+    }
+    use sha2::{Digest, Sha256};
+    use subtle::ConstantTimeEq;
+    let mut hasher = Sha256::new();
+    hasher.update(lock.salt.as_bytes());
+    hasher.update(password.as_bytes());
+    let legacy: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    legacy.as_bytes().ct_eq(lock.hash.as_bytes()).into()
+}
+
+fn write_app_lock(handle: &tauri::AppHandle, password: &str) -> Result<(), String> {
+    let path = app_lock_path(handle);
+    let lock = AppLock { salt: String::new(), hash: hash_lock_password(password)? };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string(&lock).map_err(|e| e.to_string())?;
+    write_atomic(&path, content.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn lock_now(handle: &tauri::AppHandle) {
+    if !APP_LOCKED.swap(true, Ordering::SeqCst) {
+        let _ = handle.emit("lock", ());
+    }
+}
+
+/// 设置或清除应用锁密码；已设置时需提供正确的当前密码，password 为空表示清除
+#[tauri::command]
+fn set_app_lock(handle: tauri::AppHandle, current: Option<String>, password: Option<String>) -> Result<(), String> {
+    if let Some(lock) = read_app_lock(&handle) {
+        if !verify_lock_password(&lock, &current.unwrap_or_default()) {
+            return Err("当前密码不正确".to_string());
+        }
+    }
+    let path = app_lock_path(&handle);
+    match password.filter(|p| !p.is_empty()) {
+        Some(password) => write_app_lock(&handle, &password).map_err(|e| log_error("set_app_lock", e)),
+        None => {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| log_error("set_app_lock", e))?;
+            }
+            APP_LOCKED.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+}
+
+/// 校验密码，正确时解锁并重新开始计算空闲时间
+#[tauri::command]
+fn verify_app_lock(handle: tauri::AppHandle, password: String) -> bool {
+    let ok = match read_app_lock(&handle) {
+        Some(lock) => {
+            let ok = verify_lock_password(&lock, &password);
+            // 旧版哈希校验通过后改存为 Argon2id
+            if ok && !lock.salt.is_empty() {
+                if let Err(e) = write_app_lock(&handle, &password) {
+                    log_error("verify_app_lock", e);
+                }
+            }
+            ok
+        }
+        None => true,
+    };
+    if ok {
+        APP_LOCKED.store(false, Ordering::SeqCst);
+        LAST_ACTIVITY.store(unix_now(), Ordering::Relaxed);
+    }
+    ok
+}
+
+/// 手动锁定（未设置应用锁时无效）
+#[tauri::command]
+fn lock_app(handle: tauri::AppHandle) -> bool {
+    if read_app_lock(&handle).is_none() {
+        return false;
+    }
+    lock_now(&handle);
+    true
+}
+
+#[tauri::command]
+fn is_app_locked() -> bool {
+    APP_LOCKED.load(Ordering::SeqCst)
+}
+
+#[tauri::command]
+fn has_app_lock(handle: tauri::AppHandle) -> bool {
+    read_app_lock(&handle).is_some()
+}
+
+/// 后台检查空闲时间，超过 autolock_minutes 后锁定应用
+fn spawn_autolock_watcher(handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(15));
+        let minutes = AUTOLOCK_MINUTES.load(Ordering::Relaxed);
+        if minutes == 0 || APP_LOCKED.load(Ordering::SeqCst) || read_app_lock(&handle).is_none() {
+            continue;
+        }
+        let idle = unix_now().saturating_sub(LAST_ACTIVITY.load(Ordering::Relaxed));
+        if idle >= minutes * 60 {
+            lock_now(&handle);
+        }
+    });
+}
+
+/// 后台按 auto_backup_interval_hours 自动备份数据目录，距最近一次自动备份已满间隔时执行，并按 max_backups 清理旧的自动备份
+fn spawn_auto_backup(handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(60));
+        let hours = AUTO_BACKUP_HOURS.load(Ordering::Relaxed);
+        if hours == 0 {
+            continue;
+        }
+        let config = get_app_config(handle.clone());
+        let data_path = expand_data_path(&config.data_path);
+        let due = auto_backups(&handle, &data_path)
+            .last()
+            .is_none_or(|(_, time)| (chrono::Utc::now() - *time).num_seconds() >= (hours * 3600) as i64);
+        if !due {
+            continue;
+        }
+        match create_backup(&handle, &data_path, AUTO_BACKUP_SUFFIX) {
+            Ok(name) => write_log(LogLevel::Info, "auto_backup", &format!("已创建自动备份 {}", name)),
+            Err(e) => {
+                log_error("auto_backup", e);
+                continue;
+            }
+        }
+        if config.max_backups > 0 {
+            let backups = auto_backups(&handle, &data_path);
+            let excess = backups.len().saturating_sub(config.max_backups);
+            for (path, _) in backups.into_iter().take(excess) {
+                if let Err(e) = remove_path(&path) {
+                    log_error("auto_backup", e);
+                }
+            }
+        }
+    });
+}
+
+/// 当前使用的命名配置，为空时使用默认的 config.json
+static ACTIVE_CONFIG: Mutex<String> = Mutex::new(String::new());
+/// 默认配置（config.json）在 list_configs 等命令中的名称
+const DEFAULT_CONFIG_NAME: &str = "default";
+
+/// 校验配置名称（字母、数字、- 与 _），default 或空字符串表示默认配置，返回空字符串
+fn normalize_config_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name == DEFAULT_CONFIG_NAME {
+        return Ok(String::new());
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("无效的配置名称: {}", name));
+    }
+    Ok(name.to_string())
+}
+
+/// 当前配置文件的路径：默认为 config.json，命名配置为 config-<name>.json
+fn config_path(handle: &tauri::AppHandle) -> PathBuf {
+    let name = ACTIVE_CONFIG.lock().map(|n| n.clone()).unwrap_or_default();
+    let file_name = if name.is_empty() { "config.json".to_string() } else { format!("config-{}.json", name) };
+    handle.path().app_config_dir().unwrap().join(file_name)
+}
+
+/// 从命令行参数中找出 --config=<name> 或 --config <name>
+fn find_config_arg(args: &[String]) -> Option<String> {
+    args.iter().enumerate().find_map(|(i, arg)| match arg.strip_prefix("--config") {
+        Some(rest) if rest.starts_with('=') => Some(rest[1..].to_string()),
+        Some("") => args.get(i + 1).cloned(),
+        _ => None,
+    })
+}
+
+/// 列出配置目录中的配置：default（config.json）在前，其余为 config-<name>.json 的名称
+#[tauri::command]
+fn list_configs(handle: tauri::AppHandle) -> Vec<String> {
+    let mut names: Vec<String> = handle
+        .path()
+        .app_config_dir()
+        .ok()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let name = file_name.strip_prefix("config-")?.strip_suffix(".json")?;
+            normalize_config_name(name).ok().filter(|n| !n.is_empty())
+        })
+        .collect();
+    names.sort();
+    names.insert(0, DEFAULT_CONFIG_NAME.to_string());
+    names
+}
+
+/// 返回当前使用的配置名称
+#[tauri::command]
+fn get_active_config() -> String {
+    let name = ACTIVE_CONFIG.lock().map(|n| n.clone()).unwrap_or_default();
+    if name.is_empty() { DEFAULT_CONFIG_NAME.to_string() } else { name }
+}
+
+/// 切换到命名配置（不存在时以默认值开始，保存后写入 config-<name>.json），立即生效并通知界面刷新；
+/// 仅对本次运行有效，下次启动仍使用 --config 指定的或默认的配置
+#[tauri::command]
+fn set_active_config(handle: tauri::AppHandle, name: String) -> Result<AppConfig, String> {
+    let name = normalize_config_name(&name)?;
+    let previous = {
+        let mut active = ACTIVE_CONFIG.lock().map_err(|e| e.to_string())?;
+        std::mem::replace(&mut *active, name)
+    };
+    let config = get_app_config(handle.clone());
+    if let Err(e) = transfer_data_lock(&config) {
+        if let Ok(mut active) = ACTIVE_CONFIG.lock() {
+            *active = previous;
+        }
+        return Err(log_error("set_active_config", e));
+    }
+    apply_runtime_config(&config);
+    let _ = handle.emit("config-changed", ());
+    Ok(config)
+}
+
+#[tauri::command]
+fn get_app_config(handle: tauri::AppHandle) -> AppConfig {
+    let config_path = config_path(&handle);
+    if !config_path.exists() {
+        return default_config(&handle);
+    }
+    let content = match fs::read_to_string(&config_path) {
+        Ok(c) => c,
+        Err(_) => return default_config(&handle),
+    };
+    serde_json::from_str(strip_bom(&content)).unwrap_or_else(|_| default_config(&handle))
+}
+
+/// 保存配置前确认设置的备份目录存在（不存在时创建）且可写
+fn check_backup_path_writable(config: &AppConfig) -> Result<(), String> {
+    if config.backup_path.trim().is_empty() {
+        return Ok(());
+    }
+    let dir = PathBuf::from(expand_data_path(config.backup_path.trim()));
+    let probe = dir.join(".write-test");
+    fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&probe, b""))
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|e| format!("备份目录不可写 ({}): {}", dir.display(), e))
+}
+
+/// 校验配置中各字段的取值（只检查取值，不读写磁盘），保存与重新加载配置时共用
+fn validate_config(config: &AppConfig) -> Result<(), String> {
+    validate_exclude_patterns(&config.move_exclude)?;
+    if LogLevel::parse(&config.log_level).is_none() {
+        return Err(format!("无效的日志级别: {}", config.log_level));
+    }
+    if !matches!(config.storage_backend.as_str(), "json" | "sqlite") {
+        return Err(format!("无效的存储后端: {}", config.storage_backend));
+    }
+    if !KNOWN_STATUSES.contains(&config.default_status.as_str()) {
+        return Err(format!("未知的默认状态: {}", config.default_status));
+    }
+    if !matches!(config.tag_case_policy.as_str(), "preserve" | "lower" | "title") {
+        return Err(format!("无效的标签大小写规则: {}", config.tag_case_policy));
+    }
+    if !matches!(config.line_ending.as_str(), "lf" | "crlf") {
+        return Err(format!("无效的换行符设置: {}", config.line_ending));
+    }
+    if let Some((tag, color)) = config.tag_colors.iter().find(|(_, c)| !is_hex_color(c)) {
+        return Err(format!("标签 {} 的颜色无效: {}", tag, color));
+    }
+    for (status, color) in &config.status_colors {
+        if !KNOWN_STATUSES.contains(&status.as_str()) {
+            return Err(format!("未知的状态: {}", status));
+        }
+        if !is_hex_color(color) {
+            return Err(format!("状态 {} 的颜色无效: {}", status, color));
+        }
+    }
+    if config.export_compression > 9 {
+        return Err(format!("压缩级别须在 0-9 之间: {}", config.export_compression));
+    }
+    if config.write_retries > 10 {
+        return Err(format!("写入重试次数须在 0-10 之间: {}", config.write_retries));
+    }
+    let assets_name = config.assets_dir_name.trim();
+    if sanitize_folder_name(assets_name).is_err() || assets_name.starts_with('.') || assets_name == BACKUPS_DIR {
+        return Err(format!("无效的附件目录名: {}", config.assets_dir_name));
+    }
+    if !(1..=12).contains(&config.cards_per_page) {
+        return Err(format!("每页卡片数须在 1-12 之间: {}", config.cards_per_page));
+    }
+    for weight in [config.urgency_due_weight, config.urgency_priority_weight, config.urgency_age_weight] {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(format!("紧迫度权重须为非负数: {}", weight));
+        }
+    }
+    if !config.quick_capture_hotkey.is_empty() {
+        normalize_hotkey(&config.quick_capture_hotkey)?;
+    }
+    if let Some(rules) = &config.allowed_transitions {
+        for (from, targets) in rules {
+            if let Some(unknown) = std::iter::once(from).chain(targets).find(|s| !KNOWN_STATUSES.contains(&s.as_str())) {
+                return Err(format!("状态流转规则中有未知状态: {}", unknown));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 按 allowed_transitions 检查状态能否从 from 转为 to；未配置规则或状态不变时总是允许
+fn check_status_transition(config: &AppConfig, from: &str, to: &str) -> Result<(), String> {
+    let Some(rules) = &config.allowed_transitions else { return Ok(()) };
+    if from == to || rules.get(from).is_some_and(|targets| targets.iter().any(|t| t == to)) {
+        return Ok(());
+    }
+    Err(format!("不允许将状态从 {} 改为 {}", from, to))
+}
+
+/// 按 wip_limit 检查能否将待办从 from 改为进行中；已达上限时返回含当前数量与上限的错误
+fn check_wip_limit(config: &AppConfig, todos: &[TodoItem], from: &str, to: &str) -> Result<(), String> {
+    if config.wip_limit == 0 || to != STATUS_IN_PROGRESS || from == to {
+        return Ok(());
+    }
+    let count = todos.iter().filter(|t| t.status == STATUS_IN_PROGRESS && !t.archived).count();
+    if count >= config.wip_limit {
+        return Err(format!("进行中的待办已达上限（{}/{}），请先完成或移出其他进行中的待办", count, config.wip_limit));
+    }
+    Ok(())
+}
+
+/// 拒绝位于配置目录内（或与其相同）的数据目录：迁移数据、清理孤立文件夹等逻辑都假定两者互不包含。
+/// 平台默认的数据目录例外（Windows 与 macOS 上它与配置目录相同），迁移时已跳过 config.json
+fn check_data_path_outside_config(handle: &tauri::AppHandle, data_path: &str) -> Result<(), String> {
+    let resolve = |p: PathBuf| fs::canonicalize(&p).unwrap_or(p);
+    let data_dir = resolve(PathBuf::from(expand_data_path(data_path)));
+    if handle.path().app_data_dir().is_ok_and(|d| resolve(d) == data_dir) {
+        return Ok(());
+    }
+    let config_dir = resolve(handle.path().app_config_dir().map_err(|e| e.to_string())?);
+    if data_dir.starts_with(&config_dir) {
+        return Err(format!(
+            "数据目录不能位于应用配置目录 ({}) 内，否则配置文件会与待办数据混在一起，迁移和清理时可能出错，请选择其他目录",
+            config_dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// 数据目录变更时将锁转移到新目录
+fn transfer_data_lock(config: &AppConfig) -> Result<(), String> {
+    let new_lock = Path::new(&expand_data_path(&config.data_path)).join(LOCK_FILE);
+    let held = DATA_LOCK.lock().ok().and_then(|held| held.clone());
+    if held.as_deref() != Some(new_lock.as_path()) {
+        release_data_lock();
+        acquire_data_lock(&expand_data_path(&config.data_path))?;
+    }
+    Ok(())
+}
+
+/// 只修改 patch（JSON 对象）中给出的配置项：合并到当前配置上，校验后保存，未给出的项保持不变。
+/// 含未知配置项时拒绝，返回保存后的完整配置
+#[tauri::command]
+fn save_config_partial(handle: tauri::AppHandle, patch: serde_json::Value) -> Result<AppConfig, String> {
+    let serde_json::Value::Object(patch) = patch else { return Err("配置修改须为 JSON 对象".to_string()) };
+    let mut config = serde_json::to_value(get_app_config(handle.clone())).map_err(|e| e.to_string())?;
+    let fields = config.as_object_mut().ok_or("配置格式错误")?;
+    if let Some(unknown) = patch.keys().find(|key| !fields.contains_key(*key)) {
+        return Err(format!("未知的配置项: {}", unknown));
+    }
+    fields.extend(patch);
+    let config: AppConfig = serde_json::from_value(config).map_err(|e| format!("配置无效: {}", e))?;
+    save_app_config(handle.clone(), config)?;
+    Ok(get_app_config(handle))
+}
+
+/// 校验并保存配置，返回不阻止保存、但需提示用户的警告（如语言已回退、文字对比度不足）
+#[tauri::command]
+fn save_app_config(handle: tauri::AppHandle, mut config: AppConfig) -> Result<Vec<String>, String> {
+    validate_config(&config)?;
+    check_backup_path_writable(&config)?;
+    check_data_path_outside_config(&handle, &config.data_path)?;
+    sanitize_theme_colors(&mut config);
+    let mut warnings: Vec<String> = sanitize_language(&mut config).into_iter().collect();
+    warnings.extend(low_contrast_warnings(&config));
+    transfer_data_lock(&config)?;
+    let config_dir = handle.path().app_config_dir().unwrap();
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir).map_err(|e| log_error("save_app_config", e))?;
+    }
+    let config_path = config_path(&handle);
+    let content = serde_json::to_string(&config).map_err(|e| log_error("save_app_config", e))?;
+    with_write_retry("save_app_config", || write_atomic(&config_path, content.as_bytes()))
+        .map_err(|e| log_error("save_app_config", e))?;
+    apply_runtime_config(&config);
+    let _ = handle.emit("config-changed", ());
+    Ok(warnings)
+}
+
+/// 将配置恢复为默认值，只保留原有的 data_path 与 launch_at_login。
+/// 直接从 config.json 中读取这两项，使配置文件已无法完整解析时也能保住数据目录；返回恢复后的配置
+#[tauri::command]
+fn reset_config_keep_data_path(handle: tauri::AppHandle) -> Result<AppConfig, String> {
+    let current = get_app_config(handle.clone());
+    let raw: serde_json::Value = fs::read_to_string(config_path(&handle))
+        .ok()
+        .and_then(|c| serde_json::from_str(strip_bom(&c)).ok())
+        .unwrap_or_default();
+    let mut config = default_config(&handle);
+    config.data_path = raw.get("data_path").and_then(|p| p.as_str()).map(str::to_string).unwrap_or(current.data_path);
+    config.launch_at_login = raw.get("launch_at_login").and_then(|l| l.as_bool()).unwrap_or(current.launch_at_login);
+    save_app_config(handle.clone(), config).map_err(|e| log_error("reset_config_keep_data_path", e))?;
+    Ok(get_app_config(handle))
+}
+
+/// 切换界面语言并保存配置（界面收到 config-changed 后立即重新渲染），返回支持的语言列表
+#[tauri::command]
+fn set_language(handle: tauri::AppHandle, lang: String) -> Result<Vec<String>, String> {
+    if !SUPPORTED_LANGUAGES.contains(&lang.as_str()) {
+        return Err(format!("不支持的语言: {}", lang));
+    }
+    let mut config = get_app_config(handle.clone());
+    config.language = lang;
+    save_app_config(handle, config)?;
+    Ok(SUPPORTED_LANGUAGES.iter().map(|l| l.to_string()).collect())
+}
+
+/// 从磁盘重新读取 config.json（例如手动编辑之后），校验通过后立即生效并通知界面刷新；
+/// 文件无效时返回错误，不会退回默认配置
+#[tauri::command]
+fn reload_config(handle: tauri::AppHandle) -> Result<AppConfig, String> {
+    let config_path = config_path(&handle);
+    let content = fs::read_to_string(&config_path).map_err(|e| log_error("reload_config", e))?;
+    let mut config: AppConfig =
+        serde_json::from_str(strip_bom(&content)).map_err(|e| log_error("reload_config", format!("config.json 无效: {}", e)))?;
+    validate_config(&config)?;
+    check_data_path_outside_config(&handle, &config.data_path)?;
+    sanitize_theme_colors(&mut config);
+    if let Some(warning) = sanitize_language(&mut config) {
+        write_log(LogLevel::Error, "reload_config", &warning);
+    }
+    transfer_data_lock(&config)?;
+    apply_runtime_config(&config);
+    let _ = handle.emit("config-changed", ());
+    Ok(config)
+}
+
+#[derive(Serialize, Debug)]
+struct StaleDataPath {
+    data_path: String,
+    exists: bool,
+    /// 配置的目录不存在时建议改用的平台默认数据目录
+    suggested: Option<String>,
+}
+
+/// 检查配置中的 data_path 是否存在（例如整个应用目录被复制到另一台机器后仍指向旧路径）
+#[tauri::command]
+fn detect_stale_data_path(handle: tauri::AppHandle) -> StaleDataPath {
+    let data_path = get_app_config(handle.clone()).data_path;
+    let exists = Path::new(&expand_data_path(&data_path)).is_dir();
+    let suggested = if exists { None } else { Some(default_config(&handle).data_path) };
+    StaleDataPath { data_path, exists, suggested }
+}
+
+#[derive(Serialize, Debug)]
+struct DirectoryReport {
+    ok: bool,
+    config_dir: String,
+    /// 展开后的数据目录
+    data_dir: String,
+    /// 两个目录相同或互相包含
+    overlap: bool,
+    config_writable: bool,
+    data_writable: bool,
+    issues: Vec<String>,
+}
+
+/// 目录不存在时创建，并写入临时文件确认可写
+fn probe_writable(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建目录 {}: {}", dir.display(), e))?;
+    let probe = dir.join(".write-test");
+    write_atomic(&probe, b"").map_err(|e| format!("目录不可写 {}: {}", dir.display(), e))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+/// 检查配置目录与数据目录：两者都存在（或可创建）且可写，并且互不重叠。
+/// 数据目录即平台默认数据目录、与配置目录相同时不视为问题（Windows 与 macOS 上默认如此，迁移时会跳过配置文件）
+#[tauri::command]
+fn verify_directories(handle: tauri::AppHandle) -> Result<DirectoryReport, String> {
+    let resolve = |p: PathBuf| fs::canonicalize(&p).unwrap_or(p);
+    let config_dir = handle.path().app_config_dir().map_err(|e| log_error("verify_directories", e))?;
+    let data_dir = PathBuf::from(expand_data_path(&get_app_config(handle.clone()).data_path));
+    let mut issues = Vec::new();
+    let config_writable = probe_writable(&config_dir).map_err(|e| issues.push(format!("配置目录: {}", e))).is_ok();
+    let data_writable = probe_writable(&data_dir).map_err(|e| issues.push(format!("数据目录: {}", e))).is_ok();
+    let (config_real, data_real) = (resolve(config_dir.clone()), resolve(data_dir.clone()));
+    let overlap = data_real.starts_with(&config_real) || config_real.starts_with(&data_real);
+    let default_layout = data_real == config_real && handle.path().app_data_dir().is_ok_and(|d| resolve(d) == data_real);
+    if overlap && !default_layout {
+        issues.push(format!(
+            "数据目录 ({}) 与配置目录 ({}) 相互包含，迁移数据与备份时可能覆盖配置文件，请选择其他数据目录",
+            data_real.display(),
+            config_real.display()
+        ));
+    }
+    Ok(DirectoryReport {
+        ok: issues.is_empty(),
+        config_dir: config_real.to_string_lossy().to_string(),
+        data_dir: data_real.to_string_lossy().to_string(),
+        overlap,
+        config_writable,
+        data_writable,
+        issues,
+    })
+}
+
+#[derive(Serialize, Debug)]
+struct StartupReport {
+    ok: bool,
+    /// 展开后的数据目录
+    data_path: String,
+    issues: Vec<String>,
+}
+
+/// 启动时调用一次：加载并校验配置，检查配置目录与数据目录（见 verify_directories）、快速记录快捷键是否已注册，
+/// 再检查待办索引能否读取，汇总发现的问题
+#[tauri::command]
+fn init_workspace(handle: tauri::AppHandle) -> StartupReport {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+    let mut issues = Vec::new();
+    {
+        if let Ok(content) = fs::read_to_string(config_path(&handle)) {
+            if let Err(e) = serde_json::from_str::<AppConfig>(strip_bom(&content)) {
+                issues.push(format!("配置文件解析失败，已使用默认配置: {}", e));
+            }
+        }
+    }
+    let config = get_app_config(handle.clone());
+    if let Err(e) = validate_config(&config) {
+        issues.push(e);
+    }
+    let hotkey = &config.quick_capture_hotkey;
+    if !hotkey.is_empty() && !handle.global_shortcut().is_registered(hotkey.as_str()) {
+        issues.push(format!("快速记录快捷键 {} 注册失败，可能已被其他应用占用，请在设置中改选其他组合", hotkey));
+    }
+    let data_path = expand_data_path(&config.data_path);
+    let data_dir = Path::new(&data_path);
+    // 目录布局有问题时先提示，再进行任何数据操作
+    let data_usable = match verify_directories(handle.clone()) {
+        Ok(report) => {
+            issues.extend(report.issues);
+            report.data_writable
+        }
+        Err(e) => {
+            issues.push(e);
+            data_dir.is_dir()
+        }
+    };
+    if data_usable {
+        let todos_path = data_dir.join("todos.json");
+        if config.storage_backend == "sqlite" && data_dir.join(SQLITE_FILE).exists() {
+            if let Err(e) = read_todos_sqlite(data_dir) {
+                issues.push(format!("{} 读取失败: {}", SQLITE_FILE, e));
+            }
+        } else if let Ok(content) = fs::read_to_string(&todos_path) {
+            if let Err(e) = serde_json::from_str::<Vec<TodoItem>>(strip_bom(&content)) {
+                issues.push(format!("todos.json 解析失败: {}", e));
+            }
+        }
+    }
+    for issue in &issues {
+        write_log(LogLevel::Error, "init_workspace", issue);
+    }
+    StartupReport { ok: issues.is_empty(), data_path, issues }
+}
+
+/// 配置的 data_path 不存在时改为平台默认数据目录并保存配置，返回修复后的路径；目录存在时不做修改
+#[tauri::command]
+fn repair_data_path(handle: tauri::AppHandle) -> Result<String, String> {
+    let mut config = get_app_config(handle.clone());
+    if Path::new(&expand_data_path(&config.data_path)).is_dir() {
+        return Ok(config.data_path);
+    }
+    config.data_path = default_config(&handle).data_path;
+    fs::create_dir_all(&config.data_path).map_err(|e| log_error("repair_data_path", e))?;
+    let data_path = config.data_path.clone();
+    save_app_config(handle, config)?;
+    write_log(LogLevel::Info, "repair_data_path", &format!("数据目录已重置为 {}", data_path));
+    Ok(data_path)
+}
+
+/// 与本机相关、不随设置导出的配置项；post_save_hook 会执行命令，也不允许从文件导入
+const MACHINE_CONFIG_KEYS: &[&str] = &["data_path", "backup_path", "post_save_hook"];
+
+/// 将可在不同设备间共享的设置（主题、字体、语言等）导出为 JSON 文件，不含数据目录等本机路径
+#[tauri::command]
+fn export_settings(handle: tauri::AppHandle, dest_path: String) -> Result<(), String> {
+    let mut value = serde_json::to_value(get_app_config(handle)).map_err(|e| e.to_string())?;
+    if let Some(map) = value.as_object_mut() {
+        map.retain(|key, _| !MACHINE_CONFIG_KEYS.contains(&key.as_str()));
+    }
+    let content = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    write_atomic(Path::new(&expand_data_path(&dest_path)), content.as_bytes())
+        .map_err(|e| log_error("export_settings", e))
+}
+
+#[derive(Serialize, Debug, Default)]
+struct SettingsImportReport {
+    applied: Vec<String>,
+    /// 未知或取值无效而被忽略的字段
+    rejected: Vec<String>,
+}
+
+/// 将 export_settings 导出的设置逐项合并到当前配置：本机相关的项与无效的项被忽略，合并结果通过校验后保存
+#[tauri::command]
+fn import_settings(handle: tauri::AppHandle, src_path: String) -> Result<SettingsImportReport, String> {
+    let content = fs::read_to_string(expand_data_path(&src_path)).map_err(|e| log_error("import_settings", e))?;
+    let imported: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(strip_bom(&content)).map_err(|e| format!("设置文件无效: {}", e))?;
+    let mut merged = serde_json::to_value(get_app_config(handle.clone())).map_err(|e| e.to_string())?;
+    let mut report = SettingsImportReport::default();
+    for (key, value) in imported {
+        let known = merged.get(&key).is_some() && !MACHINE_CONFIG_KEYS.contains(&key.as_str());
+        let mut candidate = merged.clone();
+        candidate[&key] = value;
+        let valid = known
+            && serde_json::from_value::<AppConfig>(candidate.clone())
+                .is_ok_and(|config| validate_config(&config).is_ok());
+        if valid {
+            merged = candidate;
+            report.applied.push(key);
+        } else {
+            report.rejected.push(key);
+        }
+    }
+    let config: AppConfig = serde_json::from_value(merged).map_err(|e| e.to_string())?;
+    save_app_config(handle, config)?;
+    Ok(report)
+}
+
+/// 外观预设包含的配置项
+const PRESET_CONFIG_KEYS: &[&str] = &[
+    "theme",
+    "font_family",
+    "font_size",
+    "text_color_light",
+    "text_color_dark",
+    "accent_color_light",
+    "accent_color_dark",
+    "bg_color_light",
+    "bg_color_dark",
+    "border_color_light",
+    "border_color_dark",
+];
+
+type ConfigPresets = std::collections::BTreeMap<String, serde_json::Map<String, serde_json::Value>>;
+
+fn presets_path(handle: &tauri::AppHandle) -> PathBuf {
+    handle.path().app_config_dir().unwrap().join("presets.json")
+}
+
+fn read_presets(handle: &tauri::AppHandle) -> ConfigPresets {
+    fs::read_to_string(presets_path(handle))
+        .ok()
+        .and_then(|c| serde_json::from_str(strip_bom(&c)).ok())
+        .unwrap_or_default()
+}
+
+/// 将当前的外观设置（主题、字体、颜色）保存为名为 name 的预设，同名预设被覆盖
+#[tauri::command]
+fn save_config_preset(handle: tauri::AppHandle, name: String) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("预设名称不能为空".to_string());
+    }
+    let config = serde_json::to_value(get_app_config(handle.clone())).map_err(|e| e.to_string())?;
+    let preset = PRESET_CONFIG_KEYS
+        .iter()
+        .filter_map(|key| config.get(*key).map(|v| (key.to_string(), v.clone())))
+        .collect();
+    let mut presets = read_presets(&handle);
+    presets.insert(name, preset);
+    let content = serde_json::to_string_pretty(&presets).map_err(|e| e.to_string())?;
+    let path = presets_path(&handle);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| log_error("save_config_preset", e))?;
+    }
+    write_atomic(&path, content.as_bytes()).map_err(|e| log_error("save_config_preset", e))
+}
+
+/// 将名为 name 的预设应用到当前配置并保存
+#[tauri::command]
+fn apply_config_preset(handle: tauri::AppHandle, name: String) -> Result<AppConfig, String> {
+    let preset = read_presets(&handle)
+        .remove(name.trim())
+        .ok_or_else(|| format!("预设不存在: {}", name))?;
+    let mut config = serde_json::to_value(get_app_config(handle.clone())).map_err(|e| e.to_string())?;
+    for (key, value) in preset.into_iter().filter(|(key, _)| PRESET_CONFIG_KEYS.contains(&key.as_str())) {
+        config[key] = value;
+    }
+    let config: AppConfig = serde_json::from_value(config).map_err(|e| format!("预设无效: {}", e))?;
+    save_app_config(handle.clone(), config)?;
+    Ok(get_app_config(handle))
+}
+
+/// 列出已保存的外观预设名称
+#[tauri::command]
+fn list_presets(handle: tauri::AppHandle) -> Vec<String> {
+    read_presets(&handle).into_keys().collect()
+}
+
+/// 保存的查询条件；各条件同时满足才算匹配，未设置的条件不限制
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+struct SavedQuery {
+    /// 状态之一
+    #[serde(default)]
+    status: Vec<String>,
+    /// 须包含全部这些标签（不区分大小写）
+    #[serde(default)]
+    tags: Vec<String>,
+    /// 截止日期范围（YYYY-MM-DD，含两端）；设置任一端时没有截止日期的待办不匹配
+    #[serde(default)]
+    due_from: Option<String>,
+    #[serde(default)]
+    due_to: Option<String>,
+    /// 只匹配已过截止日期且未完成的待办
+    #[serde(default)]
+    overdue: bool,
+    /// 最低优先级
+    #[serde(default)]
+    min_priority: Option<u8>,
+    /// 标题或详情纯文本中包含的文字（不区分大小写）
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    include_archived: bool,
+}
+
+fn queries_path(handle: &tauri::AppHandle) -> PathBuf {
+    handle.path().app_config_dir().unwrap().join("queries.json")
+}
+
+fn read_queries(handle: &tauri::AppHandle) -> std::collections::BTreeMap<String, SavedQuery> {
+    fs::read_to_string(queries_path(handle))
+        .ok()
+        .and_then(|c| serde_json::from_str(strip_bom(&c)).ok())
+        .unwrap_or_default()
+}
+
+/// 将查询条件（JSON，字段见 SavedQuery）保存为名为 name 的查询，同名查询被覆盖
+#[tauri::command]
+fn save_query(handle: tauri::AppHandle, name: String, query_json: String) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("查询名称不能为空".to_string());
+    }
+    let query: SavedQuery = serde_json::from_str(&query_json).map_err(|e| format!("查询条件无效: {}", e))?;
+    for date in query.due_from.iter().chain(&query.due_to) {
+        parse_due_date(date).ok_or_else(|| format!("无效的日期: {}", date))?;
+    }
+    if let Some(unknown) = query.status.iter().find(|s| !KNOWN_STATUSES.contains(&s.as_str())) {
+        return Err(format!("未知的状态: {}", unknown));
+    }
+    let mut queries = read_queries(&handle);
+    queries.insert(name, query);
+    let content = serde_json::to_string_pretty(&queries).map_err(|e| e.to_string())?;
+    let path = queries_path(&handle);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| log_error("save_query", e))?;
+    }
+    write_atomic(&path, content.as_bytes()).map_err(|e| log_error("save_query", e))
+}
+
+/// 列出已保存的查询名称
+#[tauri::command]
+fn list_queries(handle: tauri::AppHandle) -> Vec<String> {
+    read_queries(&handle).into_keys().collect()
+}
+
+fn todo_matches_query(data_path: &str, todo: &TodoItem, query: &SavedQuery, today: chrono::NaiveDate) -> bool {
+    if !query.include_archived && todo.archived {
+        return false;
+    }
+    if !query.status.is_empty() && !query.status.contains(&todo.status) {
+        return false;
+    }
+    if !query.tags.iter().all(|q| todo.tags.iter().any(|t| t.to_lowercase() == q.to_lowercase())) {
+        return false;
+    }
+    if query.min_priority.is_some_and(|p| todo.priority < p) {
+        return false;
+    }
+    let due = todo.due_date.as_deref().and_then(parse_due_date);
+    if query.overdue && (todo.status == STATUS_COMPLETED || due.is_none_or(|d| d >= today)) {
+        return false;
+    }
+    let bound = |b: &Option<String>| b.as_deref().and_then(parse_due_date);
+    if let Some(from) = bound(&query.due_from) {
+        if due.is_none_or(|d| d < from) {
+            return false;
+        }
+    }
+    if let Some(to) = bound(&query.due_to) {
+        if due.is_none_or(|d| d > to) {
+            return false;
+        }
+    }
+    match query.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        None => true,
+        Some(text) => {
+            let text = text.to_lowercase();
+            todo.title.to_lowercase().contains(&text)
+                || get_detail_plaintext(data_path.to_string(), todo.folder_name.clone())
+                    .is_ok_and(|detail| detail.to_lowercase().contains(&text))
+        }
+    }
+}
+
+/// 执行名为 name 的已保存查询，返回匹配的待办（按列表顺序）
+#[tauri::command]
+fn run_query(handle: tauri::AppHandle, data_path: String, name: String) -> Result<Vec<TodoItem>, String> {
+    let data_path = expand_data_path(&data_path);
+    let query = read_queries(&handle)
+        .remove(name.trim())
+        .ok_or_else(|| format!("查询不存在: {}", name))?;
+    let today = chrono::Local::now().date_naive();
+    Ok(load_todos(&data_path)
+        .into_iter()
+        .filter(|t| todo_matches_query(&data_path, t, &query, today))
+        .collect())
+}
+
+/// 将已保存查询的结果导出为 json、csv 或 markdown（任务列表），返回导出的条数
+#[tauri::command]
+fn export_query(handle: tauri::AppHandle, data_path: String, name: String, dest_path: String, format: String) -> Result<usize, String> {
+    let todos = run_query(handle, data_path, name.clone())?;
+    let content = match format.as_str() {
+        "json" => serde_json::to_string_pretty(&todos).map_err(|e| e.to_string())? + "\n",
+        "csv" => {
+            let mut lines = vec!["title,status,folder_name,due_date,priority,tags".to_string()];
+            for todo in &todos {
+                let fields = [
+                    todo.title.clone(),
+                    todo.status.clone(),
+                    todo.folder_name.clone(),
+                    todo.due_date.clone().unwrap_or_default(),
+                    todo.priority.to_string(),
+                    todo.tags.join(";"),
+                ];
+                lines.push(fields.iter().map(|f| escape_csv_field(f)).collect::<Vec<_>>().join(","));
+            }
+            lines.join("\n") + "\n"
+        }
+        "markdown" => {
+            let mut lines = vec![format!("# {}", name.trim()), String::new()];
+            for todo in &todos {
+                let mark = if todo.status == STATUS_COMPLETED { "x" } else { " " };
+                let due = todo.due_date.as_deref().map(|d| format!(" （截止 {}）", d)).unwrap_or_default();
+                lines.push(format!("- [{}] {}{}", mark, todo.title, due));
+            }
+            lines.join("\n") + "\n"
+        }
+        other => return Err(format!("不支持的格式: {}", other)),
+    };
+    write_atomic(Path::new(&expand_data_path(&dest_path)), content.as_bytes())
+        .map_err(|e| log_error("export_query", e))?;
+    Ok(todos.len())
+}
+
+/// 返回用于提交问题反馈的配置 JSON：路径替换为占位符，名称像凭据的字段一律隐去
+#[tauri::command]
+fn export_config_redacted(handle: tauri::AppHandle) -> Result<String, String> {
+    let config = get_app_config(handle);
+    let mut value = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+    if let Some(map) = value.as_object_mut() {
+        for (key, field) in map.iter_mut() {
+            let lower = key.to_lowercase();
+            let is_secret = ["password", "secret", "token", "credential", "api_key"]
+                .iter()
+                .any(|s| lower.contains(s));
+            if is_secret {
+                *field = serde_json::Value::String("<redacted>".to_string());
+            } else if lower.ends_with("_path") && field.as_str().is_some_and(|v| !v.is_empty()) {
+                *field = serde_json::Value::String(format!("<{}>", key));
+            }
+        }
+    }
+    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+fn open_todo_db(data_dir: &Path) -> Result<rusqlite::Connection, String> {
+    let conn = rusqlite::Connection::open(data_dir.join(SQLITE_FILE)).map_err(|e| e.to_string())?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS todos (
+            position INTEGER PRIMARY KEY,
+            id TEXT NOT NULL,
+            folder_name TEXT NOT NULL,
+            data TEXT NOT NULL
+        )",
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+fn read_todos_sqlite(data_dir: &Path) -> Result<Vec<TodoItem>, String> {
+    if !data_dir.join(SQLITE_FILE).exists() {
+        return Ok(vec![]);
+    }
+    let conn = open_todo_db(data_dir)?;
+    let mut stmt = conn
+        .prepare("SELECT data FROM todos ORDER BY position")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    let mut todos = Vec::new();
+    for row in rows {
+        let data = row.map_err(|e| e.to_string())?;
+        todos.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    Ok(todos)
+}
+
+fn write_todos_sqlite(data_dir: &Path, todos: &[TodoItem]) -> Result<(), String> {
+    let mut conn = open_todo_db(data_dir)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM todos", []).map_err(|e| e.to_string())?;
+    {
+        let mut stmt = tx
+            .prepare("INSERT INTO todos (position, id, folder_name, data) VALUES (?1, ?2, ?3, ?4)")
+            .map_err(|e| e.to_string())?;
+        for (position, todo) in todos.iter().enumerate() {
+            let data = serde_json::to_string(todo).map_err(|e| e.to_string())?;
+            stmt.execute(rusqlite::params![position as i64, todo.id, todo.folder_name, data])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// 读取完整的待办列表（按配置选择 JSON 或 SQLite 后端）
+/// 去掉部分编辑器写入的 UTF-8 BOM，否则 serde_json 无法解析
+fn strip_bom(content: &str) -> &str {
+    content.strip_prefix('\u{feff}').unwrap_or(content)
+}
+
+/// 若文件以 UTF-8 BOM 开头则去掉 BOM 后原子写回，返回是否改写
+fn remove_file_bom(path: &Path) -> Result<bool, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    match bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        Some(rest) => write_atomic(path, rest).map(|_| true).map_err(|e| e.to_string()),
+        None => Ok(false),
+    }
+}
+
+/// 去掉数据目录（todos.json 及各待办文件夹中的 JSON 文件）与配置文件开头的 UTF-8 BOM，返回被改写的文件路径
+#[tauri::command]
+fn repair_bom_files(app: tauri::AppHandle, data_path: String) -> Result<Vec<String>, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    let mut candidates = Vec::new();
+    candidates.push(config_path(&app));
+    let is_json = |p: &Path| p.is_file() && p.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"));
+    for entry in fs::read_dir(data_dir).map_err(|e| log_error("repair_bom_files", e))?.flatten() {
+        let path = entry.path();
+        if is_json(&path) {
+            candidates.push(path);
+        } else if path.is_dir() && !is_reserved_dir(&entry.file_name().to_string_lossy()) {
+            for inner in fs::read_dir(&path).into_iter().flatten().flatten() {
+                if is_json(&inner.path()) {
+                    candidates.push(inner.path());
+                }
+            }
+        }
+    }
+    let mut repaired = Vec::new();
+    for path in candidates.into_iter().filter(|p| p.is_file()) {
+        if remove_file_bom(&path).map_err(|e| log_error("repair_bom_files", e))? {
+            repaired.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(repaired)
+}
+
+/// 当前配置的附件子目录名
+fn assets_dir_name() -> String {
+    let name = ASSETS_DIR_NAME.lock().map(|n| n.clone()).unwrap_or_default();
+    if name.is_empty() { default_assets_dir_name() } else { name }
+}
+
+/// 读取待办索引；索引不存在、无法读取（记录错误）或无法解析时返回空列表
+fn load_todos(data_path: &str) -> Vec<TodoItem> {
+    if USE_SQLITE.load(Ordering::Relaxed) {
+        return read_todos_sqlite(Path::new(data_path)).unwrap_or_default();
+    }
+    let todos_path = Path::new(data_path).join("todos.json");
+    if !todos_path.exists() {
+        return vec![];
+    }
+    match fs::read_to_string(todos_path) {
+        Ok(content) => serde_json::from_str(strip_bom(&content)).unwrap_or_else(|_| vec![]),
+        Err(e) => {
+            log_error("load_todos", e);
+            vec![]
+        }
+    }
+}
+
+/// 返回所有已知的待办状态，供界面与配置保持一致
+#[tauri::command]
+fn get_statuses() -> Vec<String> {
+    KNOWN_STATUSES.iter().map(|s| s.to_string()).collect()
+}
+
+/// 获取待办列表；传入 status 时只返回该状态的条目，以减少跨 IPC 传输的数据量。
+/// 推迟中与已归档的待办默认不返回，include_snoozed / include_archived 为 true 时一并返回；传入 board 时只返回该看板的待办
+#[tauri::command]
+fn get_todos(
+    data_path: String,
+    status: Option<String>,
+    include_snoozed: Option<bool>,
+    include_archived: Option<bool>,
+    board: Option<String>,
+) -> Vec<TodoItem> {
+    let data_path = expand_data_path(&data_path);
+    let now = chrono::Local::now();
+    let include_snoozed = include_snoozed.unwrap_or(false);
+    let include_archived = include_archived.unwrap_or(false);
+    load_todos(&data_path)
+        .into_iter()
+        .filter(|t| status.as_ref().map(|s| t.status == *s).unwrap_or(true))
+        .filter(|t| include_snoozed || !is_snoozed(t, &now))
+        .filter(|t| include_archived || !t.archived)
+        .filter(|t| board.as_ref().is_none_or(|b| t.board == *b))
+        .collect()
+}
+
+#[derive(Serialize, Debug)]
+struct TimedTodos {
+    todos: Vec<TodoItem>,
+    /// 读取索引文件耗时（毫秒）；SQLite 后端时包含查询与解析
+    read_ms: f64,
+    /// 解析 todos.json 耗时（毫秒）；SQLite 后端时为 0
+    parse_ms: f64,
+    /// 索引文件大小（字节），SQLite 后端时为 todos.db 的大小
+    bytes: u64,
+}
+
+/// 读取全部待办并返回读取与解析各自的耗时，用于诊断启动缓慢；日志级别为 info 时同时写入日志
+#[tauri::command]
+fn get_todos_timed(data_path: String) -> Result<TimedTodos, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    let started = std::time::Instant::now();
+    let timed = if USE_SQLITE.load(Ordering::Relaxed) {
+        let todos = read_todos_sqlite(data_dir)?;
+        TimedTodos {
+            todos,
+            read_ms: ms(started.elapsed()),
+            parse_ms: 0.0,
+            bytes: fs::metadata(data_dir.join(SQLITE_FILE)).map(|m| m.len()).unwrap_or(0),
+        }
+    } else {
+        let todos_path = data_dir.join("todos.json");
+        let content = if todos_path.exists() {
+            fs::read_to_string(&todos_path).map_err(|e| log_error("get_todos_timed", e))?
+        } else {
+            "[]".to_string()
+        };
+        let read_ms = ms(started.elapsed());
+        let parsing = std::time::Instant::now();
+        let todos: Vec<TodoItem> = serde_json::from_str(strip_bom(&content))
+            .map_err(|e| log_error("get_todos_timed", format!("todos.json 解析失败: {}", e)))?;
+        TimedTodos { todos, read_ms, parse_ms: ms(parsing.elapsed()), bytes: content.len() as u64 }
+    };
+    write_log(
+        LogLevel::Info,
+        "get_todos_timed",
+        &format!(
+            "{} 条待办，{} 字节，读取 {:.1} ms，解析 {:.1} ms",
+            timed.todos.len(),
+            timed.bytes,
+            timed.read_ms,
+            timed.parse_ms
+        ),
+    );
+    Ok(timed)
+}
+
+/// 将待办移到另一个看板（去除首尾空白，不允许为空），只写入一次，返回更新后的待办
+#[tauri::command]
+fn move_todo_to_board(app: tauri::AppHandle, data_path: String, folder_name: String, board: String) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let board = board.trim().to_string();
+    if board.is_empty() {
+        return Err("看板名称不能为空".to_string());
+    }
+    let mut todos = load_todos(&data_path);
+    let todo = todos
+        .iter_mut()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    todo.board = board;
+    let updated = todo.clone();
+    save_todos(app, data_path, todos)?;
+    Ok(updated)
+}
+
+/// 列出所有待办用到的看板（去重并排序），始终包含默认看板
+#[tauri::command]
+fn list_boards(data_path: String) -> Vec<String> {
+    let data_path = expand_data_path(&data_path);
+    let mut boards: std::collections::BTreeSet<String> = load_todos(&data_path).into_iter().map(|t| t.board).collect();
+    boards.insert(default_board());
+    boards.into_iter().collect()
+}
+
+/// 返回待整理的待办（不含已归档）：同时满足 criteria 中所有条件的条目。criteria 可含 "tags"（没有标签）、
+/// "status"（仍为 default_status）、"due"（没有截止日期），未传时三者都计入
+#[tauri::command]
+fn find_unclassified(app: tauri::AppHandle, data_path: String, criteria: Option<Vec<String>>) -> Result<Vec<TodoItem>, String> {
+    let data_path = expand_data_path(&data_path);
+    let criteria = criteria.unwrap_or_else(|| vec!["tags".to_string(), "status".to_string(), "due".to_string()]);
+    if let Some(unknown) = criteria.iter().find(|c| !matches!(c.as_str(), "tags" | "status" | "due")) {
+        return Err(format!("未知的条件: {}", unknown));
+    }
+    if criteria.is_empty() {
+        return Err("至少需要一个条件".to_string());
+    }
+    let default_status = get_app_config(app).default_status;
+    Ok(load_todos(&data_path)
+        .into_iter()
+        .filter(|t| !t.archived)
+        .filter(|t| {
+            criteria.iter().all(|c| match c.as_str() {
+                "tags" => t.tags.is_empty(),
+                "status" => t.status == default_status,
+                _ => t.due_date.as_deref().is_none_or(|d| d.trim().is_empty()),
+            })
+        })
+        .collect())
+}
+
+#[derive(Serialize, Debug)]
+struct UrgentTodo {
+    todo: TodoItem,
+    score: f64,
+}
+
+/// 按截止日期临近程度、优先级与创建时长计算紧迫度：每项先归一到 0-1 再乘以配置中的权重。
+/// 截止日期逾期 7 天及以上计 1，14 天以后计 0.2，其间线性变化；没有截止日期计 0；创建满一年计 1
+fn urgency_score(todo: &TodoItem, config: &AppConfig, today: chrono::NaiveDate, now: chrono::DateTime<chrono::Local>) -> f64 {
+    let due = todo
+        .due_date
+        .as_deref()
+        .and_then(parse_due_date)
+        .map(|d| {
+            let days = (d - today).num_days().clamp(-7, 14) as f64;
+            (14.0 - days) * 0.8 / 21.0 + 0.2
+        })
+        .unwrap_or(0.0);
+    let priority = todo.priority.min(MAX_PRIORITY) as f64 / MAX_PRIORITY as f64;
+    let age = todo
+        .created_at
+        .as_deref()
+        .and_then(parse_timestamp)
+        .map(|c| ((now - c).num_hours().max(0) as f64 / (365.0 * 24.0)).min(1.0))
+        .unwrap_or(0.0);
+    due * config.urgency_due_weight + priority * config.urgency_priority_weight + age * config.urgency_age_weight
+}
+
+/// 返回未完成、未归档的待办及其紧迫度，按紧迫度从高到低排序
+#[tauri::command]
+fn get_todos_by_urgency(app: tauri::AppHandle, data_path: String) -> Vec<UrgentTodo> {
+    let data_path = expand_data_path(&data_path);
+    let config = get_app_config(app);
+    let now = chrono::Local::now();
+    let today = now.date_naive();
+    let mut todos: Vec<UrgentTodo> = load_todos(&data_path)
+        .into_iter()
+        .filter(|t| t.status != STATUS_COMPLETED && !t.archived)
+        .map(|todo| {
+            let score = urgency_score(&todo, &config, today, now);
+            UrgentTodo { todo, score }
+        })
+        .collect();
+    todos.sort_by(|a, b| b.score.total_cmp(&a.score));
+    todos
+}
+
+/// 数据目录下的待办文件夹：除保留目录外的所有子目录（文件夹名可能已被 rename_folders_by_title 改为可读名称），
+/// 包括 archive/<年份>/ 下按年份归档的（以 archive/<年份>/<文件夹名> 的形式返回）
+fn todo_folders_on_disk(data_dir: &Path) -> Vec<String> {
+    let folder_dirs = |dir: &Path| -> Vec<String> {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| e.path().is_dir())
+                    .map(|e| e.file_name().to_string_lossy().into_owned())
+                    .filter(|name| !is_reserved_dir(name))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let mut folders = folder_dirs(data_dir);
+    let archive = data_dir.join(ARCHIVE_DIR);
+    for year in fs::read_dir(&archive).into_iter().flatten().flatten() {
+        let year = year.file_name().to_string_lossy().into_owned();
+        if year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()) {
+            folders.extend(folder_dirs(&archive.join(&year)).into_iter().map(|f| format!("{}/{}/{}", ARCHIVE_DIR, year, f)));
+        }
+    }
+    folders
+}
+
+/// 归档待办应在的年份：取完成时间，缺失时依次退回修改时间、创建时间与当前年份
+fn archive_year(todo: &TodoItem) -> i32 {
+    use chrono::Datelike;
+    [&todo.completed_at, &todo.updated_at, &todo.created_at]
+        .into_iter()
+        .find_map(|t| t.as_deref().and_then(parse_timestamp))
+        .unwrap_or_else(chrono::Local::now)
+        .year()
+}
+
+/// 按归档状态移动待办文件夹：year_layout 为 true 时将已归档的移入 archive/<年份>/，未归档但仍在 archive 下的一律移回数据目录根部。
+/// 同步更新 folder_name、资源 url 与 depends_on 中的引用（调用方负责保存索引）；
+/// 引用了共用资源的待办不移入归档目录，移动失败的保持原位并记录日志，返回移动的个数
+fn relocate_archived_folders(data_dir: &Path, todos: &mut [TodoItem], year_layout: bool) -> usize {
+    let mut renamed: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    for todo in todos.iter_mut() {
+        let base = todo.folder_name.rsplit('/').next().unwrap_or_default().to_string();
+        let target = if !todo.archived {
+            base
+        } else if year_layout {
+            format!("{}/{}/{}", ARCHIVE_DIR, archive_year(todo), base)
+        } else {
+            continue;
+        };
+        if target == todo.folder_name {
+            continue;
+        }
+        let (from, to) = (data_dir.join(&todo.folder_name), data_dir.join(&target));
+        if !from.is_dir() {
+            continue;
+        }
+        // 共用资源的引用（../.assets/）相对待办文件夹，移入更深的目录后会失效，这类待办保持原位
+        let uses_shared_assets = fs::read_to_string(from.join("content.json")).is_ok_and(|c| c.contains(SHARED_ASSET_PREFIX));
+        if uses_shared_assets && target.contains('/') {
+            write_log(LogLevel::Info, "relocate_archived_folders", &format!("引用了共用资源，不移入归档目录: {}", todo.folder_name));
+            continue;
+        }
+        if to.exists() {
+            write_log(LogLevel::Error, "relocate_archived_folders", &format!("目标文件夹已存在，跳过: {}", target));
+            continue;
+        }
+        let moved = to
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::rename(&from, &to));
+        if let Err(e) = moved {
+            log_error("relocate_archived_folders", format!("{} -> {}: {}", todo.folder_name, target, e));
+            continue;
+        }
+        // 移出后留下的空年份目录与空 archive 目录一并清理（非空时 remove_dir 会失败，忽略即可）
+        if let Some(year_dir) = from.parent().filter(|p| *p != data_dir) {
+            let _ = fs::remove_dir(year_dir);
+            let _ = fs::remove_dir(data_dir.join(ARCHIVE_DIR));
+        }
+        retarget_moved_folder(data_dir, &todo.folder_name, &target);
+        renamed.insert(std::mem::replace(&mut todo.folder_name, target.clone()), target);
+    }
+    remap_folder_references(&data_dir.to_string_lossy(), todos, &renamed);
+    renamed.len()
+}
+
+/// 文件夹改名后改写依赖关系中的引用：todos 与回收站清单中各待办的 depends_on（old -> new）
+fn remap_folder_references(data_path: &str, todos: &mut [TodoItem], renamed: &std::collections::HashMap<String, String>) {
+    if renamed.is_empty() {
+        return;
+    }
+    let remap = |todo: &mut TodoItem| {
+        let mut changed = false;
+        for dep in todo.depends_on.iter_mut() {
+            if let Some(new_name) = renamed.get(dep) {
+                *dep = new_name.clone();
+                changed = true;
+            }
+        }
+        changed
+    };
+    for todo in todos.iter_mut() {
+        remap(todo);
+    }
+    let mut trash = read_trash_manifest(data_path);
+    let mut trash_changed = false;
+    for entry in trash.iter_mut() {
+        trash_changed |= remap(&mut entry.todo);
+    }
+    if trash_changed {
+        if let Err(e) = write_trash_manifest(data_path, &trash) {
+            log_error("remap_folder_references", e);
+        }
+    }
+}
+
+/// 将已有的归档待办按完成年份移入 archive/<年份>/，不论 archive_year_layout 是否开启；返回移动的个数。
+/// 开启该配置后执行一次，之后新归档的待办会自动移入
+#[tauri::command]
+fn migrate_archive_layout(app: tauri::AppHandle, data_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut todos = load_todos(&data_path);
+    let moved = relocate_archived_folders(Path::new(&data_path), &mut todos, true);
+    if moved > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(moved)
+}
+
+/// 将所列待办统一归档或取消归档，只写入一次，返回状态有变化的条数
+#[tauri::command]
+fn set_archived(app: tauri::AppHandle, data_path: String, folder_names: Vec<String>, archived: bool) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut todos = load_todos(&data_path);
+    let mut updated = 0;
+    for todo in todos.iter_mut().filter(|t| folder_names.contains(&t.folder_name)) {
+        if todo.archived != archived {
+            todo.archived = archived;
+            updated += 1;
+        }
+    }
+    if updated > 0 {
+        relocate_archived_folders(Path::new(&data_path), &mut todos, ARCHIVE_YEAR_LAYOUT.load(Ordering::Relaxed));
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(updated)
+}
+
+/// 将完成时间早于 older_than_days 天前的已完成待办一次性归档，返回归档的条数
+#[tauri::command]
+fn auto_archive(app: tauri::AppHandle, data_path: String, older_than_days: u32) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let cutoff = chrono::Local::now() - chrono::Duration::days(older_than_days as i64);
+    let mut todos = load_todos(&data_path);
+    let mut archived = 0;
+    for todo in todos
+        .iter_mut()
+        .filter(|t| t.status == STATUS_COMPLETED && !t.archived)
+    {
+        let done_before_cutoff = todo
+            .completed_at
+            .as_deref()
+            .and_then(parse_timestamp)
+            .is_some_and(|t| t < cutoff);
+        if done_before_cutoff {
+            todo.archived = true;
+            archived += 1;
+        }
+    }
+    if archived > 0 {
+        relocate_archived_folders(Path::new(&data_path), &mut todos, ARCHIVE_YEAR_LAYOUT.load(Ordering::Relaxed));
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(archived)
+}
+
+#[derive(Serialize, Debug)]
+struct TodoChanges {
+    /// updated_at 晚于 since 的待办
+    todos: Vec<TodoItem>,
+    /// content.json 修改时间晚于 since 的待办文件夹
+    changed_folders: Vec<String>,
+}
+
+/// 返回 since（RFC 3339 或 YYYY-MM-DD）之后有变化的待办与详情，供同步或后台轮询只拉取增量
+#[tauri::command]
+fn get_todos_since(data_path: String, since: String) -> Result<TodoChanges, String> {
+    let data_path = expand_data_path(&data_path);
+    let since = parse_timestamp(&since).ok_or_else(|| format!("无效的时间: {}", since))?;
+    let all = load_todos(&data_path);
+    let changed_folders = all
+        .iter()
+        .filter(|t| {
+            fs::metadata(Path::new(&data_path).join(&t.folder_name).join("content.json"))
+                .and_then(|m| m.modified())
+                .is_ok_and(|m| chrono::DateTime::<chrono::Local>::from(m) > since)
+        })
+        .map(|t| t.folder_name.clone())
+        .collect();
+    let todos = all
+        .into_iter()
+        .filter(|t| t.updated_at.as_deref().and_then(parse_timestamp).is_some_and(|u| u > since))
+        .collect();
+    Ok(TodoChanges { todos, changed_folders })
+}
+
+/// 修改单个待办的标题（去除首尾空白，不允许为空），只写入一次，返回更新后的待办
+#[tauri::command]
+fn rename_todo(app: tauri::AppHandle, data_path: String, folder_name: String, new_title: String) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let title = new_title.trim();
+    if title.is_empty() {
+        return Err("标题不能为空".to_string());
+    }
+    let max = MAX_TITLE_LENGTH.load(Ordering::Relaxed) as usize;
+    if max > 0 && title.chars().count() > max {
+        return Err(format!("标题超过 {} 个字符", max));
+    }
+    let mut todos = load_todos(&data_path);
+    let todo = todos
+        .iter_mut()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    todo.title = title.to_string();
+    todo.updated_at = Some(utc_now_rfc3339());
+    let updated = todo.clone();
+    save_todos(app, data_path, todos)?;
+    Ok(updated)
+}
+
+/// 对比待办标题与详情开头的一级标题，不一致时按 strategy 统一："index" 以 todos.json 为准改写详情，
+/// "content" 以详情为准改写标题。开头没有一级标题的详情不参与比较。返回处理过的文件夹
+#[tauri::command]
+fn reconcile_titles(app: tauri::AppHandle, data_path: String, strategy: String) -> Result<Vec<String>, String> {
+    let data_path = expand_data_path(&data_path);
+    if !matches!(strategy.as_str(), "index" | "content") {
+        return Err(format!("不支持的策略: {}", strategy));
+    }
+    let mut todos = load_todos(&data_path);
+    let mut reconciled = Vec::new();
+    for todo in todos.iter_mut() {
+        let Ok(content) = get_todo_detail(data_path.clone(), todo.folder_name.clone()) else { continue };
+        let mut nodes = parse_detail_nodes(&content);
+        let Some(first) = nodes.first_mut().filter(|n| n["type"] == "h1") else { continue };
+        let mut heading = String::new();
+        node_inline_text(first, &mut heading);
+        let heading = heading.trim().to_string();
+        if heading.is_empty() || heading == todo.title.trim() {
+            continue;
+        }
+        if strategy == "index" {
+            first["children"] = serde_json::json!([{ "type": "text", "value": &todo.title }]);
+            let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+            save_todo_detail(app.clone(), data_path.clone(), todo.folder_name.clone(), content)?;
+        } else {
+            todo.title = heading;
+        }
+        reconciled.push(todo.folder_name.clone());
+    }
+    if strategy == "content" && !reconciled.is_empty() {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(reconciled)
+}
+
+/// 将所列待办的优先级统一设为 priority，只写入一次，返回更新的条数
+#[tauri::command]
+fn bulk_set_priority(app: tauri::AppHandle, data_path: String, folder_names: Vec<String>, priority: u8) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    if priority > MAX_PRIORITY {
+        return Err(format!("优先级超出范围 (0-{}): {}", MAX_PRIORITY, priority));
+    }
+    let mut todos = load_todos(&data_path);
+    let mut updated = 0;
+    for todo in todos.iter_mut().filter(|t| folder_names.contains(&t.folder_name)) {
+        if todo.priority != priority {
+            todo.priority = priority;
+            updated += 1;
+        }
+    }
+    if updated > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(updated)
+}
+
+/// 修改单个待办的状态，按配置的 allowed_transitions 拒绝不允许的流转，返回更新后的待办
+#[tauri::command]
+fn set_todo_status(app: tauri::AppHandle, data_path: String, folder_name: String, status: String) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    if !KNOWN_STATUSES.contains(&status.as_str()) {
+        return Err(format!("未知的状态: {}", status));
+    }
+    let config = get_app_config(app.clone());
+    let mut todos = load_todos(&data_path);
+    let index = todos
+        .iter()
+        .position(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    check_status_transition(&config, &todos[index].status, &status)?;
+    check_wip_limit(&config, &todos, &todos[index].status, &status)?;
+    let todo = &mut todos[index];
+    todo.status = status;
+    let updated = todo.clone();
+    save_todos(app, data_path, todos)?;
+    Ok(updated)
+}
+
+/// 将待办状态推进到 KNOWN_STATUSES 中的下一个（末尾回到开头，未知状态从第一个开始），返回新状态
+#[tauri::command]
+fn cycle_status(app: tauri::AppHandle, data_path: String, folder_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let config = get_app_config(app.clone());
+    let mut todos = load_todos(&data_path);
+    let index = todos
+        .iter()
+        .position(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    let next = match KNOWN_STATUSES.iter().position(|s| *s == todos[index].status) {
+        Some(i) => KNOWN_STATUSES[(i + 1) % KNOWN_STATUSES.len()],
+        None => KNOWN_STATUSES[0],
+    };
+    check_status_transition(&config, &todos[index].status, next)?;
+    check_wip_limit(&config, &todos, &todos[index].status, next)?;
+    todos[index].status = next.to_string();
+    save_todos(app, data_path, todos)?;
+    Ok(next.to_string())
+}
+
+/// 判断 from 是否（直接或间接）依赖 target
+fn depends_transitively(todos: &[TodoItem], from: &str, target: &str) -> bool {
+    let mut stack = vec![from.to_string()];
+    let mut visited = std::collections::HashSet::new();
+    while let Some(current) = stack.pop() {
+        if current == target {
+            return true;
+        }
+        if !visited.insert(current.clone()) {
+            continue;
+        }
+        if let Some(todo) = todos.iter().find(|t| t.folder_name == current) {
+            stack.extend(todo.depends_on.iter().cloned());
+        }
+    }
+    false
+}
+
+#[derive(Serialize, Debug)]
+struct DependencyGraphReport {
+    nodes: usize,
+    edges: usize,
+    /// 处于循环依赖中的边数（输出中以红色标出）
+    cycle_edges: usize,
+}
+
+/// 依赖图中按状态填充的节点颜色
+fn status_fill_color(status: &str) -> &'static str {
+    match status {
+        STATUS_COMPLETED => "#95de64",
+        STATUS_PENDING => "#ffd666",
+        _ => "#d9d9d9",
+    }
+}
+
+/// 将待办间的依赖导出为 Graphviz DOT（format 为 "dot"）或 Mermaid（"mermaid"）文件：
+/// 边由被依赖的待办指向依赖它的待办，节点按状态着色，循环依赖中的边与节点标为红色；只输出有依赖关系的待办
+#[tauri::command]
+fn export_dependency_graph(data_path: String, dest_path: String, format: String) -> Result<DependencyGraphReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let todos = load_todos(&data_path);
+    let exists = |f: &str| todos.iter().any(|t| t.folder_name == f);
+    // (前置待办, 依赖它的待办, 是否处于循环中)
+    let mut edges = Vec::new();
+    for todo in &todos {
+        for dep in todo.depends_on.iter().filter(|d| exists(d)) {
+            let cyclic = depends_transitively(&todos, dep, &todo.folder_name);
+            edges.push((dep.as_str(), todo.folder_name.as_str(), cyclic));
+        }
+    }
+    let nodes: Vec<&TodoItem> = todos
+        .iter()
+        .filter(|t| edges.iter().any(|(a, b, _)| *a == t.folder_name || *b == t.folder_name))
+        .collect();
+    let in_cycle = |f: &str| edges.iter().any(|(a, b, c)| *c && (*a == f || *b == f));
+    let mut out = String::new();
+    match format.as_str() {
+        "dot" => {
+            let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+            out.push_str("digraph todos {\n  rankdir=LR;\n  node [shape=box, style=\"rounded,filled\"];\n");
+            for todo in &nodes {
+                let border = if in_cycle(&todo.folder_name) { ", color=red, penwidth=2" } else { "" };
+                out.push_str(&format!(
+                    "  {} [label={}, fillcolor=\"{}\"{}];\n",
+                    quote(&todo.folder_name),
+                    quote(&todo.title),
+                    status_fill_color(&todo.status),
+                    border
+                ));
+            }
+            for (from, to, cyclic) in &edges {
+                let style = if *cyclic { " [color=red, label=\"cycle\"]" } else { "" };
+                out.push_str(&format!("  {} -> {}{};\n", quote(from), quote(to), style));
+            }
+            out.push_str("}\n");
+        }
+        "mermaid" => {
+            let id = |f: &str| format!("t{}", nodes.iter().position(|t| t.folder_name == f).unwrap_or(0));
+            out.push_str("flowchart LR\n");
+            for (i, todo) in nodes.iter().enumerate() {
+                out.push_str(&format!("  t{}[\"{}\"]\n", i, todo.title.replace('"', "#quot;")));
+                let border = if in_cycle(&todo.folder_name) { ",stroke:red,stroke-width:2px" } else { "" };
+                out.push_str(&format!("  style t{} fill:{}{}\n", i, status_fill_color(&todo.status), border));
+            }
+            for (i, (from, to, cyclic)) in edges.iter().enumerate() {
+                out.push_str(&format!("  {} --> {}\n", id(from), id(to)));
+                if *cyclic {
+                    out.push_str(&format!("  linkStyle {} stroke:red,stroke-width:2px\n", i));
+                }
+            }
+        }
+        _ => return Err(format!("不支持的格式: {}", format)),
+    }
+    let dest = PathBuf::from(expand_data_path(&dest_path));
+    write_atomic(&dest, out.as_bytes()).map_err(|e| log_error("export_dependency_graph", e))?;
+    Ok(DependencyGraphReport {
+        nodes: nodes.len(),
+        edges: edges.len(),
+        cycle_edges: edges.iter().filter(|(_, _, c)| *c).count(),
+    })
+}
+
+/// 让 folder_name 依赖 depends_on（后者完成前前者被阻塞）；会形成循环依赖时拒绝，返回更新后的待办
+#[tauri::command]
+fn add_dependency(app: tauri::AppHandle, data_path: String, folder_name: String, depends_on: String) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut todos = load_todos(&data_path);
+    if !todos.iter().any(|t| t.folder_name == depends_on) {
+        return Err(format!("待办不存在: {}", depends_on));
+    }
+    if depends_transitively(&todos, &depends_on, &folder_name) {
+        return Err(format!("添加后会形成循环依赖: {} -> {}", folder_name, depends_on));
+    }
+    let todo = todos
+        .iter_mut()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    if !todo.depends_on.contains(&depends_on) {
+        todo.depends_on.push(depends_on);
+    }
+    let updated = todo.clone();
+    save_todos(app, data_path, todos)?;
+    Ok(updated)
+}
+
+/// 移除 folder_name 对 depends_on 的依赖，返回更新后的待办
+#[tauri::command]
+fn remove_dependency(app: tauri::AppHandle, data_path: String, folder_name: String, depends_on: String) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut todos = load_todos(&data_path);
+    let todo = todos
+        .iter_mut()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    todo.depends_on.retain(|d| *d != depends_on);
+    let updated = todo.clone();
+    save_todos(app, data_path, todos)?;
+    Ok(updated)
+}
+
+/// 返回被阻塞的待办：自身未完成，且至少有一个前置待办未完成。已被删除的前置待办视为已满足
+#[tauri::command]
+fn get_blocked_todos(data_path: String) -> Vec<TodoItem> {
+    let data_path = expand_data_path(&data_path);
+    let todos = load_todos(&data_path);
+    let unfinished: std::collections::HashSet<&str> = todos
+        .iter()
+        .filter(|t| t.status != STATUS_COMPLETED)
+        .map(|t| t.folder_name.as_str())
+        .collect();
+    todos
+        .iter()
+        .filter(|t| t.status != STATUS_COMPLETED)
+        .filter(|t| t.depends_on.iter().any(|d| unfinished.contains(d.as_str())))
+        .cloned()
+        .collect()
+}
+
+/// 标签配色板，亮色与暗色主题下均清晰可读
+const TAG_PALETTE: &[&str] = &[
+    "#e57373", "#f06292", "#ba68c8", "#9575cd", "#7986cb", "#64b5f6",
+    "#4fc3f7", "#4db6ac", "#81c784", "#aed581", "#ffb74d", "#a1887f",
+];
+
+/// 返回标签的显示颜色：优先使用配置中的 tag_colors，否则按标签名的 FNV-1a 哈希从配色板中选取，
+/// 同一标签在任何设备上都得到相同颜色
+#[tauri::command]
+fn get_tag_color(app: tauri::AppHandle, tag: String) -> String {
+    if let Some(color) = get_app_config(app).tag_colors.get(&tag) {
+        return color.clone();
+    }
+    let mut hash: u32 = 0x811c9dc5;
+    for b in tag.trim().to_lowercase().bytes() {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    TAG_PALETTE[hash as usize % TAG_PALETTE.len()].to_string()
+}
+
+/// 返回每个已知状态的显示颜色：配置中未指定的状态使用默认颜色
+#[tauri::command]
+fn get_status_colors(app: tauri::AppHandle) -> std::collections::BTreeMap<String, String> {
+    let mut colors = default_status_colors();
+    colors.extend(get_app_config(app).status_colors);
+    colors
+}
+
+/// 向系统注册快速记录快捷键：按下时显示主窗口并发出 quick-capture 事件，由界面弹出输入框新建待办。
+/// 组合已被其他应用占用等原因注册失败时返回错误
+fn register_global_hotkey(app: &tauri::AppHandle, combo: &str) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+    app.global_shortcut()
+        .on_shortcut(combo, |app, _, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            if let Some(w) = app.get_webview_window("main") {
+                let _ = w.show();
+                let _ = w.unminimize();
+                let _ = w.set_focus();
+            }
+            let _ = app.emit("quick-capture", ());
+        })
+        .map_err(|e| format!("快捷键 {} 注册失败，可能已被其他应用占用，请改选其他组合: {}", combo, e))
+}
+
+/// 设置快速记录的全局快捷键：向系统注册新组合、注销原组合并写入配置，返回规范后的组合；传入空字符串表示取消。
+/// 组合无效或已被其他应用占用时返回错误，原组合保持不变，便于用户改选其他组合
+#[tauri::command]
+fn register_quick_capture_hotkey(app: tauri::AppHandle, combo: String) -> Result<String, String> {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+    let combo = match combo.trim() {
+        "" => String::new(),
+        combo => normalize_hotkey(combo)?,
+    };
+    let mut config = get_app_config(app.clone());
+    let previous = std::mem::replace(&mut config.quick_capture_hotkey, combo.clone());
+    let shortcuts = app.global_shortcut();
+    let newly_registered = !combo.is_empty() && !shortcuts.is_registered(combo.as_str());
+    if newly_registered {
+        register_global_hotkey(&app, &combo)?;
+    }
+    if let Err(e) = save_app_config(app.clone(), config) {
+        if newly_registered {
+            let _ = shortcuts.unregister(combo.as_str());
+        }
+        return Err(e);
+    }
+    if !previous.is_empty() && previous != combo {
+        let _ = shortcuts.unregister(previous.as_str());
+    }
+    Ok(combo)
+}
+
+/// 设置某个状态的显示颜色并保存配置，返回设置后的全部状态颜色
+#[tauri::command]
+fn set_status_color(app: tauri::AppHandle, status: String, color: String) -> Result<std::collections::BTreeMap<String, String>, String> {
+    if !KNOWN_STATUSES.contains(&status.as_str()) {
+        return Err(format!("未知的状态: {}", status));
+    }
+    let color = color.trim().to_lowercase();
+    if !is_hex_color(&color) {
+        return Err(format!("无效的颜色: {}", color));
+    }
+    let mut config = get_app_config(app.clone());
+    config.status_colors.insert(status, color);
+    save_app_config(app.clone(), config)?;
+    Ok(get_status_colors(app))
+}
+
+/// 按状态或标签分组返回待办（不含推迟中的）。按状态分组时所有已知状态都会出现；
+/// 按标签分组时多标签的待办出现在每个标签下，无标签的归入空字符串键
+#[tauri::command]
+fn get_todos_grouped(
+    data_path: String,
+    group_by: String,
+) -> Result<std::collections::BTreeMap<String, Vec<TodoItem>>, String> {
+    let data_path = expand_data_path(&data_path);
+    let now = chrono::Local::now();
+    let todos = load_todos(&data_path).into_iter().filter(|t| !is_snoozed(t, &now));
+    let mut groups: std::collections::BTreeMap<String, Vec<TodoItem>> = std::collections::BTreeMap::new();
+    match group_by.as_str() {
+        "status" => {
+            for status in KNOWN_STATUSES {
+                groups.insert(status.to_string(), Vec::new());
+            }
+            for todo in todos {
+                groups.entry(todo.status.clone()).or_default().push(todo);
+            }
+        }
+        "tag" => {
+            for todo in todos {
+                if todo.tags.is_empty() {
+                    groups.entry(String::new()).or_default().push(todo);
+                    continue;
+                }
+                let mut tags = todo.tags.clone();
+                tags.sort();
+                tags.dedup();
+                for tag in tags {
+                    groups.entry(tag).or_default().push(todo.clone());
+                }
+            }
+        }
+        other => return Err(format!("不支持的分组方式: {}", other)),
+    }
+    Ok(groups)
+}
+
+/// 将所有待办的 old_tag 改为 new_tag（已有 new_tag 的合并，不重复），并转移其配色；只写入一次，返回受影响的待办数
+#[tauri::command]
+fn rename_tag(app: tauri::AppHandle, data_path: String, old_tag: String, new_tag: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let new_tag = new_tag.trim().to_string();
+    if new_tag.is_empty() {
+        return Err("标签不能为空".to_string());
+    }
+    if new_tag == old_tag {
+        return Ok(0);
+    }
+    let mut todos = load_todos(&data_path);
+    let mut affected = 0;
+    for todo in todos.iter_mut().filter(|t| t.tags.contains(&old_tag)) {
+        for tag in todo.tags.iter_mut().filter(|t| **t == old_tag) {
+            *tag = new_tag.clone();
+        }
+        let mut seen = std::collections::HashSet::new();
+        todo.tags.retain(|t| seen.insert(t.clone()));
+        affected += 1;
+    }
+    if affected > 0 {
+        save_todos(app.clone(), data_path, todos)?;
+    }
+    let mut config = get_app_config(app.clone());
+    if let Some(color) = config.tag_colors.remove(&old_tag) {
+        config.tag_colors.entry(new_tag).or_insert(color);
+        save_app_config(app, config)?;
+    }
+    Ok(affected)
+}
+
+/// 按大小写规则转换单个标签：lower 全部小写，title 将每个词（以空白、- 或 / 分隔）首字母大写、其余小写，其他规则原样返回
+fn case_tag(tag: &str, policy: &str) -> String {
+    match policy {
+        "lower" => tag.to_lowercase(),
+        "title" => {
+            let mut out = String::with_capacity(tag.len());
+            let mut word_start = true;
+            for c in tag.chars() {
+                if word_start {
+                    out.extend(c.to_uppercase());
+                } else {
+                    out.extend(c.to_lowercase());
+                }
+                word_start = c.is_whitespace() || c == '-' || c == '/';
+            }
+            out
+        }
+        _ => tag.to_string(),
+    }
+}
+
+/// 对待办的标签应用大小写规则，转换后重复的标签只保留第一个
+fn apply_tag_case_policy(tags: &mut Vec<String>, policy: &str) {
+    let mut seen = std::collections::HashSet::new();
+    *tags = tags.drain(..).map(|t| case_tag(&t, policy)).filter(|t| seen.insert(t.clone())).collect();
+}
+
+/// 按配置 tag_case_policy 统一所有待办已有标签的大小写，只有大小写不同的标签合并为一个：
+/// preserve 规则下合并为使用最多的写法（相同时取最先出现的）。标签配色一并转移，返回标签有变化的待办数
+#[tauri::command]
+fn normalize_tags(app: tauri::AppHandle, data_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut config = get_app_config(app.clone());
+    let policy = config.tag_case_policy.clone();
+    let mut todos = load_todos(&data_path);
+    // 小写形式 -> 各写法（按首次出现顺序）及出现次数
+    let mut variants: std::collections::HashMap<String, Vec<(String, usize)>> = std::collections::HashMap::new();
+    for tag in todos.iter().flat_map(|t| &t.tags) {
+        let entry = variants.entry(tag.to_lowercase()).or_default();
+        match entry.iter_mut().find(|(v, _)| v == tag) {
+            Some((_, count)) => *count += 1,
+            None => entry.push((tag.clone(), 1)),
+        }
+    }
+    let canonical = |tag: &str| -> String {
+        if policy != "preserve" {
+            return case_tag(tag, &policy);
+        }
+        variants
+            .get(&tag.to_lowercase())
+            .and_then(|v| v.iter().rev().max_by_key(|(_, count)| *count))
+            .map(|(v, _)| v.clone())
+            .unwrap_or_else(|| tag.to_string())
+    };
+    let mut changed = 0;
+    for todo in todos.iter_mut() {
+        let mut seen = std::collections::HashSet::new();
+        let tags: Vec<String> = todo.tags.iter().map(|t| canonical(t)).filter(|t| seen.insert(t.clone())).collect();
+        if tags != todo.tags {
+            todo.tags = tags;
+            changed += 1;
+        }
+    }
+    if changed > 0 {
+        save_todos(app.clone(), data_path, todos)?;
+    }
+    let colors = std::mem::take(&mut config.tag_colors);
+    let mut colors_changed = false;
+    for (tag, color) in colors {
+        let target = canonical(&tag);
+        colors_changed |= target != tag;
+        config.tag_colors.entry(target).or_insert(color);
+    }
+    if colors_changed {
+        save_app_config(app, config)?;
+    }
+    Ok(changed)
+}
+
+/// 从所有待办中移除标签 tag 及其配色；只写入一次，返回受影响的待办数
+#[tauri::command]
+fn delete_tag(app: tauri::AppHandle, data_path: String, tag: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut todos = load_todos(&data_path);
+    let mut affected = 0;
+    for todo in todos.iter_mut().filter(|t| t.tags.contains(&tag)) {
+        todo.tags.retain(|t| *t != tag);
+        affected += 1;
+    }
+    if affected > 0 {
+        save_todos(app.clone(), data_path, todos)?;
+    }
+    let mut config = get_app_config(app.clone());
+    if config.tag_colors.remove(&tag).is_some() {
+        save_app_config(app, config)?;
+    }
+    Ok(affected)
+}
+
+#[derive(Serialize, Debug)]
+struct TagProgress {
+    tag: String,
+    done: usize,
+    total: usize,
+    /// 完成百分比（0-100）
+    percent: f64,
+}
+
+/// 按标签统计已完成数与总数（不含已归档的待办），按完成百分比从高到低排序
+#[tauri::command]
+fn get_tag_progress(data_path: String) -> Vec<TagProgress> {
+    let data_path = expand_data_path(&data_path);
+    let mut counts: std::collections::BTreeMap<String, (usize, usize)> = std::collections::BTreeMap::new();
+    for todo in load_todos(&data_path).into_iter().filter(|t| !t.archived) {
+        let mut tags = todo.tags.clone();
+        tags.sort();
+        tags.dedup();
+        for tag in tags {
+            let entry = counts.entry(tag).or_default();
+            entry.1 += 1;
+            if todo.status == STATUS_COMPLETED {
+                entry.0 += 1;
+            }
+        }
+    }
+    let mut progress: Vec<TagProgress> = counts
+        .into_iter()
+        .map(|(tag, (done, total))| TagProgress {
+            tag,
+            done,
+            total,
+            percent: done as f64 * 100.0 / total as f64,
+        })
+        .collect();
+    progress.sort_by(|a, b| b.percent.total_cmp(&a.percent));
+    progress
+}
+
+#[derive(Serialize, Debug)]
+struct TagNode {
+    name: String,
+    /// 完整标签路径，如 "work/projectA"
+    path: String,
+    /// 带有该标签或其子标签的待办数（同一待办只计一次）
+    count: usize,
+    children: Vec<TagNode>,
+}
+
+fn build_tag_nodes(prefix: &str, todos_by_path: &std::collections::BTreeMap<String, std::collections::HashSet<String>>) -> Vec<TagNode> {
+    todos_by_path
+        .iter()
+        .filter_map(|(path, ids)| {
+            let name = if prefix.is_empty() { path.as_str() } else { path.strip_prefix(prefix)?.strip_prefix('/')? };
+            (!name.contains('/')).then(|| TagNode {
+                name: name.to_string(),
+                path: path.clone(),
+                count: ids.len(),
+                children: build_tag_nodes(path, todos_by_path),
+            })
+        })
+        .collect()
+}
+
+/// 将以 "/" 分隔的标签解析为层级树，每个节点附带待办数；有多个标签的待办出现在每个相关分支下。不含已归档的待办
+#[tauri::command]
+fn get_tag_tree(data_path: String) -> Vec<TagNode> {
+    let data_path = expand_data_path(&data_path);
+    let mut todos_by_path: std::collections::BTreeMap<String, std::collections::HashSet<String>> =
+        std::collections::BTreeMap::new();
+    for todo in load_todos(&data_path).into_iter().filter(|t| !t.archived) {
+        for tag in &todo.tags {
+            let segments: Vec<&str> = tag.split('/').map(str::trim).filter(|s| !s.is_empty()).collect();
+            for depth in 1..=segments.len() {
+                todos_by_path
+                    .entry(segments[..depth].join("/"))
+                    .or_default()
+                    .insert(todo.id.clone());
+            }
+        }
+    }
+    build_tag_nodes("", &todos_by_path)
+}
+
+/// 推迟待办到 until（RFC 3339 或 YYYY-MM-DD）；until 为空时取消推迟
+#[tauri::command]
+fn snooze_todo(app: tauri::AppHandle, data_path: String, folder_name: String, until: String) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let until = until.trim().to_string();
+    if !until.is_empty() && parse_timestamp(&until).is_none() {
+        return Err(format!("无效的时间: {}", until));
+    }
+    let mut todos = load_todos(&data_path);
+    let todo = todos
+        .iter_mut()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    todo.snoozed_until = if until.is_empty() { None } else { Some(normalize_timestamp(&until).unwrap_or(until)) };
+    let updated = todo.clone();
+    save_todos(app, data_path, todos)?;
+    Ok(updated)
+}
+
+/// 迁移旧数据：将待办中不带时区或非 UTC 的时间戳统一为 UTC RFC 3339（纯日期的截止日期不变），返回改写的字段数
+#[tauri::command]
+fn normalize_timestamps(app: tauri::AppHandle, data_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut todos = load_todos(&data_path);
+    let mut normalized = 0;
+    for todo in todos.iter_mut() {
+        for field in [
+            &mut todo.created_at,
+            &mut todo.updated_at,
+            &mut todo.completed_at,
+            &mut todo.snoozed_until,
+            &mut todo.due_date,
+        ] {
+            if let Some(utc) = field.as_deref().and_then(normalize_timestamp) {
+                *field = Some(utc);
+                normalized += 1;
+            }
+        }
+    }
+    if normalized > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(normalized)
+}
+
+/// 设置待办的提醒时间（RFC 3339 或 YYYY-MM-DD），remind_at 为空时取消提醒；只修改该字段
+#[tauri::command]
+fn set_reminder(app: tauri::AppHandle, data_path: String, folder_name: String, remind_at: String) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let remind_at = remind_at.trim().to_string();
+    if !remind_at.is_empty() && parse_timestamp(&remind_at).is_none() {
+        return Err(format!("无效的时间: {}", remind_at));
+    }
+    let mut todos = load_todos(&data_path);
+    let todo = todos
+        .iter_mut()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    todo.remind_at = if remind_at.is_empty() {
+        None
+    } else {
+        Some(normalize_timestamp(&remind_at).unwrap_or(remind_at))
+    };
+    let updated = todo.clone();
+    save_todos(app, data_path, todos)?;
+    Ok(updated)
+}
+
+/// 设置或清除（effort 为空）单个待办的预估工作量，只写入一次，返回更新后的待办
+#[tauri::command]
+fn set_effort(app: tauri::AppHandle, data_path: String, folder_name: String, effort: Option<u32>) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut todos = load_todos(&data_path);
+    let todo = todos
+        .iter_mut()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    todo.effort = effort;
+    let updated = todo.clone();
+    save_todos(app, data_path, todos)?;
+    Ok(updated)
+}
+
+/// 汇总指定状态下（不含已归档）所有待办的预估工作量，未估算的按 0 计
+#[tauri::command]
+fn get_total_effort(data_path: String, status: String) -> Result<u64, String> {
+    let data_path = expand_data_path(&data_path);
+    if !KNOWN_STATUSES.contains(&status.as_str()) {
+        return Err(format!("未知的状态: {}", status));
+    }
+    Ok(load_todos(&data_path)
+        .iter()
+        .filter(|t| t.status == status && !t.archived)
+        .filter_map(|t| t.effort)
+        .map(u64::from)
+        .sum())
+}
+
+/// 返回需要提醒的未完成待办：提醒时间已到，或截止日期为今天及以前。每个提醒时间 / 截止日期在本次运行中只提醒一次，
+/// 有新提醒时发出 "reminder" 事件
+#[tauri::command]
+fn check_due_reminders(app: tauri::AppHandle, data_path: String) -> Vec<TodoItem> {
+    // 锁定时不显示待办标题，解锁后再提醒
+    if APP_LOCKED.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+    let data_path = expand_data_path(&data_path);
+    let now = chrono::Local::now();
+    let today = now.date_naive();
+    // 锁中毒时仍沿用已记录的提醒，避免重复提醒
+    let mut fired = FIRED_REMINDERS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut due = Vec::new();
+    for todo in load_todos(&data_path).into_iter().filter(|t| t.status != STATUS_COMPLETED && !is_snoozed(t, &now)) {
+        let remind = todo.remind_at.as_deref().filter(|r| parse_timestamp(r).is_some_and(|r| r <= now));
+        let deadline = todo.due_date.as_deref().filter(|d| parse_due_date(d).is_some_and(|d| d <= today));
+        let keys: Vec<(String, String)> = [remind, deadline]
+            .into_iter()
+            .flatten()
+            .map(|k| (todo.id.clone(), k.to_string()))
+            .collect();
+        let mut is_new = false;
+        for key in keys {
+            is_new |= fired.insert(key);
+        }
+        if is_new {
+            due.push(todo);
+        }
+    }
+    if !due.is_empty() {
+        let _ = app.emit("reminder", &due);
+    }
+    due
+}
+
+fn serialize_index(todos: &[TodoItem]) -> serde_json::Result<String> {
+    if PRETTY_INDEX.load(Ordering::Relaxed) {
+        serde_json::to_string_pretty(todos)
+    } else {
+        serde_json::to_string(todos)
+    }
+}
+
+/// 保存待办列表，返回标题超过 max_title_length 而被截断的待办文件夹
+#[tauri::command]
+fn save_todos(app: tauri::AppHandle, data_path: String, mut todos: Vec<TodoItem>) -> Result<Vec<String>, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    if !data_dir.exists() {
+        fs::create_dir_all(data_dir).map_err(|e| log_error("save_todos", e))?;
+    }
+    // 新出现的待办由后端记录创建时间，内容有变化的记录修改时间，状态变为已完成的记录完成时间；
+    // 旧数据中缺失的字段保持为空
+    let existing: std::collections::HashMap<String, TodoItem> =
+        load_todos(&data_path).into_iter().map(|t| (t.id.clone(), t)).collect();
+    let now = utc_now_rfc3339();
+    let max_title = MAX_TITLE_LENGTH.load(Ordering::Relaxed) as usize;
+    let tag_policy = TAG_CASE_POLICY.lock().map(|p| p.clone()).unwrap_or_default();
+    let mut truncated = Vec::new();
+    for todo in todos.iter_mut() {
+        if max_title > 0 && todo.title.chars().count() > max_title {
+            todo.title = todo.title.chars().take(max_title).collect::<String>().trim_end().to_string();
+            truncated.push(todo.folder_name.clone());
+        }
+        if todo.board.trim().is_empty() {
+            todo.board = default_board();
+        }
+        if tag_policy != "preserve" {
+            apply_tag_case_policy(&mut todo.tags, &tag_policy);
+        }
+        match existing.get(&todo.id) {
+            None => {
+                if todo.created_at.is_none() {
+                    todo.created_at = Some(now.clone());
+                }
+                todo.updated_at.get_or_insert_with(|| now.clone());
+                if todo.status == STATUS_COMPLETED {
+                    todo.completed_at.get_or_insert_with(|| now.clone());
+                } else {
+                    todo.completed_at = None;
+                }
+            }
+            Some(old) => {
+                // 完成时间只由后端根据状态流转决定，忽略前端传入的值
+                todo.completed_at = if todo.status == old.status {
+                    old.completed_at.as_deref().map(|c| normalize_timestamp(c).unwrap_or_else(|| c.to_string()))
+                } else {
+                    (todo.status == STATUS_COMPLETED).then(|| now.clone())
+                };
+                let changed = TodoItem { updated_at: old.updated_at.clone(), ..todo.clone() } != *old;
+                if changed {
+                    todo.updated_at = Some(now.clone());
+                }
+            }
+        }
+    }
+    if USE_SQLITE.load(Ordering::Relaxed) {
+        with_write_retry("save_todos", || {
+            write_todos_sqlite(data_dir, &todos)?;
+            if VERIFY_WRITES.load(Ordering::Relaxed) && read_todos_sqlite(data_dir)? != todos {
+                return Err("写入校验失败，读回的待办列表与写入内容不一致: todos.db".to_string());
+            }
+            Ok(())
+        })
+        .map_err(|e| log_error("save_todos", e))?;
+    } else {
+        let todos_path = data_dir.join("todos.json");
+        let content = serialize_index(&todos).map_err(|e| log_error("save_todos", e))?;
+        with_write_retry("save_todos", || {
+            write_atomic(&todos_path, content.as_bytes()).map_err(|e| e.to_string())?;
+            verify_written_file(&todos_path, content.as_bytes())
+        })
+        .map_err(|e| log_error("save_todos", e))?;
+    }
+    if !truncated.is_empty() {
+        write_log(LogLevel::Info, "save_todos", &format!("{} 个标题超出长度上限已截断", truncated.len()));
+    }
+    let _ = app.emit("todos-changed", ());
+    schedule_post_save_hook(&app, &data_path);
+    Ok(truncated)
+}
+
+/// 列出标题超过 max_title_length 的待办，供用户清理；未设置上限时返回空列表
+#[tauri::command]
+fn find_long_titles(data_path: String) -> Vec<TodoItem> {
+    let data_path = expand_data_path(&data_path);
+    let max = MAX_TITLE_LENGTH.load(Ordering::Relaxed) as usize;
+    if max == 0 {
+        return Vec::new();
+    }
+    load_todos(&data_path).into_iter().filter(|t| t.title.chars().count() > max).collect()
+}
+
+/// 在最后一次保存后 POST_SAVE_HOOK_DELAY 异步执行 post_save_hook，不阻塞保存；
+/// 结果写入日志并通过 "post-save-hook" 事件通知界面
+fn schedule_post_save_hook(app: &tauri::AppHandle, data_path: &str) {
+    let hook = POST_SAVE_HOOK.lock().map(|h| h.clone()).unwrap_or_default();
+    if hook.is_empty() {
+        return;
+    }
+    let generation = POST_SAVE_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    let app = app.clone();
+    let data_path = data_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        use tauri_plugin_shell::ShellExt;
+        tokio::time::sleep(POST_SAVE_HOOK_DELAY).await;
+        if POST_SAVE_GENERATION.load(Ordering::Relaxed) != generation {
+            return;
+        }
+        let command = if cfg!(windows) {
+            app.shell().command("cmd").args(["/C", hook.as_str(), data_path.as_str()])
+        } else {
+            let script = format!("{} \"$1\"", hook);
+            app.shell().command("sh").args(["-c", script.as_str(), "sh", data_path.as_str()])
+        };
+        #[derive(Clone, Serialize)]
+        struct Payload { success: bool, code: Option<i32> }
+        match command.output().await {
+            Ok(output) => {
+                let code = output.status.code();
+                let success = output.status.success();
+                let level = if success { LogLevel::Info } else { LogLevel::Error };
+                write_log(level, "post_save_hook", &format!("退出码: {:?}", code));
+                let _ = app.emit("post-save-hook", Payload { success, code });
+            }
+            Err(e) => {
+                log_error("post_save_hook", e);
+                let _ = app.emit("post-save-hook", Payload { success: false, code: None });
+            }
+        }
+    });
+}
+
+#[derive(Serialize, Debug)]
+struct CompactReport {
+    todo_count: usize,
+    bytes_before: u64,
+    bytes_after: u64,
+}
+
+/// 重写待办索引：只保留当前版本识别的字段（未知字段随反序列化丢弃），并按 pretty_index 重新序列化。
+/// 索引无法解析时中止，避免覆盖
+#[tauri::command]
+fn compact_index(data_path: String) -> Result<CompactReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    if USE_SQLITE.load(Ordering::Relaxed) {
+        let db_path = data_dir.join(SQLITE_FILE);
+        let bytes_before = fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
+        let todos = read_todos_sqlite(data_dir).map_err(|e| log_error("compact_index", e))?;
+        write_todos_sqlite(data_dir, &todos).map_err(|e| log_error("compact_index", e))?;
+        open_todo_db(data_dir)?.execute_batch("VACUUM;").map_err(|e| log_error("compact_index", e))?;
+        return Ok(CompactReport {
+            todo_count: todos.len(),
+            bytes_before,
+            bytes_after: fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0),
+        });
+    }
+    let todos_path = data_dir.join("todos.json");
+    let content = fs::read_to_string(&todos_path).map_err(|e| log_error("compact_index", e))?;
+    let todos: Vec<TodoItem> = serde_json::from_str(strip_bom(&content))
+        .map_err(|e| format!("todos.json 解析失败，已取消整理以免覆盖: {}", e))?;
+    let compacted = serialize_index(&todos).map_err(|e| e.to_string())?;
+    write_atomic(&todos_path, compacted.as_bytes()).map_err(|e| log_error("compact_index", e))?;
+    Ok(CompactReport {
+        todo_count: todos.len(),
+        bytes_before: content.len() as u64,
+        bytes_after: compacted.len() as u64,
+    })
+}
+
+#[derive(Serialize, Debug, Default)]
+struct ResaveReport {
+    processed: usize,
+    /// 无法解析或写入失败的文件及原因
+    failed: Vec<String>,
+}
+
+/// 逐一解析 todos.json 与各待办的 content.json，解析通过的按当前格式重新序列化并原子写回；
+/// 解析失败的文件保持原样并记入 failed。用于手动修改或迁移后整体检查一遍工作区
+#[tauri::command]
+fn resave_all(data_path: String) -> Result<ResaveReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    let mut report = ResaveReport::default();
+    let mut resave = |path: &Path, serialize: &dyn Fn(&str) -> Result<String, String>| {
+        let label = path.strip_prefix(data_dir).unwrap_or(path).to_string_lossy().into_owned();
+        let result = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serialize(strip_bom(&c)))
+            .and_then(|content| {
+                write_atomic(path, content.as_bytes()).map_err(|e| e.to_string())?;
+                verify_written_file(path, content.as_bytes())
+            });
+        match result {
+            Ok(()) => report.processed += 1,
+            Err(e) => report.failed.push(format!("{}: {}", label, e)),
+        }
+    };
+    let todos_path = data_dir.join("todos.json");
+    if todos_path.exists() {
+        resave(&todos_path, &|c| {
+            let todos: Vec<TodoItem> = serde_json::from_str(strip_bom(c)).map_err(|e| e.to_string())?;
+            serialize_index(&todos).map_err(|e| e.to_string())
+        });
+    }
+    for todo in load_todos(&data_path) {
+        let Ok(folder_name) = sanitize_folder_name(&todo.folder_name) else { continue };
+        let detail_path = data_dir.join(folder_name).join("content.json");
+        if detail_path.exists() {
+            resave(&detail_path, &|c| {
+                let value: serde_json::Value = serde_json::from_str(strip_bom(c)).map_err(|e| e.to_string())?;
+                serde_json::to_string(&value).map_err(|e| e.to_string())
+            });
+        }
+    }
+    if !report.failed.is_empty() {
+        write_log(LogLevel::Error, "resave_all", &format!("{} 个文件未能重新保存", report.failed.len()));
+    }
+    Ok(report)
+}
+
+/// 将 todos.json 导入 todos.db；todos.json 原样保留，切换 storage_backend 后生效
+#[tauri::command]
+fn migrate_json_to_sqlite(data_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    let todos_path = data_dir.join("todos.json");
+    let todos: Vec<TodoItem> = if todos_path.exists() {
+        let content = fs::read_to_string(&todos_path).map_err(|e| log_error("migrate_json_to_sqlite", e))?;
+        serde_json::from_str(strip_bom(&content)).map_err(|e| log_error("migrate_json_to_sqlite", e))?
+    } else {
+        vec![]
+    };
+    write_todos_sqlite(data_dir, &todos).map_err(|e| log_error("migrate_json_to_sqlite", e))?;
+    Ok(todos.len())
+}
+
+/// 扫描数据目录下的待办文件夹（含 archive/<年份>/ 下的），为 todos.json 中缺失的文件夹补回索引项（已有条目保持不变）
+#[tauri::command]
+fn rebuild_index(app: tauri::AppHandle, data_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    if !data_dir.exists() {
+        return Ok(0);
+    }
+    let todos_path = data_dir.join("todos.json");
+    if USE_SQLITE.load(Ordering::Relaxed) {
+        read_todos_sqlite(data_dir).map_err(|e| format!("todos.db 读取失败，已取消重建以免覆盖: {}", e))?;
+    } else if todos_path.exists() {
+        let content = fs::read_to_string(&todos_path).map_err(|e| e.to_string())?;
+        serde_json::from_str::<Vec<TodoItem>>(strip_bom(&content))
+            .map_err(|e| format!("todos.json 解析失败，已取消重建以免覆盖: {}", e))?;
+    }
+    let mut todos = load_todos(&data_path);
+    let known: std::collections::HashSet<String> =
+        todos.iter().map(|t| t.folder_name.clone()).collect();
+
+    let mut found: Vec<String> = todo_folders_on_disk(data_dir).into_iter().filter(|f| !known.contains(f)).collect();
+    found.sort();
+
+    let added = found.len();
+    let status = get_app_config(app.clone()).default_status;
+    for folder_name in found {
+        let content = get_todo_detail(data_path.clone(), folder_name.clone()).unwrap_or_default();
+        let mut lines = Vec::new();
+        collect_block_text(&parse_detail_nodes(&content), &mut lines);
+        let title = lines
+            .iter()
+            .map(|l| l.trim())
+            .find(|l| !l.is_empty())
+            .map(|l| l.chars().take(100).collect())
+            .unwrap_or_else(|| "未命名代办".to_string());
+        todos.push(TodoItem {
+            id: Uuid::new_v4().to_string(),
+            title,
+            status: status.clone(),
+            archived: folder_name.starts_with(&format!("{}/", ARCHIVE_DIR)),
+            folder_name,
+            ..Default::default()
+        });
+    }
+    if added > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(added)
+}
+
+#[derive(Serialize, Debug)]
+struct UpcomingDay {
+    date: String,
+    todos: Vec<TodoItem>,
+}
+
+#[derive(Serialize, Debug)]
+struct UpcomingReport {
+    overdue_count: usize,
+    overdue: Vec<TodoItem>,
+    days: Vec<UpcomingDay>,
+}
+
+/// 未完成且截止日期在今天起 days 天内的待办按天分组，已过期的单独列出；组内按优先级排序
+#[tauri::command]
+fn get_upcoming(data_path: String, days: u32) -> UpcomingReport {
+    let data_path = expand_data_path(&data_path);
+    let today = chrono::Local::now().date_naive();
+    let last = today + chrono::Duration::days(days as i64);
+    let mut overdue = Vec::new();
+    let mut by_day: std::collections::BTreeMap<chrono::NaiveDate, Vec<TodoItem>> =
+        std::collections::BTreeMap::new();
+    for todo in load_todos(&data_path) {
+        if todo.status == STATUS_COMPLETED {
+            continue;
+        }
+        let due = match todo.due_date.as_deref().and_then(parse_due_date) {
+            Some(d) => d,
+            None => continue,
+        };
+        if due < today {
+            overdue.push(todo);
+        } else if due <= last {
+            by_day.entry(due).or_default().push(todo);
+        }
+    }
+    overdue.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.due_date.cmp(&b.due_date)));
+    let days = by_day
+        .into_iter()
+        .map(|(date, mut todos)| {
+            todos.sort_by_key(|t| std::cmp::Reverse(t.priority));
+            UpcomingDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                todos,
+            }
+        })
+        .collect();
+    UpcomingReport {
+        overdue_count: overdue.len(),
+        overdue,
+        days,
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct MonthDay {
+    date: String,
+    /// 星期（0 为周一，6 为周日）
+    weekday: u32,
+    is_today: bool,
+    /// 截止日期为当天的待办（含已完成的），已过期未完成的移到今天的 overdue 中
+    todos: Vec<TodoItem>,
+    /// 只在今天的格子中：截止日期早于今天、仍未完成的待办
+    overdue: Vec<TodoItem>,
+    count: usize,
+}
+
+#[derive(Serialize, Debug)]
+struct MonthView {
+    year: i32,
+    month: u32,
+    /// 当月 1 日的星期（0 为周一），供前端在首行前补空格
+    first_weekday: u32,
+    days: Vec<MonthDay>,
+}
+
+/// 以月历形式返回 year 年 month 月每天截止的待办（不含已归档的），组内按优先级排序；
+/// 今天在该月内时，已过期未完成的待办（含更早月份的）归入今天
+#[tauri::command]
+fn get_month_view(data_path: String, year: i32, month: u32) -> Result<MonthView, String> {
+    use chrono::Datelike;
+    let data_path = expand_data_path(&data_path);
+    let first = chrono::NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| format!("无效的月份: {}-{}", year, month))?;
+    let next_month = if month == 12 { chrono::NaiveDate::from_ymd_opt(year + 1, 1, 1) } else { chrono::NaiveDate::from_ymd_opt(year, month + 1, 1) }
+        .ok_or_else(|| format!("无效的月份: {}-{}", year, month))?;
+    let today = chrono::Local::now().date_naive();
+    let today_in_month = first <= today && today < next_month;
+    let mut by_day: std::collections::BTreeMap<chrono::NaiveDate, Vec<TodoItem>> = std::collections::BTreeMap::new();
+    let mut overdue = Vec::new();
+    for todo in load_todos(&data_path).into_iter().filter(|t| !t.archived) {
+        let Some(due) = todo.due_date.as_deref().and_then(parse_due_date) else { continue };
+        if today_in_month && due < today && todo.status != STATUS_COMPLETED {
+            overdue.push(todo);
+        } else if first <= due && due < next_month {
+            by_day.entry(due).or_default().push(todo);
+        }
+    }
+    overdue.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.due_date.cmp(&b.due_date)));
+    let days = first
+        .iter_days()
+        .take_while(|d| *d < next_month)
+        .map(|date| {
+            let mut todos = by_day.remove(&date).unwrap_or_default();
+            todos.sort_by_key(|t| std::cmp::Reverse(t.priority));
+            let overdue = if date == today { std::mem::take(&mut overdue) } else { Vec::new() };
+            MonthDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                weekday: date.weekday().num_days_from_monday(),
+                is_today: date == today,
+                count: todos.len() + overdue.len(),
+                todos,
+                overdue,
+            }
+        })
+        .collect();
+    Ok(MonthView {
+        year,
+        month,
+        first_weekday: first.weekday().num_days_from_monday(),
+        days,
+    })
+}
+
+#[derive(Serialize, Debug)]
+struct TodayReport {
+    count: usize,
+    todos: Vec<TodoItem>,
+}
+
+/// 专注模式：未完成、未推迟，且标记为今天或截止日期为今天及以前的待办，按优先级排序
+#[tauri::command]
+fn get_today(data_path: String) -> TodayReport {
+    let data_path = expand_data_path(&data_path);
+    let now = chrono::Local::now();
+    let today = now.date_naive();
+    let mut todos: Vec<TodoItem> = load_todos(&data_path)
+        .into_iter()
+        .filter(|t| t.status != STATUS_COMPLETED && !is_snoozed(t, &now))
+        .filter(|t| t.today || t.due_date.as_deref().and_then(parse_due_date).is_some_and(|d| d <= today))
+        .collect();
+    todos.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.due_date.cmp(&b.due_date)));
+    TodayReport {
+        count: todos.len(),
+        todos,
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct WeeklyDigest {
+    /// 最近 7 天内完成的待办
+    completed: Vec<TodoItem>,
+    /// 最近 7 天内新建的待办
+    created: Vec<TodoItem>,
+    overdue: Vec<TodoItem>,
+    /// 今天起 7 天内到期的待办，按天分组
+    upcoming: Vec<UpcomingDay>,
+}
+
+/// 每周回顾：一次返回最近 7 天完成与新建的待办、已过期的待办以及未来 7 天到期的待办
+#[tauri::command]
+fn get_weekly_digest(data_path: String) -> WeeklyDigest {
+    let week_ago = chrono::Local::now() - chrono::Duration::days(7);
+    let within_week = |value: &Option<String>| value.as_deref().and_then(parse_timestamp).is_some_and(|t| t >= week_ago);
+    let todos = load_todos(&expand_data_path(&data_path));
+    let completed = todos
+        .iter()
+        .filter(|t| t.status == STATUS_COMPLETED && within_week(&t.completed_at))
+        .cloned()
+        .collect();
+    let created = todos.iter().filter(|t| within_week(&t.created_at)).cloned().collect();
+    let upcoming = get_upcoming(data_path, 7);
+    WeeklyDigest {
+        completed,
+        created,
+        overdue: upcoming.overdue,
+        upcoming: upcoming.days,
+    }
+}
+
+/// 资源改属另一个待办文件夹（folder_name 可含 archive/<年份>/ 前缀）后改写节点树中各资源的 url（assetPath 为相对路径，无需修改）：
+/// url 中的路径可能以 / 或 \ 分隔且经过编码，按 url 实际使用的分隔符替换前后都是分隔符的旧文件夹路径。返回是否有改动
+fn retarget_folder_urls(nodes: &mut [serde_json::Value], old_folder: &str, new_folder: &str) -> bool {
+    let mut changed = false;
+    for node in nodes.iter_mut() {
+        if let Some(url) = node.get("url").and_then(|u| u.as_str()) {
+            for (sep, encode) in [("%2F", true), ("%5C", true), ("/", false), ("\\", false)] {
+                let join = |folder: &str| {
+                    folder
+                        .split('/')
+                        .map(|p| if encode { encode_uri_component(p) } else { p.to_string() })
+                        .fold(String::new(), |acc, p| acc + sep + &p)
+                        + sep
+                };
+                let old_tail = join(old_folder);
+                if url.contains(&old_tail) {
+                    node["url"] = serde_json::Value::String(url.replace(&old_tail, &join(new_folder)));
+                    changed = true;
+                    break;
+                }
+            }
+        }
+        if let Some(children) = node.get_mut("children").and_then(|c| c.as_array_mut()) {
+            changed |= retarget_folder_urls(children, old_folder, new_folder);
+        }
+    }
+    changed
+}
+
+/// 数据目录变化（如导入其他设备的工作区）后改写各资源 url 中的数据目录：url 中的路径经过编码，
+/// 只替换位于 url 路径开头、且其后紧跟分隔符的旧数据目录。返回是否有改动
+fn retarget_data_path_urls(nodes: &mut [serde_json::Value], old_root: &str, new_root: &str) -> bool {
+    fn walk(nodes: &mut [serde_json::Value], old_root: &str, new_root: &str) -> bool {
+        let mut changed = false;
+        for node in nodes.iter_mut() {
+            if let Some(url) = node.get("url").and_then(|u| u.as_str()) {
+                let start = url.find("localhost/").map_or(0, |i| i + "localhost/".len());
+                let rest = url[start..].strip_prefix(old_root).filter(|r| r.starts_with("%2F") || r.starts_with("%5C"));
+                if let Some(rest) = rest {
+                    node["url"] = serde_json::Value::String(format!("{}{}{}", &url[..start], new_root, rest));
+                    changed = true;
+                }
+            }
+            if let Some(children) = node.get_mut("children").and_then(|c| c.as_array_mut()) {
+                changed |= walk(children, old_root, new_root);
+            }
+        }
+        changed
+    }
+    let encode = |root: &str| encode_uri_component(root.trim_end_matches(['/', '\\']));
+    walk(nodes, &encode(old_root), &encode(new_root))
+}
+
+/// 待办文件夹移动后同步改写其 content.json 中资源的 url
+fn retarget_moved_folder(data_dir: &Path, old_folder: &str, new_folder: &str) {
+    let detail_path = data_dir.join(new_folder).join("content.json");
+    let Ok(content) = fs::read_to_string(&detail_path) else { return };
+    let mut nodes = parse_detail_nodes(&content);
+    if retarget_folder_urls(&mut nodes, old_folder, new_folder) {
+        if let Ok(content) = serde_json::to_string(&nodes) {
+            if let Err(e) = write_atomic(&detail_path, content.as_bytes()) {
+                log_error("retarget_moved_folder", e);
+            }
+        }
+    }
+}
+
+/// 将一个待办 assets 目录下的文件移入另一个待办；同名文件（资源按内容哈希命名）视为相同，直接跳过
+fn move_assets_into(src_folder: &Path, dest_folder: &Path) -> Result<(), String> {
+    let src_assets = src_folder.join(assets_dir_name());
+    if !src_assets.is_dir() {
+        return Ok(());
+    }
+    let dest_assets = dest_folder.join(assets_dir_name());
+    fs::create_dir_all(&dest_assets).map_err(|e| e.to_string())?;
+    for entry in fs::read_dir(&src_assets).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        let target = dest_assets.join(entry.file_name());
+        if !path.is_file() || target.exists() {
+            continue;
+        }
+        if fs::rename(&path, &target).is_err() {
+            fs::copy(&path, &target).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// 按标题（去除首尾空白、忽略大小写）分组，返回包含多个待办的组
+#[tauri::command]
+fn find_duplicate_titles(data_path: String) -> Vec<Vec<TodoItem>> {
+    let data_path = expand_data_path(&data_path);
+    let mut groups: Vec<(String, Vec<TodoItem>)> = Vec::new();
+    for todo in load_todos(&data_path) {
+        let key = todo.title.trim().to_lowercase();
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, items)) => items.push(todo),
+            None => groups.push((key, vec![todo])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(_, items)| items)
+        .filter(|items| items.len() > 1)
+        .collect()
+}
+
+/// 将 merge_folders 对应的待办详情（及资源）追加到 keep_folder 中，并删除被合并的待办
+#[tauri::command]
+fn merge_todos(
+    app: tauri::AppHandle,
+    data_path: String,
+    keep_folder: String,
+    merge_folders: Vec<String>,
+) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    sanitize_folder_name(&keep_folder)?;
+    for folder in &merge_folders {
+        sanitize_folder_name(folder)?;
+    }
+    let mut todos = load_todos(&data_path);
+    let keep = todos
+        .iter()
+        .find(|t| t.folder_name == keep_folder)
+        .cloned()
+        .ok_or_else(|| format!("待办不存在: {}", keep_folder))?;
+    let data_dir = Path::new(&data_path);
+    let keep_path = data_dir.join(&keep_folder);
+    if let Some(missing) = merge_folders
+        .iter()
+        .find(|f| !todos.iter().any(|t| t.folder_name == **f))
+    {
+        return Err(format!("待办不存在: {}", missing));
+    }
+    let mut nodes = parse_detail_nodes(&get_todo_detail(data_path.clone(), keep_folder.clone())?);
+
+    for merged in todos
+        .iter()
+        .filter(|t| t.folder_name != keep_folder && merge_folders.contains(&t.folder_name))
+    {
+        let folder = &merged.folder_name;
+        let mut merged_nodes = parse_detail_nodes(&get_todo_detail(data_path.clone(), folder.clone())?);
+        retarget_folder_urls(&mut merged_nodes, folder, &keep_folder);
+        nodes.push(serde_json::json!({
+            "type": "h2",
+            "id": Uuid::new_v4().to_string(),
+            "children": [{ "type": "text", "value": &merged.title }],
+        }));
+        nodes.extend(merged_nodes);
+        move_assets_into(&data_dir.join(folder), &keep_path).map_err(|e| log_error("merge_todos", e))?;
+    }
+
+    let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+    write_atomic(&keep_path.join("content.json"), content.as_bytes()).map_err(|e| log_error("merge_todos", e))?;
+    for folder in merge_folders.iter().filter(|f| **f != keep_folder) {
+        delete_todo_folder(data_path.clone(), folder.clone())?;
+    }
+    todos.retain(|t| t.folder_name == keep_folder || !merge_folders.contains(&t.folder_name));
+    save_todos(app.clone(), data_path, todos)?;
+    #[derive(Clone, Serialize)]
+    struct Payload { folder_name: String }
+    let _ = app.emit("todo-detail-changed", Payload { folder_name: keep_folder });
+    Ok(keep)
+}
+
+/// 按分隔行拆分待办：split_markers 为空时在每个一二级标题处拆分，否则在文本与任一标记相同的顶层块处拆分。
+/// 每段新建一个待办（标题为分隔行文本），复制其引用的资源，并沿用原待办的状态、截止日期、优先级、标签、看板与前置待办；
+/// 工作量是对原待办整体的估计，不复制到各段。keep_original 为 false 时原待办移入回收站，
+/// 第一个分隔行之前的内容以原标题另建待办。返回新建的文件夹名
+#[tauri::command]
+fn split_todo(
+    app: tauri::AppHandle,
+    data_path: String,
+    folder_name: String,
+    split_markers: Vec<String>,
+    keep_original: bool,
+) -> Result<Vec<String>, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let mut todos = load_todos(&data_path);
+    let original = todos
+        .iter()
+        .find(|t| t.folder_name == folder_name)
+        .cloned()
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    let markers: Vec<&str> = split_markers.iter().map(|m| m.trim()).filter(|m| !m.is_empty()).collect();
+
+    let mut sections: Vec<(String, Vec<serde_json::Value>)> = vec![(original.title.clone(), Vec::new())];
+    for node in parse_detail_nodes(&get_todo_detail(data_path.clone(), folder_name.clone())?) {
+        let mut text = String::new();
+        node_inline_text(&node, &mut text);
+        let text = text.trim().to_string();
+        let is_split = if markers.is_empty() {
+            matches!(node["type"].as_str(), Some("h1" | "h2")) && !text.is_empty()
+        } else {
+            markers.contains(&text.as_str())
+        };
+        if is_split {
+            sections.push((text, Vec::new()));
+        } else if let Some((_, nodes)) = sections.last_mut() {
+            nodes.push(node);
+        }
+    }
+    if sections.len() < 2 {
+        return Err("没有找到可拆分的位置".to_string());
+    }
+    // 原待办保留时第一段已在其中，无需另建
+    let preamble_empty = sections[0].1.is_empty();
+    if keep_original || preamble_empty {
+        sections.remove(0);
+    }
+
+    let source = Path::new(&data_path).join(&folder_name);
+    let mut created = Vec::new();
+    for (title, mut nodes) in sections {
+        let new_folder = create_todo_folder(data_path.clone())?;
+        let dest = Path::new(&data_path).join(&new_folder);
+        let mut assets = std::collections::HashSet::new();
+        collect_asset_paths(&nodes, &mut assets);
+        // 共用资源（../.assets/）按引用共享，不复制（源与目标会解析到资源池中的同一个文件）
+        for asset in assets.iter().filter(|a| !a.starts_with(SHARED_ASSET_PREFIX)) {
+            let from = source.join(asset);
+            if from.is_file() {
+                fs::copy(&from, dest.join(asset)).map_err(|e| log_error("split_todo", e))?;
+            }
+        }
+        retarget_folder_urls(&mut nodes, &folder_name, &new_folder);
+        let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+        write_atomic(&dest.join("content.json"), content.as_bytes()).map_err(|e| log_error("split_todo", e))?;
+        todos.push(TodoItem {
+            id: Uuid::new_v4().to_string(),
+            title,
+            status: original.status.clone(),
+            folder_name: new_folder.clone(),
+            due_date: original.due_date.clone(),
+            priority: original.priority,
+            tags: original.tags.clone(),
+            board: original.board.clone(),
+            depends_on: original.depends_on.clone(),
+            ..Default::default()
+        });
+        created.push(new_folder);
+    }
+    if !keep_original {
+        delete_todo_folder_to_os_trash(data_path.clone(), folder_name.clone())?;
+        todos.retain(|t| t.folder_name != folder_name);
+    }
+    save_todos(app, data_path, todos)?;
+    Ok(created)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct DailyStats {
+    date: String,
+    /// 当天新建的待办数
+    created: usize,
+    /// 当天完成的待办数（与上一条记录的已完成总数相比）
+    completed: usize,
+    total: usize,
+    completed_total: usize,
+}
+
+fn read_stats_history(data_dir: &Path) -> Vec<DailyStats> {
+    fs::read_to_string(data_dir.join("stats_history.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(strip_bom(&c)).ok())
+        .unwrap_or_default()
+}
+
+/// 记录今天的统计；同一天重复调用只会更新当天的那一条
+#[tauri::command]
+fn record_daily_stats(data_path: String) -> Result<DailyStats, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    if !data_dir.exists() {
+        return Err(format!("数据目录不存在: {}", data_path));
+    }
+    let today = chrono::Local::now().date_naive();
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let todos = load_todos(&data_path);
+    let completed_total = todos.iter().filter(|t| t.status == STATUS_COMPLETED).count();
+    let created = todos
+        .iter()
+        .filter_map(|t| t.created_at.as_deref())
+        .filter_map(|c| chrono::DateTime::parse_from_rfc3339(c).ok())
+        .filter(|c| c.with_timezone(&chrono::Local).date_naive() == today)
+        .count();
+
+    let mut history = read_stats_history(data_dir);
+    history.retain(|d| d.date != today_str);
+    let completed = history
+        .last()
+        .map(|prev| completed_total.saturating_sub(prev.completed_total))
+        .unwrap_or(0);
+    let stats = DailyStats {
+        date: today_str,
+        created,
+        completed,
+        total: todos.len(),
+        completed_total,
+    };
+    history.push(stats.clone());
+    let content = serde_json::to_string(&history).map_err(|e| e.to_string())?;
+    write_atomic(&data_dir.join("stats_history.json"), content.as_bytes())
+        .map_err(|e| log_error("record_daily_stats", e))?;
+    Ok(stats)
+}
+
+/// 返回最近 days 天的统计记录（按日期升序）
+#[tauri::command]
+fn get_stats_history(data_path: String, days: u32) -> Vec<DailyStats> {
+    let data_path = expand_data_path(&data_path);
+    let since = chrono::Local::now().date_naive() - chrono::Duration::days(days as i64);
+    let since = since.format("%Y-%m-%d").to_string();
+    read_stats_history(Path::new(&data_path))
+        .into_iter()
+        .filter(|d| d.date > since)
+        .collect()
+}
+
+/// 估算完成速度时参考的最近天数
+const FORECAST_WINDOW_DAYS: u32 = 14;
+
+#[derive(Serialize, Debug)]
+struct CompletionForecast {
+    /// 最近 FORECAST_WINDOW_DAYS 天内平均每天完成的待办数
+    rate_per_day: f64,
+    open_count: usize,
+    /// 按当前速度清空未完成待办所需的天数（向上取整）；速度为 0 时为空
+    estimated_days: Option<u32>,
+}
+
+/// 根据统计记录中最近的完成速度，估算清空当前未完成待办所需的天数
+#[tauri::command]
+fn forecast_completion(data_path: String) -> CompletionForecast {
+    let completed: usize = get_stats_history(data_path.clone(), FORECAST_WINDOW_DAYS)
+        .iter()
+        .map(|d| d.completed)
+        .sum();
+    let data_path = expand_data_path(&data_path);
+    let open_count = load_todos(&data_path)
+        .iter()
+        .filter(|t| t.status != STATUS_COMPLETED)
+        .count();
+    let rate_per_day = completed as f64 / FORECAST_WINDOW_DAYS as f64;
+    let estimated_days = (rate_per_day > 0.0).then(|| (open_count as f64 / rate_per_day).ceil() as u32);
+    CompletionForecast { rate_per_day, open_count, estimated_days }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Scratchpad {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
+/// 读取数据目录根部 scratchpad.json 中的全局便签内容
+#[tauri::command]
+fn get_scratchpad(data_path: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let path = Path::new(&data_path).join("scratchpad.json");
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| log_error("get_scratchpad", e))?;
+    let pad: Scratchpad = serde_json::from_str(strip_bom(&content)).map_err(|e| log_error("get_scratchpad", e))?;
+    Ok(pad.content)
+}
+
+#[tauri::command]
+fn save_scratchpad(data_path: String, content: String) -> Result<(), String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    if !data_dir.exists() {
+        fs::create_dir_all(data_dir).map_err(|e| log_error("save_scratchpad", e))?;
+    }
+    let pad = Scratchpad {
+        content,
+        updated_at: Some(utc_now_rfc3339()),
+    };
+    let json = serde_json::to_string(&pad).map_err(|e| log_error("save_scratchpad", e))?;
+    write_atomic(&data_dir.join("scratchpad.json"), json.as_bytes())
+        .map_err(|e| log_error("save_scratchpad", e))
+}
+
+/// 校验来自前端的待办文件夹名：不允许为空、包含路径分隔符或 ".."、以 "." 开头（保留目录）或含控制字符。
+/// 文件夹名可能已被 rename_folders_by_title 改为可读名称，因此不要求必须是 UUID
+fn sanitize_folder_name(name: &str) -> Result<String, String> {
+    let invalid_segment = |segment: &str| {
+        segment.is_empty()
+            || segment.contains(['/', '\\'])
+            || segment.contains("..")
+            || segment.starts_with('.')
+            || segment.chars().any(|c| c.is_control())
+            || Path::new(segment).is_absolute()
+    };
+    // 按年份归档的待办文件夹名为 archive/<四位年份>/<文件夹名>
+    let archived = name
+        .strip_prefix(ARCHIVE_DIR)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(year, _)| year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()));
+    let invalid = match archived {
+        Some((_, inner)) => invalid_segment(inner),
+        None => invalid_segment(name),
+    };
+    if invalid {
+        return Err(format!("无效的文件夹名称: {}", name));
+    }
+    Ok(name.to_string())
+}
+
+#[tauri::command]
+fn create_todo_folder(data_path: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = Uuid::new_v4().to_string();
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    fs::create_dir_all(&folder_path).map_err(|e| log_error("create_todo_folder", e))?;
+    fs::create_dir_all(folder_path.join(assets_dir_name())).map_err(|e| log_error("create_todo_folder", e))?;
+    Ok(folder_name)
+}
+
+/// 按标题批量新建待办（每个标题一个文件夹，状态为 default_status），只写入一次索引；空白标题跳过，返回新建的文件夹名
+#[tauri::command]
+fn create_todos_bulk(app: tauri::AppHandle, data_path: String, titles: Vec<String>) -> Result<Vec<String>, String> {
+    let data_path = expand_data_path(&data_path);
+    let status = get_app_config(app.clone()).default_status;
+    let mut todos = load_todos(&data_path);
+    let mut created = Vec::new();
+    for title in titles.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        let folder_name = create_todo_folder(data_path.clone())?;
+        todos.push(TodoItem {
+            id: Uuid::new_v4().to_string(),
+            title: title.to_string(),
+            status: status.clone(),
+            folder_name: folder_name.clone(),
+            ..Default::default()
+        });
+        created.push(folder_name);
+    }
+    if !created.is_empty() {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(created)
+}
+
+#[tauri::command]
+fn delete_todo_folder(data_path: String, folder_name: String) -> Result<(), String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    if folder_path.exists() {
+        release_shared_assets(&data_path, &folder_name).map_err(|e| log_error("delete_todo_folder", e))?;
+        fs::remove_dir_all(&folder_path).map_err(|e| log_error("delete_todo_folder", format!("删除文件夹失败: {}", e)))?;
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct TrashEntry {
+    /// 删除时的待办索引项（标题、状态等），恢复时原样写回
+    todo: TodoItem,
+    deleted_at: String,
+    /// "os" 为系统回收站，"app" 为数据目录下的 .trash
+    location: String,
+    /// 位于 .trash 中的文件夹名（仅 location 为 "app" 时）
+    #[serde(default)]
+    trash_name: Option<String>,
+}
+
+fn trash_manifest_path(data_path: &str) -> PathBuf {
+    Path::new(data_path).join(TRASH_DIR).join("manifest.json")
+}
+
+fn read_trash_manifest(data_path: &str) -> Vec<TrashEntry> {
+    fs::read_to_string(trash_manifest_path(data_path))
+        .ok()
+        .and_then(|c| serde_json::from_str(strip_bom(&c)).ok())
+        .unwrap_or_default()
+}
+
+fn write_trash_manifest(data_path: &str, entries: &[TrashEntry]) -> Result<(), String> {
+    let path = trash_manifest_path(data_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+    write_atomic(&path, content.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// 将待办文件夹移入系统回收站；系统回收站不可用时移入数据目录下的 .trash，返回 "os" 或 "app"。
+/// 索引中存在该待办时记入回收站清单，供 list_trash / restore_from_trash 使用
+#[tauri::command]
+fn delete_todo_folder_to_os_trash(data_path: String, folder_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    if !folder_path.exists() {
+        return Ok("os".to_string());
+    }
+    release_shared_assets(&data_path, &folder_name).map_err(|e| log_error("delete_todo_folder_to_os_trash", e))?;
+    let (location, trash_name) = match trash::delete(&folder_path) {
+        Ok(()) => ("os", None),
+        Err(e) => {
+            write_log(LogLevel::Error, "delete_todo_folder_to_os_trash", &format!("系统回收站不可用: {}", e));
+            let trash_dir = Path::new(&data_path).join(TRASH_DIR);
+            fs::create_dir_all(&trash_dir).map_err(|e| log_error("delete_todo_folder_to_os_trash", e))?;
+            let name = format!("{}-{}", folder_name, chrono::Local::now().format("%Y%m%d%H%M%S"));
+            let dest = trash_dir.join(&name);
+            if fs::rename(&folder_path, &dest).is_err() {
+                copy_dir_all(&folder_path, &dest).map_err(|e| log_error("delete_todo_folder_to_os_trash", e))?;
+                fs::remove_dir_all(&folder_path).map_err(|e| log_error("delete_todo_folder_to_os_trash", e))?;
+            }
+            ("app", Some(name))
+        }
+    };
+    if let Some(todo) = load_todos(&data_path).into_iter().find(|t| t.folder_name == folder_name) {
+        let mut entries = read_trash_manifest(&data_path);
+        entries.push(TrashEntry {
+            todo,
+            deleted_at: utc_now_rfc3339(),
+            location: location.to_string(),
+            trash_name,
+        });
+        write_trash_manifest(&data_path, &entries).map_err(|e| log_error("delete_todo_folder_to_os_trash", e))?;
+    }
+    Ok(location.to_string())
+}
+
+/// 最近删除的待办，按删除时间从新到旧
+#[tauri::command]
+fn list_trash(data_path: String) -> Vec<TrashEntry> {
+    let data_path = expand_data_path(&data_path);
+    let mut entries = read_trash_manifest(&data_path);
+    entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    entries
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+fn restore_from_os_trash(original: &Path) -> Result<(), String> {
+    let item = trash::os_limited::list()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|item| item.original_path() == original)
+        .max_by_key(|item| item.time_deleted)
+        .ok_or_else(|| format!("系统回收站中找不到: {}", original.display()))?;
+    trash::os_limited::restore_all([item]).map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+fn restore_from_os_trash(original: &Path) -> Result<(), String> {
+    Err(format!("当前系统不支持自动从回收站恢复，请在系统回收站中手动还原: {}", original.display()))
+}
+
+/// 将回收站中的待办文件夹放回原处，并按删除时记录的标题、状态等写回待办列表
+#[tauri::command]
+fn restore_from_trash(app: tauri::AppHandle, data_path: String, folder_name: String) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let mut entries = read_trash_manifest(&data_path);
+    let index = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.todo.folder_name == folder_name)
+        .max_by(|(_, a), (_, b)| a.deleted_at.cmp(&b.deleted_at))
+        .map(|(i, _)| i)
+        .ok_or_else(|| format!("回收站中没有该待办: {}", folder_name))?;
+    let target = Path::new(&data_path).join(&folder_name);
+    let entry = entries[index].clone();
+    match (entry.location.as_str(), entry.trash_name.as_deref()) {
+        _ if target.exists() && entry.location == "os" => {
+            // 已在系统回收站中手动还原，只需写回索引
+        }
+        _ if target.exists() => return Err(format!("待办文件夹已存在: {}", folder_name)),
+        ("app", Some(name)) => {
+            let src = Path::new(&data_path).join(TRASH_DIR).join(sanitize_folder_name(name)?);
+            fs::rename(&src, &target).map_err(|e| log_error("restore_from_trash", e))?;
+        }
+        _ => restore_from_os_trash(&target).map_err(|e| log_error("restore_from_trash", e))?,
+    }
+    entries.remove(index);
+    write_trash_manifest(&data_path, &entries).map_err(|e| log_error("restore_from_trash", e))?;
+    let mut todos = load_todos(&data_path);
+    if !todos.iter().any(|t| t.id == entry.todo.id) {
+        todos.push(entry.todo.clone());
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(entry.todo)
+}
+
+/// 由标题生成文件夹名片段：保留字母数字（含中文），其余字符折叠为单个 "-"
+fn slugify_title(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.trim().chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug: String = slug.trim_end_matches('-').chars().take(48).collect();
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() { "todo".to_string() } else { slug }
+}
+
+#[derive(Serialize, Debug)]
+struct FolderRename {
+    old_name: String,
+    new_name: String,
+}
+
+/// 按列表顺序将待办文件夹重命名为 "001-标题" 形式并同步 todos.json；dry_run 时只返回计划不做修改。
+/// 先全部改为临时名再改为目标名，任一步失败都会回滚已完成的重命名
+#[tauri::command]
+fn rename_folders_by_title(app: tauri::AppHandle, data_path: String, dry_run: bool) -> Result<Vec<FolderRename>, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    let mut todos = load_todos(&data_path);
+    let width = todos.len().to_string().len().max(3);
+    let plan: Vec<FolderRename> = todos
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            // 按年份归档的待办保留 archive/<年份>/ 前缀，只改最后一级
+            let prefix = t.folder_name.rsplit_once('/').map(|(p, _)| format!("{}/", p)).unwrap_or_default();
+            FolderRename {
+                old_name: t.folder_name.clone(),
+                new_name: format!("{}{:0width$}-{}", prefix, i + 1, slugify_title(&t.title), width = width),
+            }
+        })
+        .filter(|r| r.old_name != r.new_name)
+        .collect();
+    if dry_run || plan.is_empty() {
+        return Ok(plan);
+    }
+    if let Some(r) = plan.iter().find(|r| !data_dir.join(&r.old_name).is_dir()) {
+        return Err(format!("待办文件夹不存在: {}", r.old_name));
+    }
+
+    // 暂存名按计划中的序号生成，文件夹名含 archive/<年份>/ 时也不会产生子路径
+    let staging = |i: usize| data_dir.join(format!(".rename-{}", i));
+    let mut staged: Vec<usize> = Vec::new();
+    let mut finished: Vec<usize> = Vec::new();
+    let rollback = |staged: &[usize], finished: &[usize]| {
+        for &i in finished {
+            let _ = fs::rename(data_dir.join(&plan[i].new_name), staging(i));
+        }
+        for &i in staged {
+            let _ = fs::rename(staging(i), data_dir.join(&plan[i].old_name));
+        }
+    };
+    for (i, r) in plan.iter().enumerate() {
+        if let Err(e) = fs::rename(data_dir.join(&r.old_name), staging(i)) {
+            rollback(&staged, &finished);
+            return Err(log_error("rename_folders_by_title", e));
+        }
+        staged.push(i);
+    }
+    for (i, r) in plan.iter().enumerate() {
+        let target = data_dir.join(&r.new_name);
+        let result = if target.exists() {
+            Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("目标文件夹已存在: {}", r.new_name)))
+        } else {
+            fs::rename(staging(i), &target)
+        };
+        if let Err(e) = result {
+            rollback(&staged, &finished);
+            return Err(log_error("rename_folders_by_title", e));
+        }
+        finished.push(i);
+    }
+
+    // 资源 url 中包含文件夹名，需随之改写
+    for r in &plan {
+        retarget_moved_folder(data_dir, &r.old_name, &r.new_name);
+    }
+    for todo in todos.iter_mut() {
+        if let Some(r) = plan.iter().find(|r| r.old_name == todo.folder_name) {
+            todo.folder_name = r.new_name.clone();
+        }
+    }
+    let renamed = plan.iter().map(|r| (r.old_name.clone(), r.new_name.clone())).collect();
+    remap_folder_references(&data_path, &mut todos, &renamed);
+    save_todos(app, data_path, todos)?;
+    Ok(plan)
+}
+
+/// 监视待办 assets 目录的外部增删，变化时发出 todo-assets-changed 事件；重复调用不会重复监视
+#[tauri::command]
+fn watch_folder(app: tauri::AppHandle, data_path: String, folder_name: String) -> Result<(), String> {
+    use notify::Watcher;
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    if !folder_path.is_dir() {
+        return Err(format!("待办文件夹不存在: {}", folder_name));
+    }
+    let assets = folder_path.join(assets_dir_name());
+    fs::create_dir_all(&assets).map_err(|e| log_error("watch_folder", e))?;
+    let mut watchers = ASSET_WATCHERS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    if watchers.iter().any(|(p, _)| *p == folder_path) {
+        return Ok(());
+    }
+    #[derive(Clone, Serialize)]
+    struct Payload { folder_name: String }
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if matches!(
+                event.kind,
+                notify::EventKind::Create(_) | notify::EventKind::Remove(_) | notify::EventKind::Modify(_)
+            ) {
+                let _ = app.emit("todo-assets-changed", Payload { folder_name: folder_name.clone() });
+            }
+        }
+    })
+    .map_err(|e| log_error("watch_folder", e))?;
+    watcher
+        .watch(&assets, notify::RecursiveMode::NonRecursive)
+        .map_err(|e| log_error("watch_folder", e))?;
+    watchers.push((folder_path, watcher));
+    Ok(())
+}
+
+/// 停止监视待办的 assets 目录（关闭详情页时调用）
+#[tauri::command]
+fn unwatch_folder(data_path: String, folder_name: String) {
+    let data_path = expand_data_path(&data_path);
+    let Ok(folder_name) = sanitize_folder_name(&folder_name) else { return };
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    ASSET_WATCHERS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .retain(|(p, _)| *p != folder_path);
+}
+
+/// 批量删除待办及其文件夹；数量超过 confirm_delete_threshold 且未传 confirm 时拒绝执行，返回删除的条数
+#[tauri::command]
+fn delete_todos(app: tauri::AppHandle, data_path: String, folder_names: Vec<String>, confirm: bool) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let threshold = get_app_config(app.clone()).confirm_delete_threshold;
+    let mut todos = load_todos(&data_path);
+    let count = todos.iter().filter(|t| folder_names.contains(&t.folder_name)).count();
+    if count > threshold && !confirm {
+        return Err(format!("将删除 {} 个待办，超过免确认上限 {}，请确认后重试", count, threshold));
+    }
+    // 只删除索引中存在的待办文件夹，避免误删数据目录下的其他内容
+    for todo in todos.iter().filter(|t| folder_names.contains(&t.folder_name)) {
+        if let Err(e) = delete_todo_folder(data_path.clone(), todo.folder_name.clone()) {
+            // 文件夹删除失败时仍移除索引项，与单条删除保持一致
+            write_log(LogLevel::Error, "delete_todos", &e);
+        }
+    }
+    todos.retain(|t| !folder_names.contains(&t.folder_name));
+    save_todos(app, data_path, todos)?;
+    Ok(count)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct OrphanFolder {
+    folder_name: String,
+    size: u64,
+}
+
+fn calculate_dir_size(path: &Path) -> u64 {
+    let mut total_size = 0u64;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+            if entry_path.is_dir() {
+                total_size += calculate_dir_size(&entry_path);
+            } else if let Ok(metadata) = entry_path.metadata() {
+                total_size += metadata.len();
+            }
+        }
+    }
+    total_size
+}
+
+#[derive(Serialize, Debug)]
+struct TodoSize {
+    todo: TodoItem,
+    /// 文件夹（content.json 与资源）的总字节数
+    size: u64,
+}
+
+/// 按文件夹总大小从大到小列出待办，用于找出占用空间最多的笔记
+#[tauri::command]
+fn list_todos_by_size(data_path: String) -> Vec<TodoSize> {
+    let data_path = expand_data_path(&data_path);
+    let mut sizes: Vec<TodoSize> = load_todos(&data_path)
+        .into_iter()
+        .map(|todo| {
+            let size = calculate_dir_size(&Path::new(&data_path).join(&todo.folder_name));
+            TodoSize { todo, size }
+        })
+        .collect();
+    sizes.sort_by_key(|t| std::cmp::Reverse(t.size));
+    sizes
+}
+
+#[tauri::command]
+fn find_orphan_todo_folders(data_path: String) -> Result<Vec<OrphanFolder>, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    if !data_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    // 获取所有有效的待办文件夹名
+    let todos = load_todos(&data_path);
+    let valid_folders: std::collections::HashSet<String> = todos
+        .iter()
+        .map(|todo| todo.folder_name.clone())
+        .collect();
+
+    let mut orphan_folders = Vec::new();
+
+    // 扫描数据目录下的所有文件夹
+    if let Ok(entries) = fs::read_dir(data_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if let Some(folder_name) = path.file_name().and_then(|n| n.to_str()) {
+                    // 检查是否在有效文件夹列表中
+                    if !valid_folders.contains(folder_name) && !is_reserved_dir(folder_name) {
+                        let size = calculate_dir_size(&path);
+                        orphan_folders.push(OrphanFolder {
+                            folder_name: folder_name.to_string(),
+                            size,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(orphan_folders)
+}
+
+#[derive(Serialize, Debug)]
+struct ConsistencyReport {
+    /// 索引中的待办数
+    indexed: usize,
+    /// 数据目录下的待办文件夹数（不含保留目录，含按年份归档的）
+    folders: usize,
+    /// 索引中引用、但磁盘上不存在的文件夹
+    missing_folders: Vec<String>,
+    /// 磁盘上存在、但索引中未引用的待办文件夹
+    unreferenced_folders: Vec<String>,
+}
+
+/// 对比索引与磁盘上的待办文件夹，两者不一致时界面可提示执行 rebuild_index 或清理孤立文件夹
+#[tauri::command]
+fn check_consistency(data_path: String) -> ConsistencyReport {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    let todos = load_todos(&data_path);
+    let indexed: std::collections::BTreeSet<String> = todos.iter().map(|t| t.folder_name.clone()).collect();
+    let on_disk: std::collections::BTreeSet<String> = todo_folders_on_disk(data_dir).into_iter().collect();
+    ConsistencyReport {
+        indexed: todos.len(),
+        folders: on_disk.len(),
+        missing_folders: indexed.iter().filter(|f| !data_dir.join(f).is_dir()).cloned().collect(),
+        unreferenced_folders: on_disk.difference(&indexed).cloned().collect(),
+    }
+}
+
+#[tauri::command]
+fn save_todo_detail(app: tauri::AppHandle, data_path: String, folder_name: String, content: String) -> Result<(), String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let max = MAX_DETAIL_BYTES.load(Ordering::Relaxed);
+    let size = content.len() as u64;
+    if max > 0 && size > max {
+        return Err(format!("详情内容过大（{} 字节，上限 {} 字节），请拆分为多个待办或将大段内容改为附件", size, max));
+    }
+    let detail_path = Path::new(&data_path).join(&folder_name).join("content.json");
+    with_write_retry("save_todo_detail", || {
+        write_atomic(&detail_path, content.as_bytes()).map_err(|e| e.to_string())?;
+        verify_written_file(&detail_path, content.as_bytes())
+    })
+    .map_err(|e| log_error("save_todo_detail", e))?;
+    // 已正式保存，草稿随之失效
+    let _ = fs::remove_file(Path::new(&data_path).join(&folder_name).join(DRAFT_FILE));
+    #[derive(Clone, Serialize)]
+    struct Payload { folder_name: String }
+    let _ = app.emit("todo-detail-changed", Payload { folder_name });
+    Ok(())
+}
+
+/// 编辑中尚未保存的详情草稿，正式保存后删除
+const DRAFT_FILE: &str = "content.json.draft";
+
+/// 写入编辑中的详情草稿（不触发 todo-detail-changed），应用崩溃或保存失败后可据此恢复
+#[tauri::command]
+fn save_detail_draft(data_path: String, folder_name: String, content: String) -> Result<(), String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let folder = Path::new(&data_path).join(&folder_name);
+    if !folder.is_dir() {
+        return Err(format!("待办不存在: {}", folder_name));
+    }
+    write_atomic(&folder.join(DRAFT_FILE), content.as_bytes()).map_err(|e| log_error("save_detail_draft", e))
+}
+
+/// 是否有比 content.json 更新的草稿（即上次编辑未正常保存）
+#[tauri::command]
+fn has_detail_draft(data_path: String, folder_name: String) -> Result<bool, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder = Path::new(&data_path).join(sanitize_folder_name(&folder_name)?);
+    let modified = |name: &str| fs::metadata(folder.join(name)).and_then(|m| m.modified()).ok();
+    Ok(match (modified(DRAFT_FILE), modified("content.json")) {
+        (Some(draft), Some(content)) => draft >= content,
+        (Some(_), None) => true,
+        _ => false,
+    })
+}
+
+/// 丢弃草稿
+#[tauri::command]
+fn discard_draft(data_path: String, folder_name: String) -> Result<(), String> {
+    let data_path = expand_data_path(&data_path);
+    let draft = Path::new(&data_path).join(sanitize_folder_name(&folder_name)?).join(DRAFT_FILE);
+    match fs::remove_file(&draft) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(log_error("discard_draft", e)),
+        _ => Ok(()),
+    }
+}
+
+/// 用草稿覆盖详情（与 save_todo_detail 相同的校验与写入），成功后删除草稿
+#[tauri::command]
+fn commit_draft(app: tauri::AppHandle, data_path: String, folder_name: String) -> Result<(), String> {
+    let expanded = expand_data_path(&data_path);
+    let draft = Path::new(&expanded).join(sanitize_folder_name(&folder_name)?).join(DRAFT_FILE);
+    let content = fs::read_to_string(&draft).map_err(|_| "草稿不存在".to_string())?;
+    save_todo_detail(app, data_path, folder_name, content)
+}
+
+#[tauri::command]
+fn get_todo_detail(data_path: String, folder_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let detail_path = Path::new(&data_path).join(folder_name).join("content.json");
+    if detail_path.exists() {
+        fs::read_to_string(detail_path)
+            .map(|c| strip_bom(&c).to_string())
+            .map_err(|e| e.to_string())
+    } else {
+        Ok("{}".to_string())
+    }
+}
+
+fn node_inline_text(node: &serde_json::Value, out: &mut String) {
+    if let Some(v) = node.get("value").and_then(|v| v.as_str()) {
+        out.push_str(v);
+    }
+    if let Some(children) = node.get("children").and_then(|c| c.as_array()) {
+        for child in children {
+            node_inline_text(child, out);
+        }
+    }
+}
+
+/// 从编辑器节点树中提取纯文本，每个块级节点一行（不读取任何资源文件）
+fn collect_block_text(nodes: &[serde_json::Value], lines: &mut Vec<String>) {
+    for node in nodes {
+        match node.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "ul" | "ol" | "taskList" | "fold" => {
+                if let Some(children) = node.get("children").and_then(|c| c.as_array()) {
+                    collect_block_text(children, lines);
+                }
+            }
+            "code" => {
+                if let Some(content) = node.get("content").and_then(|c| c.as_str()) {
+                    lines.extend(content.lines().map(|l| l.to_string()));
+                }
+            }
+            "image" | "file" => {}
+            _ => {
+                let mut line = String::new();
+                node_inline_text(node, &mut line);
+                lines.push(line);
+            }
+        }
+    }
+}
+
+fn parse_detail_nodes(content: &str) -> Vec<serde_json::Value> {
+    match serde_json::from_str::<serde_json::Value>(strip_bom(content)) {
+        Ok(serde_json::Value::Array(nodes)) => nodes,
+        _ => vec![],
+    }
+}
+
+#[tauri::command]
+fn get_detail_preview(data_path: String, folder_name: String, max_chars: usize) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let content = get_todo_detail(data_path, folder_name)?;
+    let mut lines = Vec::new();
+    collect_block_text(&parse_detail_nodes(&content), &mut lines);
+    let text = lines
+        .iter()
+        .flat_map(|l| l.split_whitespace())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.chars().count() <= max_chars {
+        return Ok(text);
+    }
+    let mut preview: String = text.chars().take(max_chars).collect();
+    preview.push('…');
+    Ok(preview)
+}
+
+/// 将文本中的 HTML 标签与 Markdown 链接转为可读文本：标签去除，链接目标以 "文字 (地址)" 的形式保留
+fn inline_to_plaintext(text: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut href: Option<String> = None;
+    let mut i = 0;
+    while let Some(rel) = lower[i..].find('<') {
+        let start = i + rel;
+        out.push_str(&text[i..start]);
+        let is_tag = lower[start + 1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/');
+        let Some(end) = lower[start..].find('>').map(|e| start + e + 1).filter(|_| is_tag) else {
+            out.push('<');
+            i = start + 1;
+            continue;
+        };
+        let tag = &lower[start + 1..end - 1];
+        let name: String = tag.trim_start_matches('/').chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+        match name.as_str() {
+            "a" if tag.starts_with('/') => {
+                if let Some(target) = href.take() {
+                    out.push_str(&format!(" ({})", target));
+                }
+            }
+            "a" => {
+                href = tag.find("href=").and_then(|h| {
+                    let rest = &text[start + 1 + h + 5..end - 1];
+                    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+                    rest[1..].split(quote).next().map(unescape_html)
+                });
+            }
+            "br" => out.push('\n'),
+            _ => {}
+        }
+        i = end;
+    }
+    out.push_str(&text[i..]);
+    let text = unescape_html(&out);
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(open) = rest.find('[') {
+        let parsed = rest[open + 1..].find("](").and_then(|close| {
+            let label = &rest[open + 1..open + 1 + close];
+            let after = &rest[open + 1 + close + 2..];
+            after.find(')').map(|end| (label, &after[..end], &after[end + 1..]))
+        });
+        match parsed {
+            Some((label, target, tail)) if !label.contains('[') => {
+                out.push_str(&rest[..open]);
+                if label.is_empty() || label == target {
+                    out.push_str(target);
+                } else {
+                    out.push_str(&format!("{} ({})", label, target));
+                }
+                rest = tail;
+            }
+            _ => {
+                out.push_str(&rest[..=open]);
+                rest = &rest[open + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 将节点树转为完整的纯文本：列表保留层级缩进与序号 / 勾选标记，附件以名称与路径表示
+fn detail_to_plaintext(nodes: &[serde_json::Value], depth: usize, out: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    let is_list = |n: &serde_json::Value| matches!(n.get("type").and_then(|t| t.as_str()), Some("ul" | "ol" | "taskList"));
+    for node in nodes {
+        let children = node.get("children").and_then(|c| c.as_array());
+        match node.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "ul" | "ol" | "taskList" => {
+                let ordered = node["type"] == "ol";
+                for (i, item) in children.into_iter().flatten().enumerate() {
+                    let marker = match item.get("checked").and_then(|c| c.as_bool()) {
+                        Some(true) => "[x] ".to_string(),
+                        Some(false) => "[ ] ".to_string(),
+                        None if ordered => format!("{}. ", i + 1),
+                        None => "- ".to_string(),
+                    };
+                    let item_children = item.get("children").and_then(|c| c.as_array());
+                    let mut text = String::new();
+                    for child in item_children.into_iter().flatten().filter(|c| !is_list(c)) {
+                        node_inline_text(child, &mut text);
+                    }
+                    out.push(format!("{}{}{}", indent, marker, inline_to_plaintext(&text).trim()));
+                    let nested: Vec<serde_json::Value> =
+                        item_children.into_iter().flatten().filter(|c| is_list(c)).cloned().collect();
+                    detail_to_plaintext(&nested, depth + 1, out);
+                }
+            }
+            "fold" => {
+                if let Some(children) = children {
+                    detail_to_plaintext(children, depth, out);
+                }
+            }
+            "code" => {
+                let content = node.get("content").and_then(|c| c.as_str()).unwrap_or("");
+                out.extend(content.lines().map(|l| format!("{}{}", indent, l)));
+            }
+            "image" => {
+                let asset = node.get("assetPath").and_then(|a| a.as_str()).unwrap_or("");
+                out.push(format!("{}[图片] {}", indent, asset));
+            }
+            "file" => {
+                let asset = node.get("assetPath").and_then(|a| a.as_str()).unwrap_or("");
+                let name = node.get("fileName").and_then(|n| n.as_str()).unwrap_or(asset);
+                out.push(format!("{}[附件] {} ({})", indent, name, asset));
+            }
+            _ => {
+                let mut text = String::new();
+                node_inline_text(node, &mut text);
+                out.extend(inline_to_plaintext(&text).lines().map(|l| format!("{}{}", indent, l.trim_end())));
+            }
+        }
+    }
+}
+
+/// 返回详情的完整纯文本（不截断），供读屏软件与纯文本归档使用；旧格式的详情会先转换为当前格式
+#[tauri::command]
+fn get_detail_plaintext(data_path: String, folder_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    Ok(content_to_plaintext(get_todo_detail(data_path, folder_name)?))
+}
+
+/// 将 content.json 的内容转为纯文本，旧格式的详情会先转换为当前格式
+fn content_to_plaintext(content: String) -> String {
+    let value: serde_json::Value = serde_json::from_str(strip_bom(&content)).unwrap_or(serde_json::Value::String(content));
+    let nodes = match convert_legacy_content(value.clone()) {
+        Some(nodes) => nodes,
+        None => value.as_array().cloned().unwrap_or_default(),
+    };
+    let mut lines = Vec::new();
+    detail_to_plaintext(&nodes, 0, &mut lines);
+    lines.join("\n").trim().to_string()
+}
+
+#[derive(Serialize, Debug)]
+struct OutlineEntry {
+    /// 标题级别，1 为最高
+    level: u8,
+    text: String,
+    /// 所在顶层块的序号，折叠块内的标题取折叠块的序号
+    position: usize,
+    /// 标题节点的 id（有时），便于编辑器直接定位
+    id: Option<String>,
+    /// 由标题文字生成的锚点，同名标题依次加 -1、-2……；标题文字与同名标题的先后不变时锚点不变
+    anchor: String,
+}
+
+/// 将标题文字转为锚点：保留字母数字（含中文等），其余连续字符合并为一个 -，全部被去掉时为 section
+fn slugify_heading(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() || c == '_' {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { "section".to_string() } else { slug.to_string() }
+}
+
+/// 按文档顺序为标题分配不重复的锚点
+fn assign_anchors(outline: &mut [OutlineEntry]) {
+    let mut used = std::collections::HashSet::new();
+    for entry in outline.iter_mut() {
+        let base = slugify_heading(&entry.text);
+        let mut anchor = base.clone();
+        let mut n = 1;
+        while !used.insert(anchor.clone()) {
+            anchor = format!("{}-{}", base, n);
+            n += 1;
+        }
+        entry.anchor = anchor;
+    }
+}
+
+fn collect_outline(nodes: &[serde_json::Value], position: Option<usize>, out: &mut Vec<OutlineEntry>) {
+    for (i, node) in nodes.iter().enumerate() {
+        let position = position.unwrap_or(i);
+        let mut text = String::new();
+        node_inline_text(node, &mut text);
+        let level = match node.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "h1" => Some(1),
+            "h2" => Some(2),
+            "fold" => {
+                if let Some(children) = node.get("children").and_then(|c| c.as_array()) {
+                    collect_outline(children, Some(position), out);
+                }
+                None
+            }
+            // 导入的 Markdown 文本中以 # 开头的段落
+            "p" => {
+                let hashes = text.chars().take_while(|c| *c == '#').count();
+                let rest = &text[hashes..];
+                if (1..=6).contains(&hashes) && rest.starts_with(' ') {
+                    text = rest.to_string();
+                    Some(hashes as u8)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        let text = text.trim();
+        if let Some(level) = level.filter(|_| !text.is_empty()) {
+            out.push(OutlineEntry {
+                level,
+                text: text.to_string(),
+                position,
+                id: node.get("id").and_then(|v| v.as_str()).map(str::to_string),
+                anchor: String::new(),
+            });
+        }
+    }
+}
+
+/// 返回详情中的标题列表（一二级标题与 Markdown 的 # 标题）及各自的锚点，供编辑器生成目录；没有标题时返回空列表
+#[tauri::command]
+fn get_detail_outline(data_path: String, folder_name: String) -> Result<Vec<OutlineEntry>, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let content = get_todo_detail(data_path, folder_name)?;
+    let mut outline = Vec::new();
+    collect_outline(&parse_detail_nodes(&content), None, &mut outline);
+    assign_anchors(&mut outline);
+    Ok(outline)
+}
+
+/// 将旧格式的 content.json 转为当前的节点数组；已是当前格式或无法识别时返回 None
+fn convert_legacy_content(value: serde_json::Value) -> Option<Vec<serde_json::Value>> {
+    use serde_json::Value;
+    match value {
+        Value::Array(items) if items.iter().all(|v| v.is_object()) => None,
+        // 早期每段保存为一个字符串
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .flat_map(|v| match v {
+                    Value::String(text) => text_to_detail_nodes(text),
+                    Value::Object(_) => vec![v.clone()],
+                    _ => Vec::new(),
+                })
+                .collect(),
+        ),
+        Value::String(text) => Some(text_to_detail_nodes(&text)),
+        Value::Object(map) => {
+            for key in ["body", "blocks", "nodes", "content"] {
+                match map.get(key) {
+                    Some(Value::String(text)) => return Some(text_to_detail_nodes(text)),
+                    Some(Value::Array(items)) => {
+                        return convert_legacy_content(Value::Array(items.clone())).or_else(|| Some(items.clone()))
+                    }
+                    _ => {}
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Debug, Default)]
+struct ContentMigrationReport {
+    migrated: usize,
+    /// 无法解析或无法识别格式的文件夹
+    failed: Vec<String>,
+}
+
+/// 检测旧格式的 content.json 并转换为当前格式，原文件备份为同目录下的 content.legacy.json
+#[tauri::command]
+fn migrate_content_schema(app: tauri::AppHandle, data_path: String) -> Result<ContentMigrationReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut report = ContentMigrationReport::default();
+    for todo in load_todos(&data_path) {
+        let folder_path = Path::new(&data_path).join(&todo.folder_name);
+        let detail_path = folder_path.join("content.json");
+        let Ok(content) = fs::read_to_string(&detail_path) else { continue };
+        if content.trim().is_empty() {
+            continue;
+        }
+        let value = match serde_json::from_str::<serde_json::Value>(strip_bom(&content)) {
+            Ok(v) => v,
+            Err(_) => {
+                report.failed.push(todo.folder_name);
+                continue;
+            }
+        };
+        let is_current = matches!(&value, serde_json::Value::Array(items) if items.iter().all(|v| v.is_object()));
+        let Some(nodes) = convert_legacy_content(value) else {
+            if !is_current {
+                report.failed.push(todo.folder_name);
+            }
+            continue;
+        };
+        fs::copy(&detail_path, folder_path.join("content.legacy.json"))
+            .map_err(|e| log_error("migrate_content_schema", e))?;
+        let migrated = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+        write_atomic(&detail_path, migrated.as_bytes()).map_err(|e| log_error("migrate_content_schema", e))?;
+        #[derive(Clone, Serialize)]
+        struct Payload { folder_name: String }
+        let _ = app.emit("todo-detail-changed", Payload { folder_name: todo.folder_name });
+        report.migrated += 1;
+    }
+    Ok(report)
+}
+
+#[derive(Serialize, Debug, Default)]
+struct PayloadDiagnostics {
+    todo_count: usize,
+    /// 完整待办列表序列化后的字节数（即 get_todos 返回的大小）
+    todos_bytes: usize,
+    detail_count: usize,
+    average_detail_bytes: u64,
+    largest_detail_bytes: u64,
+    largest_detail_folder: Option<String>,
+    storage_backend: String,
+}
+
+/// 调试用：统计待办列表与 content.json 的大小，帮助判断是否需要分页或改用 SQLite 后端
+#[tauri::command]
+fn get_payload_diagnostics(data_path: String) -> Result<PayloadDiagnostics, String> {
+    let data_path = expand_data_path(&data_path);
+    let todos = load_todos(&data_path);
+    let mut report = PayloadDiagnostics {
+        todo_count: todos.len(),
+        todos_bytes: serde_json::to_vec(&todos).map_err(|e| e.to_string())?.len(),
+        storage_backend: if USE_SQLITE.load(Ordering::Relaxed) { "sqlite" } else { "json" }.to_string(),
+        ..Default::default()
+    };
+    let mut total: u64 = 0;
+    for todo in &todos {
+        let Ok(meta) = fs::metadata(Path::new(&data_path).join(&todo.folder_name).join("content.json")) else {
+            continue;
+        };
+        report.detail_count += 1;
+        total += meta.len();
+        if meta.len() > report.largest_detail_bytes {
+            report.largest_detail_bytes = meta.len();
+            report.largest_detail_folder = Some(todo.folder_name.clone());
+        }
+    }
+    if report.detail_count > 0 {
+        report.average_detail_bytes = total / report.detail_count as u64;
+    }
+    Ok(report)
+}
+
+/// 超过该大小的 content.json 不参与字数统计
+const TEXT_STATS_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Serialize, Debug)]
+struct TodoTextStats {
+    folder_name: String,
+    title: String,
+    chars: usize,
+    words: usize,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct WorkspaceTextStats {
+    total_chars: usize,
+    total_words: usize,
+    todos: Vec<TodoTextStats>,
+    /// 因过大、无法读取或无法解析而跳过的文件数
+    skipped: usize,
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF)
+}
+
+/// 统计字符数（不含空白）与词数：中日韩字符每字计一词，其余按空白分词
+fn count_text(text: &str) -> (usize, usize) {
+    let chars = text.chars().filter(|c| !c.is_whitespace()).count();
+    let mut words = 0;
+    for token in text.split_whitespace() {
+        let mut in_word = false;
+        for c in token.chars() {
+            if is_cjk(c) {
+                words += 1;
+                in_word = false;
+            } else if !in_word {
+                words += 1;
+                in_word = true;
+            }
+        }
+    }
+    (chars, words)
+}
+
+/// 汇总所有待办 content.json 的字符数与词数，并给出每个待办的明细
+#[tauri::command]
+fn get_workspace_text_stats(data_path: String) -> WorkspaceTextStats {
+    let data_path = expand_data_path(&data_path);
+    let mut stats = WorkspaceTextStats::default();
+    for todo in load_todos(&data_path) {
+        let detail_path = Path::new(&data_path).join(&todo.folder_name).join("content.json");
+        let Ok(meta) = fs::metadata(&detail_path) else { continue };
+        if meta.len() > TEXT_STATS_MAX_BYTES {
+            stats.skipped += 1;
+            continue;
+        }
+        let nodes = match fs::read_to_string(&detail_path).map(|c| serde_json::from_str::<serde_json::Value>(strip_bom(&c))) {
+            Ok(Ok(serde_json::Value::Array(nodes))) => nodes,
+            _ => {
+                stats.skipped += 1;
+                continue;
+            }
+        };
+        let mut lines = Vec::new();
+        collect_block_text(&nodes, &mut lines);
+        let (chars, words) = count_text(&lines.join("\n"));
+        stats.total_chars += chars;
+        stats.total_words += words;
+        stats.todos.push(TodoTextStats {
+            folder_name: todo.folder_name,
+            title: todo.title,
+            chars,
+            words,
+        });
+    }
+    stats
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct TodoMeta {
+    #[serde(default)]
+    content_hash: Option<String>,
+    /// 由 import_ical 导入时日历条目的 UID，重新导入时据此更新而非重复创建
+    #[serde(default)]
+    ical_uid: Option<String>,
+    /// 打开该待办时编辑区使用的主题（"light" / "dark"），为空时跟随全局主题
+    #[serde(default)]
+    editor_theme_override: Option<String>,
+    /// 开启 track_access 时详情被界面打开的次数
+    #[serde(default)]
+    open_count: u64,
+    /// 开启 track_access 时详情被界面保存的次数
+    #[serde(default)]
+    edit_count: u64,
+    #[serde(default)]
+    last_opened_at: Option<String>,
+}
+
+/// 待办编辑区的主题覆盖，未设置时为空（跟随全局主题）
+#[tauri::command]
+fn get_editor_theme(data_path: String, folder_name: String) -> Result<Option<String>, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    Ok(read_todo_meta(&Path::new(&data_path).join(&folder_name)).editor_theme_override)
+}
+
+/// 设置待办编辑区的主题覆盖（"light" / "dark"），传入空值表示恢复跟随全局主题
+#[tauri::command]
+fn set_editor_theme(data_path: String, folder_name: String, theme: Option<String>) -> Result<(), String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let theme = theme.filter(|t| !t.trim().is_empty());
+    if let Some(theme) = theme.as_deref().filter(|t| !matches!(*t, "light" | "dark")) {
+        return Err(format!("无效的主题: {}", theme));
+    }
+    let folder = Path::new(&data_path).join(&folder_name);
+    if !folder.is_dir() {
+        return Err(format!("待办不存在: {}", folder_name));
+    }
+    let mut meta = read_todo_meta(&folder);
+    meta.editor_theme_override = theme;
+    write_todo_meta(&folder, &meta).map_err(|e| log_error("set_editor_theme", e))
+}
+
+fn read_todo_meta(folder_path: &Path) -> TodoMeta {
+    fs::read_to_string(folder_path.join("meta.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(strip_bom(&c)).ok())
+        .unwrap_or_default()
+}
+
+fn write_todo_meta(folder_path: &Path, meta: &TodoMeta) -> Result<(), String> {
+    let content = serde_json::to_string(meta).map_err(|e| e.to_string())?;
+    write_atomic(&folder_path.join("meta.json"), content.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// 递归收集目录下所有文件的相对路径（统一使用 / 分隔）
+fn collect_relative_files(root: &Path, dir: &Path, out: &mut Vec<String>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_relative_files(root, &path, out);
+            } else if let Ok(rel) = path.strip_prefix(root) {
+                let parts: Vec<String> = rel
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                out.push(parts.join("/"));
+            }
+        }
+    }
+}
+
+/// 计算 content.json 与 assets 目录内容的哈希，文件按相对路径排序，与遍历顺序无关
+fn compute_content_hash(folder_path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    let mut files = Vec::new();
+    if folder_path.join("content.json").is_file() {
+        files.push("content.json".to_string());
+    }
+    let mut assets = Vec::new();
+    collect_relative_files(folder_path, &folder_path.join(assets_dir_name()), &mut assets);
+    assets.sort();
+    files.extend(assets);
+    for rel in files {
+        let data = fs::read(folder_path.join(&rel)).map_err(|e| e.to_string())?;
+        hasher.update(rel.as_bytes());
+        hasher.update([0u8]);
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(&data);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+#[derive(Serialize, Debug)]
+struct DuplicateAsset {
+    hash: String,
+    size: u64,
+    /// 相对数据目录的路径，如 "<folder>/assets/x.png"
+    files: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct AssetDedupeReport {
+    duplicates: Vec<DuplicateAsset>,
+    reclaimable_bytes: u64,
+    /// 已替换为硬链接的文件数
+    linked: usize,
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    use sha2::{Digest, Sha256};
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).ok()?;
+    Some(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 文件的物理标识，已是同一文件的硬链接不再视为重复
+#[cfg(unix)]
+fn file_identity(meta: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[derive(Serialize, Debug)]
+struct PermissionFix {
+    /// 相对数据目录的路径
+    path: String,
+    /// 修改前后的权限（Unix 上为八进制模式，其他平台为是否只读）
+    from: String,
+    to: String,
+}
+
+/// 目录需可读写并可进入，文件需可读写；返回需要修改时的新权限
+#[cfg(unix)]
+fn repaired_permissions(meta: &fs::Metadata) -> Option<(String, String, fs::Permissions)> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode() & 0o7777;
+    let required = if meta.is_dir() { 0o700 } else { 0o600 };
+    (mode & required != required)
+        .then(|| (format!("{:o}", mode), format!("{:o}", mode | required), fs::Permissions::from_mode(mode | required)))
+}
+
+#[cfg(not(unix))]
+fn repaired_permissions(meta: &fs::Metadata) -> Option<(String, String, fs::Permissions)> {
+    let mut permissions = meta.permissions();
+    if !permissions.readonly() {
+        return None;
+    }
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    Some(("readonly".to_string(), "writable".to_string(), permissions))
+}
+
+fn repair_permissions_in(root: &Path, dir: &Path, fixes: &mut Vec<PermissionFix>, errors: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // 不跟随符号链接，避免修改数据目录之外的文件
+        let Ok(meta) = fs::symlink_metadata(&path) else { continue };
+        if meta.file_type().is_symlink() {
+            continue;
+        }
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        if let Some((from, to, permissions)) = repaired_permissions(&meta) {
+            match fs::set_permissions(&path, permissions) {
+                Ok(()) => fixes.push(PermissionFix { path: rel.clone(), from, to }),
+                Err(e) => errors.push(format!("{}: {}", rel, e)),
+            }
+        }
+        if meta.is_dir() {
+            repair_permissions_in(root, &path, fixes, errors);
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Default)]
+struct PermissionRepairReport {
+    fixed: Vec<PermissionFix>,
+    /// 无法修改权限的路径及原因
+    errors: Vec<String>,
+}
+
+/// 修复数据目录下的权限：目录保证当前用户可进入与读写，文件保证可读写（不跟随符号链接），返回修改过的路径
+#[tauri::command]
+fn repair_permissions(data_path: String) -> Result<PermissionRepairReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let root = Path::new(&data_path);
+    let meta = fs::metadata(root).map_err(|e| format!("数据目录不存在: {} ({})", data_path, e))?;
+    let mut report = PermissionRepairReport::default();
+    if let Some((from, to, permissions)) = repaired_permissions(&meta) {
+        fs::set_permissions(root, permissions).map_err(|e| log_error("repair_permissions", e))?;
+        report.fixed.push(PermissionFix { path: String::new(), from, to });
+    }
+    repair_permissions_in(root, root, &mut report.fixed, &mut report.errors);
+    for error in &report.errors {
+        write_log(LogLevel::Error, "repair_permissions", error);
+    }
+    Ok(report)
+}
+
+/// 计算图片的差值哈希（dHash）：缩放为 9x8 灰度图，比较每行相邻像素的明暗得到 64 位指纹
+fn perceptual_hash(path: &Path) -> Option<u64> {
+    let img = image::open(path).ok()?.resize_exact(9, 8, image::imageops::FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash = (hash << 1) | (img.get_pixel(x, y)[0] > img.get_pixel(x + 1, y)[0]) as u64;
+        }
+    }
+    Some(hash)
+}
+
+/// 按感知哈希查找待办 assets 中看起来相同的图片（如以不同压缩率保存的同一截图）。
+/// threshold 为 0 到 1 的相似度，1 表示指纹完全一致；返回每组相似图片的文件名
+#[tauri::command]
+fn find_similar_images(data_path: String, folder_name: String, threshold: f64) -> Result<Vec<Vec<String>>, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(format!("相似度阈值须在 0 到 1 之间: {}", threshold));
+    }
+    let assets = Path::new(&data_path).join(&folder_name).join(assets_dir_name());
+    let mut hashes: Vec<(String, u64)> = fs::read_dir(&assets)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_file())
+                .filter_map(|e| Some((e.file_name().to_string_lossy().into_owned(), perceptual_hash(&e.path())?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    hashes.sort();
+    let max_distance = ((1.0 - threshold) * 64.0).floor() as u32;
+    let mut groups: Vec<Vec<(String, u64)>> = Vec::new();
+    for (name, hash) in hashes {
+        match groups
+            .iter_mut()
+            .find(|g| g.iter().any(|(_, h)| (h ^ hash).count_ones() <= max_distance))
+        {
+            Some(group) => group.push((name, hash)),
+            None => groups.push(vec![(name, hash)]),
+        }
+    }
+    Ok(groups
+        .into_iter()
+        .filter(|g| g.len() > 1)
+        .map(|g| g.into_iter().map(|(name, _)| name).collect())
+        .collect())
+}
+
+/// 按内容哈希查找所有待办之间重复的资源文件并报告可回收的空间；
+/// link 为 true 时将重复副本替换为指向第一份的硬链接（路径不变，引用无需修改；跨文件系统时保留副本）
+#[tauri::command]
+fn global_dedupe_attachments(data_path: String, link: bool) -> Result<AssetDedupeReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let data_dir = Path::new(&data_path);
+    // (大小, 哈希) -> [(相对路径, 物理标识)]
+    type AssetCopies = Vec<(String, Option<(u64, u64)>)>;
+    let mut by_hash: std::collections::BTreeMap<(u64, String), AssetCopies> = std::collections::BTreeMap::new();
+    for todo in load_todos(&data_path) {
+        let folder_path = data_dir.join(&todo.folder_name);
+        let mut files = Vec::new();
+        collect_relative_files(&folder_path, &folder_path.join(assets_dir_name()), &mut files);
+        for rel in files {
+            let path = folder_path.join(&rel);
+            let Ok(meta) = fs::symlink_metadata(&path) else { continue };
+            if !meta.is_file() || meta.len() == 0 {
+                continue;
+            }
+            let Some(hash) = hash_file(&path) else { continue };
+            by_hash
+                .entry((meta.len(), hash))
+                .or_default()
+                .push((format!("{}/{}", todo.folder_name, rel), file_identity(&meta)));
+        }
+    }
+
+    let mut report = AssetDedupeReport::default();
+    for ((size, hash), files) in by_hash {
+        if files.len() < 2 {
+            continue;
+        }
+        let mut seen = std::collections::HashSet::new();
+        let distinct = files
+            .iter()
+            .filter(|(_, id)| id.is_none_or(|id| seen.insert(id)))
+            .count();
+        if distinct < 2 {
+            continue;
+        }
+        report.reclaimable_bytes += size * (distinct as u64 - 1);
+        if link {
+            let (first, first_id) = &files[0];
+            let source = data_dir.join(first);
+            for (rel, id) in &files[1..] {
+                if first_id.is_some() && id == first_id {
+                    continue;
+                }
+                let target = data_dir.join(rel);
+                let tmp = target.with_extension("dedupe-tmp");
+                if fs::hard_link(&source, &tmp).is_ok() {
+                    if fs::rename(&tmp, &target).is_ok() {
+                        report.linked += 1;
+                    } else {
+                        let _ = fs::remove_file(&tmp);
+                    }
+                }
+            }
+        }
+        report.duplicates.push(DuplicateAsset {
+            hash,
+            size,
+            files: files.into_iter().map(|(rel, _)| rel).collect(),
+        });
+    }
+    report.duplicates.sort_by_key(|d| std::cmp::Reverse(d.size * d.files.len() as u64));
+    Ok(report)
+}
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Serialize, Debug)]
+struct ManifestEntry {
+    /// 相对数据目录的路径（统一使用 / 分隔）
+    path: String,
+    size: u64,
+    /// 修改时间（UTC RFC 3339），便于不同时区的机器对比
+    modified_at: Option<String>,
+    hash: String,
+}
+
+/// 列出数据目录下所有文件的相对路径、大小、修改时间与内容哈希，按路径排序写入 manifest.json；
+/// 不含备份目录、锁文件与清单本身，对比两台机器的清单即可看出哪些文件不同步
+#[tauri::command]
+fn generate_manifest(data_path: String) -> Result<Vec<ManifestEntry>, String> {
+    let data_path = expand_data_path(&data_path);
+    let root = Path::new(&data_path);
+    if !root.is_dir() {
+        return Err(format!("数据目录不存在: {}", data_path));
+    }
+    let mut files = Vec::new();
+    collect_relative_files(root, root, &mut files);
+    files.retain(|rel| {
+        rel != LOCK_FILE && rel != MANIFEST_FILE && !rel.starts_with(&format!("{}/", BACKUPS_DIR))
+    });
+    files.sort();
+    let mut entries = Vec::with_capacity(files.len());
+    for rel in files {
+        let path = root.join(&rel);
+        let meta = fs::metadata(&path).map_err(|e| log_error("generate_manifest", e))?;
+        let hash = hash_file(&path).ok_or_else(|| format!("读取文件失败: {}", rel))?;
+        entries.push(ManifestEntry {
+            path: rel,
+            size: meta.len(),
+            modified_at: meta
+                .modified()
+                .ok()
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+            hash,
+        });
+    }
+    let content = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+    write_atomic(&root.join(MANIFEST_FILE), content.as_bytes())
+        .map_err(|e| log_error("generate_manifest", e))?;
+    Ok(entries)
+}
+
+#[tauri::command]
+fn get_content_hash(data_path: String, folder_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    if !folder_path.is_dir() {
+        return Err(format!("待办文件夹不存在: {}", folder_name));
+    }
+    let hash = compute_content_hash(&folder_path)?;
+    let mut meta = read_todo_meta(&folder_path);
+    if meta.content_hash.as_deref() != Some(hash.as_str()) {
+        meta.content_hash = Some(hash.clone());
+        write_todo_meta(&folder_path, &meta)?;
+    }
+    Ok(hash)
+}
+
+/// 优化后图片的最大边长（像素）
+const OPTIMIZE_MAX_DIMENSION: u32 = 1920;
+const OPTIMIZE_JPEG_QUALITY: u8 = 75;
+
+#[derive(Serialize, Debug, Default)]
+struct OptimizeReport {
+    bytes_saved: u64,
+    images_optimized: usize,
+    files_removed: usize,
+}
+
+fn collect_asset_paths(nodes: &[serde_json::Value], out: &mut std::collections::HashSet<String>) {
+    for node in nodes {
+        if let Some(asset) = node.get("assetPath").and_then(|a| a.as_str()) {
+            out.insert(asset.replace('\\', "/"));
+        }
+        if let Some(children) = node.get("children").and_then(|c| c.as_array()) {
+            collect_asset_paths(children, out);
+        }
+    }
+}
+
+/// 所列待办的详情中引用的共用资源（../.assets/ 下的文件名，已排除非法名称），按名称排序
+fn referenced_shared_assets<'a>(data_dir: &Path, folder_names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut assets = std::collections::HashSet::new();
+    for folder_name in folder_names {
+        if let Ok(content) = fs::read_to_string(data_dir.join(folder_name).join("content.json")) {
+            collect_asset_paths(&parse_detail_nodes(&content), &mut assets);
+        }
+    }
+    let mut names: Vec<String> = assets
+        .iter()
+        .filter_map(|a| a.strip_prefix(SHARED_ASSET_PREFIX))
+        .filter(|name| sanitize_folder_name(name).is_ok())
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names
+}
+
+#[derive(Serialize, Debug)]
+struct BrokenAssets {
+    folder_name: String,
+    /// 详情中引用、但 assets 中不存在的文件（assetPath）
+    missing: Vec<String>,
+}
+
+/// 检查所有待办的详情，列出引用了不存在资源文件的待办及缺失的文件
+#[tauri::command]
+fn scan_broken_assets(data_path: String) -> Vec<BrokenAssets> {
+    let data_path = expand_data_path(&data_path);
+    let mut broken = Vec::new();
+    for todo in load_todos(&data_path) {
+        let folder_path = Path::new(&data_path).join(&todo.folder_name);
+        let content = fs::read_to_string(folder_path.join("content.json")).unwrap_or_default();
+        let mut referenced = std::collections::HashSet::new();
+        collect_asset_paths(&parse_detail_nodes(&content), &mut referenced);
+        let mut missing: Vec<String> = referenced
+            .into_iter()
+            .filter(|asset| !folder_path.join(asset).is_file())
+            .collect();
+        if !missing.is_empty() {
+            missing.sort();
+            broken.push(BrokenAssets { folder_name: todo.folder_name, missing });
+        }
+    }
+    broken
+}
+
+#[derive(Serialize, Debug)]
+struct AssetIntegrityIssue {
+    /// 详情中的 assetPath
+    asset_path: String,
+    reason: String,
+}
+
+/// 确认待办详情中的每个资源引用都指向该待办 assets 目录内（或共用资源池 .assets 内）真实存在的文件，
+/// 返回越出目录（绝对路径、含 ..、指向其他待办文件夹或经符号链接跳出）或文件不存在的引用
+#[tauri::command]
+fn validate_asset_integrity(data_path: String, folder_name: String) -> Result<Vec<AssetIntegrityIssue>, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let data_dir = Path::new(&data_path);
+    let folder_path = data_dir.join(&folder_name);
+    let content = fs::read_to_string(folder_path.join("content.json")).map_err(|e| log_error("validate_asset_integrity", e))?;
+    let mut referenced = std::collections::HashSet::new();
+    collect_asset_paths(&parse_detail_nodes(&content), &mut referenced);
+    let mut referenced: Vec<String> = referenced.into_iter().collect();
+    referenced.sort();
+    let mut issues = Vec::new();
+    for asset in referenced {
+        let (base, rel) = match asset.strip_prefix(SHARED_ASSET_PREFIX) {
+            Some(name) => (data_dir.join(SHARED_ASSETS_DIR), name),
+            None => (folder_path.join(assets_dir_name()), asset.strip_prefix(&format!("{}/", assets_dir_name())).unwrap_or("")),
+        };
+        let rel_path = Path::new(rel);
+        let reason = if rel.is_empty() || rel_path.is_absolute() {
+            Some("引用不在 assets 目录下".to_string())
+        } else if rel_path.components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+            Some("引用路径越出 assets 目录".to_string())
+        } else {
+            let target = base.join(rel_path);
+            match (fs::canonicalize(&target), fs::canonicalize(&base)) {
+                (Ok(real), Ok(real_base)) if !real.starts_with(&real_base) => Some(format!("引用经符号链接指向目录外: {}", real.display())),
+                (Ok(real), _) if !real.is_file() => Some("引用的不是文件".to_string()),
+                (Ok(_), _) => None,
+                (Err(_), _) => Some("引用的文件不存在".to_string()),
+            }
+        };
+        if let Some(reason) = reason {
+            issues.push(AssetIntegrityIssue { asset_path: asset, reason });
+        }
+    }
+    Ok(issues)
+}
+
+/// 列出详情没有任何内容的待办：content.json 为空、只有空白、为 "{}" / "[]"，或节点中既无文字也无资源。
+/// 无法解析的 content.json 不在此列
+#[tauri::command]
+fn find_empty_details(data_path: String) -> Vec<String> {
+    let data_path = expand_data_path(&data_path);
+    load_todos(&data_path)
+        .into_iter()
+        .filter(|todo| {
+            let Ok(content) = fs::read_to_string(Path::new(&data_path).join(&todo.folder_name).join("content.json")) else {
+                return false;
+            };
+            if content.trim().is_empty() {
+                return true;
+            }
+            let nodes = match serde_json::from_str::<serde_json::Value>(strip_bom(&content)) {
+                Ok(serde_json::Value::Array(nodes)) => nodes,
+                Ok(serde_json::Value::Object(map)) => return map.is_empty(),
+                _ => return false,
+            };
+            let mut lines = Vec::new();
+            collect_block_text(&nodes, &mut lines);
+            let mut assets = std::collections::HashSet::new();
+            collect_asset_paths(&nodes, &mut assets);
+            lines.iter().all(|l| l.trim().is_empty()) && assets.is_empty()
+        })
+        .map(|todo| todo.folder_name)
+        .collect()
+}
+
+/// 缩小并重新编码图片，仅在结果更小时返回新内容；GIF 可能含动画，不处理
+fn reencode_image(path: &Path) -> Option<Vec<u8>> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let format = match ext.as_str() {
+        "jpg" | "jpeg" => image::ImageFormat::Jpeg,
+        "png" => image::ImageFormat::Png,
+        _ => return None,
+    };
+    let original = fs::read(path).ok()?;
+    let mut img = image::load_from_memory_with_format(&original, format).ok()?;
+    if img.width() > OPTIMIZE_MAX_DIMENSION || img.height() > OPTIMIZE_MAX_DIMENSION {
+        img = img.resize(OPTIMIZE_MAX_DIMENSION, OPTIMIZE_MAX_DIMENSION, image::imageops::FilterType::Lanczos3);
+    }
+    let mut out = Vec::new();
+    match format {
+        image::ImageFormat::Jpeg => {
+            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, OPTIMIZE_JPEG_QUALITY);
+            img.to_rgb8().write_with_encoder(encoder).ok()?;
+        }
+        _ => {
+            let encoder = image::codecs::png::PngEncoder::new_with_quality(
+                &mut out,
+                image::codecs::png::CompressionType::Best,
+                image::codecs::png::FilterType::Adaptive,
+            );
+            img.write_with_encoder(encoder).ok()?;
+        }
+    }
+    (out.len() < original.len()).then_some(out)
+}
+
+/// 压缩待办 assets 中的图片并删除 content.json 未引用的文件，返回节省的字节数等统计；
+/// 用于待办完成后归档前瘦身，文件名保持不变以免破坏引用
+#[tauri::command]
+fn optimize_folder(data_path: String, folder_name: String) -> Result<OptimizeReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    if !folder_path.is_dir() {
+        return Err(format!("待办文件夹不存在: {}", folder_name));
+    }
+    let assets_dir = folder_path.join(assets_dir_name());
+    let mut report = OptimizeReport::default();
+    if !assets_dir.is_dir() {
+        return Ok(report);
+    }
+    let detail_path = folder_path.join("content.json");
+    let content = fs::read_to_string(&detail_path).unwrap_or_default();
+    // content.json 存在但无法解析时无法判断引用关系，不删除任何文件
+    let nodes: Vec<serde_json::Value> = if content.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(strip_bom(&content)).map_err(|e| format!("content.json 解析失败，已取消优化: {}", e))?
+    };
+    let mut referenced = std::collections::HashSet::new();
+    collect_asset_paths(&nodes, &mut referenced);
+
+    for entry in fs::read_dir(&assets_dir).map_err(|e| log_error("optimize_folder", e))?.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        let rel = format!("{}/{}", assets_dir_name(), entry.file_name().to_string_lossy());
+        if !referenced.contains(&rel) {
+            fs::remove_file(&path).map_err(|e| log_error("optimize_folder", e))?;
+            report.bytes_saved += size;
+            report.files_removed += 1;
+            continue;
+        }
+        if let Some(bytes) = reencode_image(&path) {
+            write_atomic(&path, &bytes).map_err(|e| log_error("optimize_folder", e))?;
+            report.bytes_saved += size.saturating_sub(bytes.len() as u64);
+            report.images_optimized += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// 将 JSON 值中所有字符串的换行统一为 eol，返回是否有改动
+fn normalize_value_line_endings(value: &mut serde_json::Value, eol: &str) -> bool {
+    match value {
+        serde_json::Value::String(text) => {
+            let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+            let normalized = if eol == "\n" { normalized } else { normalized.replace('\n', eol) };
+            if normalized != *text {
+                *text = normalized;
+                return true;
+            }
+            false
+        }
+        serde_json::Value::Array(items) => {
+            let mut changed = false;
+            for v in items.iter_mut() {
+                changed |= normalize_value_line_endings(v, eol);
+            }
+            changed
+        }
+        serde_json::Value::Object(map) => {
+            let mut changed = false;
+            for v in map.values_mut() {
+                changed |= normalize_value_line_endings(v, eol);
+            }
+            changed
+        }
+        _ => false,
+    }
+}
+
+fn normalize_folder_line_endings(data_dir: &Path, folder_name: &str, eol: &str) -> Result<bool, String> {
+    let detail_path = data_dir.join(folder_name).join("content.json");
+    if !detail_path.is_file() {
+        return Ok(false);
+    }
+    let content = fs::read_to_string(&detail_path).map_err(|e| e.to_string())?;
+    let mut value: serde_json::Value = serde_json::from_str(strip_bom(&content))
+        .map_err(|e| format!("content.json 解析失败 ({}): {}", folder_name, e))?;
+    let changed = normalize_value_line_endings(&mut value, eol);
+    // 文件本身按紧凑格式重写，去掉编辑器外部修改时引入的换行
+    let rewritten = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+    if !changed && rewritten == content {
+        return Ok(false);
+    }
+    write_atomic(&detail_path, rewritten.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+fn configured_line_ending(app: &tauri::AppHandle) -> &'static str {
+    if get_app_config(app.clone()).line_ending == "crlf" { "\r\n" } else { "\n" }
+}
+
+/// 按配置的换行符重写单个待办的 content.json，返回是否有改动
+#[tauri::command]
+fn normalize_line_endings(app: tauri::AppHandle, data_path: String, folder_name: String) -> Result<bool, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let eol = configured_line_ending(&app);
+    let changed = normalize_folder_line_endings(Path::new(&data_path), &folder_name, eol)
+        .map_err(|e| log_error("normalize_line_endings", e))?;
+    if changed {
+        #[derive(Clone, Serialize)]
+        struct Payload { folder_name: String }
+        let _ = app.emit("todo-detail-changed", Payload { folder_name });
+    }
+    Ok(changed)
+}
+
+/// 对所有待办执行 normalize_line_endings，返回改动的文件夹数；单个文件失败时记录日志并继续
+#[tauri::command]
+fn normalize_all_line_endings(app: tauri::AppHandle, data_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let eol = configured_line_ending(&app);
+    let mut changed = 0;
+    for todo in load_todos(&data_path) {
+        match normalize_folder_line_endings(Path::new(&data_path), &todo.folder_name, eol) {
+            Ok(true) => changed += 1,
+            Ok(false) => {}
+            Err(e) => {
+                log_error("normalize_all_line_endings", e);
+            }
+        }
+    }
+    if changed > 0 {
+        let _ = app.emit("todos-changed", ());
+    }
+    Ok(changed)
+}
+
+#[tauri::command]
+fn move_data(app: tauri::AppHandle, old_path: String, new_path: String) -> Result<(), String> {
+    let old_path = expand_data_path(&old_path);
+    let new_path = expand_data_path(&new_path);
+    if old_path == new_path || old_path.is_empty() || new_path.is_empty() {
+        return Ok(());
+    }
+    check_data_path_outside_config(&app, &new_path)?;
+
+    let old_p = Path::new(&old_path);
+    let new_p = Path::new(&new_path);
+    
+    if !old_p.exists() {
+        return Ok(());
+    }
+    
+    if !new_p.exists() {
+        fs::create_dir_all(new_p).map_err(|e| log_error("move_data", format!("Failed to create new directory: {}", e)))?;
+    }
+    
+    // 遍历旧路径下的所有文件和文件夹（跳过配置中排除的项，默认包含 config.json，其属于应用配置）
+    let exclude = get_app_config(app.clone()).move_exclude;
+    for entry in fs::read_dir(old_p).map_err(|e| log_error("move_data", format!("Failed to read old directory: {}", e)))? {
+        let entry = entry.map_err(|e| log_error("move_data", e))?;
+        let path = entry.path();
+        let name = path.file_name().ok_or("Invalid file name")?;
+        if is_excluded(&name.to_string_lossy(), &exclude) {
+            continue;
+        }
+        let dest = new_p.join(name);
+        
+        // 文件夹递归复制，符号链接重建为链接而不复制其指向的内容
+        copy_path(&path, &dest).map_err(|e| log_error("move_data", format!("Failed to copy: {}", e)))?;
+        remove_path(&path).map_err(|e| log_error("move_data", format!("Failed to remove old entry: {}", e)))?;
+    }
+    let _ = app.emit("todos-changed", ());
+    Ok(())
+}
+
+fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
+    let mut visited = std::collections::HashSet::new();
+    copy_dir_inner(src.as_ref(), dst.as_ref(), &mut visited)
+}
+
+/// 递归复制目录；符号链接原样重建而不跟随，已访问过的真实目录（如绑定挂载造成的循环）报错
+fn copy_dir_inner(src: &Path, dst: &Path, visited: &mut std::collections::HashSet<PathBuf>) -> std::io::Result<()> {
+    let real = fs::canonicalize(src)?;
+    if !visited.insert(real.clone()) {
+        return Err(std::io::Error::other(format!("检测到目录循环: {}", src.display())));
+    }
+    if fs::canonicalize(dst.parent().unwrap_or(dst)).is_ok_and(|p| p.starts_with(&real)) {
+        return Err(std::io::Error::other(format!("不能将目录复制到其自身内部: {}", src.display())));
+    }
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let ty = entry.file_type()?;
+        let target = dst.join(entry.file_name());
+        if ty.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else if ty.is_dir() {
+            copy_dir_inner(&entry.path(), &target, visited)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// 在 dst 处重建与 src 指向相同目标的符号链接（不复制链接指向的内容）
+fn copy_symlink(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let link_target = fs::read_link(src)?;
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&link_target, dst)
+    }
+    #[cfg(windows)]
+    {
+        if src.is_dir() {
+            std::os::windows::fs::symlink_dir(&link_target, dst)
+        } else {
+            std::os::windows::fs::symlink_file(&link_target, dst)
+        }
+    }
+}
+
+/// 复制文件、目录或符号链接（链接本身），用于数据迁移与备份
+fn copy_path(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let ty = fs::symlink_metadata(src)?.file_type();
+    if ty.is_symlink() {
+        copy_symlink(src, dst)
+    } else if ty.is_dir() {
+        copy_dir_all(src, dst)
+    } else {
+        fs::copy(src, dst).map(|_| ())
+    }
+}
+
+/// 删除文件、目录或符号链接；符号链接只删除链接本身
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    let ty = fs::symlink_metadata(path)?.file_type();
+    if ty.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+fn node_inline_markdown(node: &serde_json::Value, out: &mut String) {
+    let mut inner = String::new();
+    if let Some(v) = node.get("value").and_then(|v| v.as_str()) {
+        inner.push_str(v);
+    }
+    if let Some(children) = node.get("children").and_then(|c| c.as_array()) {
+        for child in children {
+            node_inline_markdown(child, &mut inner);
+        }
+    }
+    match node.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+        "strong" if !inner.is_empty() => out.push_str(&format!("**{}**", inner)),
+        "em" if !inner.is_empty() => out.push_str(&format!("*{}*", inner)),
+        _ => out.push_str(&inner),
+    }
+}
+
+/// 将编辑器节点树转换为 Markdown，资源以相对 assetPath 引用
+fn detail_to_markdown(nodes: &[serde_json::Value], out: &mut Vec<String>) {
+    for node in nodes {
+        let children = node.get("children").and_then(|c| c.as_array());
+        let mut inline = String::new();
+        match node.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "h1" | "h2" => {
+                node_inline_markdown(node, &mut inline);
+                let hashes = if node["type"] == "h1" { "#" } else { "##" };
+                out.push(format!("{} {}", hashes, inline));
+            }
+            "ul" | "ol" | "taskList" => {
+                let ordered = node["type"] == "ol";
+                for (i, item) in children.into_iter().flatten().enumerate() {
+                    let mut text = String::new();
+                    node_inline_markdown(item, &mut text);
+                    out.push(match item.get("checked").and_then(|c| c.as_bool()) {
+                        Some(true) => format!("- [x] {}", text),
+                        Some(false) => format!("- [ ] {}", text),
+                        None if ordered => format!("{}. {}", i + 1, text),
+                        None => format!("- {}", text),
+                    });
+                }
+            }
+            "fold" => {
+                if let Some(children) = children {
+                    detail_to_markdown(children, out);
+                }
+            }
+            "code" => {
+                let lang = node.get("language").and_then(|l| l.as_str()).unwrap_or("");
+                let content = node.get("content").and_then(|c| c.as_str()).unwrap_or("");
+                out.push(format!("```{}\n{}\n```", lang, content));
+            }
+            "image" => {
+                let asset = node.get("assetPath").and_then(|a| a.as_str()).unwrap_or("");
+                out.push(format!("![]({})", asset));
+            }
+            "file" => {
+                let asset = node.get("assetPath").and_then(|a| a.as_str()).unwrap_or("");
+                let name = node.get("fileName").and_then(|n| n.as_str()).unwrap_or(asset);
+                out.push(format!("[{}]({})", name, asset));
+            }
+            _ => {
+                node_inline_markdown(node, &mut inline);
+                out.push(inline);
+            }
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 将编辑器节点树渲染为静态 HTML，资源以 "<folder>/<assetPath>" 相对引用
+fn detail_to_html(nodes: &[serde_json::Value], folder: &str, out: &mut String) {
+    for node in nodes {
+        let children = node.get("children").and_then(|c| c.as_array()).map(|c| c.as_slice()).unwrap_or(&[]);
+        let asset = node
+            .get("assetPath")
+            .and_then(|a| a.as_str())
+            .map(|a| {
+                // 按年份归档的文件夹名含 /，逐段编码
+                let folder = folder.split('/').map(encode_uri_component).collect::<Vec<_>>().join("/");
+                escape_html(&format!("{}/{}", folder, a))
+            });
+        match node.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "text" => out.push_str(&escape_html(node.get("value").and_then(|v| v.as_str()).unwrap_or(""))),
+            tag @ ("p" | "h1" | "h2" | "strong" | "em" | "ul" | "ol" | "li") => {
+                out.push_str(&format!("<{}>", tag));
+                detail_to_html(children, folder, out);
+                out.push_str(&format!("</{}>", tag));
+            }
+            "color" => {
+                let color = node.get("color").and_then(|c| c.as_str()).filter(|c| is_hex_color(c));
+                match color {
+                    Some(color) => out.push_str(&format!("<span style=\"color: {}\">", color)),
+                    None => out.push_str("<span>"),
+                }
+                detail_to_html(children, folder, out);
+                out.push_str("</span>");
+            }
+            "taskList" => {
+                out.push_str("<ul class=\"tasks\">");
+                detail_to_html(children, folder, out);
+                out.push_str("</ul>");
+            }
+            "taskItem" => {
+                let checked = node.get("checked").and_then(|c| c.as_bool()).unwrap_or(false);
+                out.push_str(&format!("<li><input type=\"checkbox\" disabled{}> ", if checked { " checked" } else { "" }));
+                detail_to_html(children, folder, out);
+                out.push_str("</li>");
+            }
+            "fold" => {
+                out.push_str("<details open>");
+                detail_to_html(children, folder, out);
+                out.push_str("</details>");
+            }
+            "code" => {
+                let content = node.get("content").and_then(|c| c.as_str()).unwrap_or("");
+                out.push_str(&format!("<pre><code>{}</code></pre>", escape_html(content)));
+            }
+            "image" => {
+                if let Some(src) = asset {
+                    out.push_str(&format!("<p><img src=\"{}\" alt=\"\"></p>", src));
+                }
+            }
+            "file" => {
+                if let Some(href) = asset {
+                    let name = node.get("fileName").and_then(|n| n.as_str()).unwrap_or("file");
+                    out.push_str(&format!("<p><a href=\"{}\" download>📄 {}</a></p>", href, escape_html(name)));
+                }
+            }
+            _ => detail_to_html(children, folder, out),
+        }
+    }
+}
+
+const WEB_BUNDLE_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:860px;margin:2em auto;padding:0 1em;color:#333}\
+input#filter{width:100%;padding:.5em;margin-bottom:1em;box-sizing:border-box}\
+details.todo{border:1px solid #ddd;border-radius:8px;margin:.5em 0;padding:.5em 1em}\
+details.todo>summary{cursor:pointer;font-weight:600}\
+.status{font-size:.8em;color:#888;margin-left:.5em}.done>summary{text-decoration:line-through;color:#999}\
+ul.tasks{list-style:none;padding-left:1em}img{max-width:100%;border-radius:8px}pre{background:#f5f5f5;padding:.75em;overflow:auto}";
+
+const WEB_BUNDLE_SCRIPT: &str = "document.getElementById('filter').addEventListener('input',function(e){\
+var q=e.target.value.toLowerCase();document.querySelectorAll('details.todo').forEach(function(d){\
+d.style.display=d.textContent.toLowerCase().indexOf(q)>=0?'':'none'})})";
+
+/// 导出只读的静态网页：index.html 渲染待办列表与详情，资源复制到各自文件夹；include_done 为 false 时跳过已完成的待办。
+/// 返回导出的待办数
+#[tauri::command]
+fn export_web_bundle(data_path: String, dest_dir: String, include_done: bool) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let dest = PathBuf::from(expand_data_path(&dest_dir));
+    fs::create_dir_all(&dest).map_err(|e| log_error("export_web_bundle", e))?;
+    let todos: Vec<TodoItem> = load_todos(&data_path)
+        .into_iter()
+        .filter(|t| include_done || t.status != STATUS_COMPLETED)
+        .collect();
+
+    let mut body = String::new();
+    for todo in &todos {
+        let folder_path = Path::new(&data_path).join(&todo.folder_name);
+        let assets = folder_path.join(assets_dir_name());
+        if assets.is_dir() {
+            copy_dir_all(&assets, dest.join(&todo.folder_name).join(assets_dir_name()))
+                .map_err(|e| log_error("export_web_bundle", e))?;
+        }
+        let content = fs::read_to_string(folder_path.join("content.json")).unwrap_or_default();
+        let done = todo.status == STATUS_COMPLETED;
+        body.push_str(&format!(
+            "<details class=\"todo{}\"><summary>{}<span class=\"status\">{}</span></summary>",
+            if done { " done" } else { "" },
+            escape_html(&todo.title),
+            escape_html(&todo.status)
+        ));
+        detail_to_html(&parse_detail_nodes(&content), &todo.folder_name, &mut body);
+        body.push_str("</details>\n");
+    }
+    // 共用资源的 ../.assets/ 引用相对待办文件夹，复制到导出目录下的 .assets 后可直接显示
+    for name in referenced_shared_assets(Path::new(&data_path), todos.iter().map(|t| t.folder_name.as_str())) {
+        let source = Path::new(&data_path).join(SHARED_ASSETS_DIR).join(&name);
+        if source.is_file() {
+            fs::create_dir_all(dest.join(SHARED_ASSETS_DIR)).map_err(|e| log_error("export_web_bundle", e))?;
+            fs::copy(&source, dest.join(SHARED_ASSETS_DIR).join(&name)).map_err(|e| log_error("export_web_bundle", e))?;
+        }
+    }
+    let html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\">\
+<title>Simple Todo</title><style>{}</style></head><body><h1>Simple Todo</h1>\
+<p class=\"status\">{}</p><input id=\"filter\" placeholder=\"Filter…\">\n{}<script>{}</script></body></html>\n",
+        WEB_BUNDLE_STYLE,
+        escape_html(&chrono::Local::now().format("%Y-%m-%d %H:%M").to_string()),
+        body,
+        WEB_BUNDLE_SCRIPT
+    );
+    write_atomic(&dest.join("index.html"), html.as_bytes()).map_err(|e| log_error("export_web_bundle", e))?;
+    Ok(todos.len())
+}
+
+/// 卡片中详情摘要的最大字符数
+const CARD_SUMMARY_CHARS: usize = 240;
+
+const CARDS_STYLE: &str = "@page{size:A4;margin:1cm}body{margin:0;font-family:system-ui,sans-serif;color:#222}\
+.page{height:27.7cm;display:grid;gap:.5cm;break-after:page;box-sizing:border-box}.page:last-child{break-after:auto}\
+.card{border:1px dashed #999;border-radius:6px;padding:.6cm;overflow:hidden;display:flex;flex-direction:column}\
+.card h2{margin:0 0 .3cm;font-size:1.3em}.meta{font-size:.85em;color:#666;margin-bottom:.3cm}\
+.meta span+span{margin-left:1em}.summary{white-space:pre-wrap;font-size:.9em;line-height:1.4;flex:1;overflow:hidden}";
+
+/// 将选中的待办排成可打印的卡片（标题、状态、截止日期与详情摘要），按配置 cards_per_page 分页写入 HTML，
+/// 用浏览器打印即可得到纸质卡片；返回导出的卡片数
+#[tauri::command]
+fn export_cards(app: tauri::AppHandle, data_path: String, folder_names: Vec<String>, dest_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let todos = load_todos(&data_path);
+    let mut selected = Vec::new();
+    for folder_name in &folder_names {
+        let todo = todos
+            .iter()
+            .find(|t| t.folder_name == *folder_name)
+            .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+        selected.push(todo);
+    }
+    let per_page = get_app_config(app).cards_per_page.clamp(1, 12) as usize;
+    let cols = if per_page == 1 { 1 } else { 2 };
+    let rows = per_page.div_ceil(cols);
+    let mut body = String::new();
+    for page in selected.chunks(per_page) {
+        body.push_str(&format!(
+            "<div class=\"page\" style=\"grid-template-columns:repeat({},1fr);grid-template-rows:repeat({},1fr)\">",
+            cols, rows
+        ));
+        for todo in page {
+            let content = fs::read_to_string(Path::new(&data_path).join(&todo.folder_name).join("content.json")).unwrap_or_default();
+            let mut lines = Vec::new();
+            detail_to_plaintext(&parse_detail_nodes(&content), 0, &mut lines);
+            let text = lines.join("\n");
+            let mut summary: String = text.trim().chars().take(CARD_SUMMARY_CHARS).collect();
+            if text.trim().chars().count() > CARD_SUMMARY_CHARS {
+                summary.push('…');
+            }
+            let due = todo
+                .due_date
+                .as_deref()
+                .filter(|d| !d.trim().is_empty())
+                .map(|d| format!("<span>截止：{}</span>", escape_html(d)))
+                .unwrap_or_default();
+            body.push_str(&format!(
+                "<div class=\"card\"><h2>{}</h2><div class=\"meta\"><span>{}</span>{}</div><div class=\"summary\">{}</div></div>",
+                escape_html(&todo.title),
+                escape_html(&todo.status),
+                due,
+                escape_html(&summary)
+            ));
+        }
+        body.push_str("</div>\n");
+    }
+    let html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Simple Todo</title><style>{}</style></head><body>\n{}</body></html>\n",
+        CARDS_STYLE, body
+    );
+    let dest = PathBuf::from(expand_data_path(&dest_path));
+    write_atomic(&dest, html.as_bytes()).map_err(|e| log_error("export_cards", e))?;
+    Ok(selected.len())
+}
+
+/// 按标签分组时无标签一组的显示名
+const UNTAGGED_LABEL: &str = "无标签";
+
+/// 看板类导出使用的分组（见 get_todos_grouped，不含已归档的待办）：按状态时所有已知状态按流转顺序排列，
+/// 按标签时只含非空的组、按名称排列，无标签的一组（键为空字符串）放在最后
+fn board_groups(data_path: String, group_by: &str) -> Result<Vec<(String, Vec<TodoItem>)>, String> {
+    let mut groups = get_todos_grouped(data_path, group_by.to_string())?;
+    for todos in groups.values_mut() {
+        todos.retain(|t| !t.archived);
+    }
+    let mut ordered = Vec::new();
+    if group_by == "status" {
+        for status in KNOWN_STATUSES {
+            ordered.push((status.to_string(), groups.remove(*status).unwrap_or_default()));
+        }
+        ordered.extend(groups);
+    } else {
+        let untagged = groups.remove("").unwrap_or_default();
+        ordered.extend(groups.into_iter().filter(|(_, todos)| !todos.is_empty()));
+        if !untagged.is_empty() {
+            ordered.push((String::new(), untagged));
+        }
+    }
+    Ok(ordered)
+}
+
+/// 看板图片的布局尺寸（像素）
+const BOARD_COLUMN_WIDTH: u32 = 260;
+const BOARD_GAP: u32 = 16;
+const BOARD_HEADER_HEIGHT: u32 = 40;
+const BOARD_CARD_HEIGHT: u32 = 56;
+const BOARD_MAX_HEIGHT: u32 = 16384;
+const BOARD_TITLE_PX: f32 = 15.0;
+const BOARD_META_PX: f32 = 12.0;
+
+/// 配置的字体缺少中文等字符时依次尝试的字体
+const CJK_FALLBACK_FONTS: &[&str] = &["Microsoft YaHei", "PingFang SC", "Noto Sans CJK SC", "Source Han Sans SC", "WenQuanYi Micro Hei"];
+
+fn parse_hex_rgba(value: &str) -> image::Rgba<u8> {
+    let hex = value.trim().trim_start_matches('#');
+    let hex: String = if hex.len() == 3 { hex.chars().flat_map(|c| [c, c]).collect() } else { hex.to_string() };
+    let byte = |i: usize| hex.get(i..i + 2).and_then(|h| u8::from_str_radix(h, 16).ok());
+    image::Rgba([byte(0).unwrap_or(0), byte(2).unwrap_or(0), byte(4).unwrap_or(0), byte(6).unwrap_or(255)])
+}
+
+/// 载入绘制图片用的字体数据：配置中的字体（找不到时为系统无衬线字体）在前，中文备用字体在后
+fn load_board_fonts(font_family: &str) -> Vec<(Vec<u8>, u32)> {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+    let query = |families: &[fontdb::Family]| db.query(&fontdb::Query { families, ..Default::default() });
+    let mut ids: Vec<fontdb::ID> = Vec::new();
+    let candidates = std::iter::once(query(&[fontdb::Family::Name(font_family), fontdb::Family::SansSerif]))
+        .chain(CJK_FALLBACK_FONTS.iter().map(|name| query(&[fontdb::Family::Name(name)])))
+        .chain(std::iter::once(db.faces().next().map(|f| f.id)));
+    for id in candidates.flatten() {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids.into_iter()
+        .filter_map(|id| db.with_face_data(id, |data, index| (data.to_vec(), index)))
+        .collect()
+}
+
+/// 以 alpha（0-1，再乘以颜色自身的透明度）将颜色叠加到像素上，超出图片的坐标忽略
+fn blend_pixel(img: &mut image::RgbaImage, x: i32, y: i32, color: image::Rgba<u8>, alpha: f32) {
+    if x < 0 || y < 0 || x >= img.width() as i32 || y >= img.height() as i32 {
+        return;
+    }
+    let a = alpha * color[3] as f32 / 255.0;
+    let pixel = img.get_pixel_mut(x as u32, y as u32);
+    for i in 0..3 {
+        pixel[i] = (pixel[i] as f32 * (1.0 - a) + color[i] as f32 * a).round() as u8;
+    }
+}
+
+fn fill_rect(img: &mut image::RgbaImage, x: u32, y: u32, w: u32, h: u32, color: image::Rgba<u8>) {
+    for py in y..(y + h).min(img.height()) {
+        for px in x..(x + w).min(img.width()) {
+            blend_pixel(img, px as i32, py as i32, color, 1.0);
+        }
+    }
+}
+
+/// 从 (x, baseline) 开始绘制单行文字（px 为字号，即 em 的像素大小），每个字符使用第一个包含它的字体；
+/// 宽度超出 max_width 时截断并加省略号
+#[allow(clippy::too_many_arguments)]
+fn draw_text_line(
+    img: &mut image::RgbaImage,
+    fonts: &[ab_glyph::FontVec],
+    text: &str,
+    x: f32,
+    baseline: f32,
+    px: f32,
+    max_width: f32,
+    color: image::Rgba<u8>,
+) {
+    use ab_glyph::{Font, ScaleFont};
+    let scale = |font: &ab_glyph::FontVec| ab_glyph::PxScale::from(px * font.height_unscaled() / font.units_per_em().unwrap_or(1000.0));
+    let glyph = |c: char| fonts.iter().map(|f| (f, f.glyph_id(c))).find(|(_, id)| id.0 != 0);
+    let advance = |c: char| glyph(c).map(|(f, id)| f.as_scaled(scale(f)).h_advance(id)).unwrap_or(0.0);
+    let mut chars: Vec<char> = text.chars().filter(|c| !c.is_control()).collect();
+    if chars.iter().map(|c| advance(*c)).sum::<f32>() > max_width {
+        let ellipsis = advance('…');
+        let mut width = 0.0;
+        let keep = chars
+            .iter()
+            .take_while(|c| {
+                width += advance(**c);
+                width + ellipsis <= max_width
+            })
+            .count();
+        chars.truncate(keep);
+        chars.push('…');
+    }
+    let mut pen = x;
+    for c in chars {
+        let Some((font, id)) = glyph(c) else { continue };
+        let scale = scale(font);
+        if let Some(outlined) = font.outline_glyph(id.with_scale_and_position(scale, ab_glyph::point(pen, baseline))) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                blend_pixel(img, bounds.min.x as i32 + gx as i32, bounds.min.y as i32 + gy as i32, color, coverage);
+            });
+        }
+        pen += font.as_scaled(scale).h_advance(id);
+    }
+}
+
+/// 将待办按状态或标签（group_by 为 "status" / "tag"）排成看板列并绘制为 PNG，每张卡片显示标题与截止日期，
+/// 颜色取自配置中的当前主题与状态 / 标签颜色；不含已归档与推迟中的待办。返回绘制的卡片数
+#[tauri::command]
+fn export_board_image(app: tauri::AppHandle, data_path: String, dest_path: String, group_by: String) -> Result<usize, String> {
+    let config = get_app_config(app.clone());
+    let status_colors = get_status_colors(app.clone());
+    let columns: Vec<(String, String, Vec<TodoItem>)> = board_groups(data_path, &group_by)?
+        .into_iter()
+        .map(|(key, todos)| match group_by.as_str() {
+            "status" => {
+                let color = status_colors.get(&key).cloned().unwrap_or_else(|| "#909399".to_string());
+                (key, color, todos)
+            }
+            _ if key.is_empty() => (UNTAGGED_LABEL.to_string(), "#909399".to_string(), todos),
+            _ => (key.clone(), get_tag_color(app.clone(), key), todos),
+        })
+        .collect();
+    if columns.is_empty() {
+        return Err("没有可导出的待办".to_string());
+    }
+
+    let max_cards = columns.iter().map(|(_, _, todos)| todos.len() as u32).max().unwrap_or(0);
+    let width = BOARD_GAP + columns.len() as u32 * (BOARD_COLUMN_WIDTH + BOARD_GAP);
+    let height = BOARD_GAP * 2 + BOARD_HEADER_HEIGHT + max_cards * (BOARD_CARD_HEIGHT + BOARD_GAP / 2) + BOARD_GAP / 2;
+    if height > BOARD_MAX_HEIGHT || width > BOARD_MAX_HEIGHT {
+        return Err("待办过多，无法绘制为一张图片".to_string());
+    }
+    let faces: Vec<ab_glyph::FontVec> = load_board_fonts(&config.font_family)
+        .into_iter()
+        .filter_map(|(data, index)| ab_glyph::FontVec::try_from_vec_and_index(data, index).ok())
+        .collect();
+    if faces.is_empty() {
+        return Err("未找到可用的字体".to_string());
+    }
+
+    let dark = config.theme == "dark";
+    let pick = |dark_color: &str, light_color: &str| parse_hex_rgba(if dark { dark_color } else { light_color });
+    let bg = pick(&config.bg_color_dark, &config.bg_color_light);
+    let text = pick(&config.text_color_dark, &config.text_color_light);
+    let border = pick(&config.border_color_dark, &config.border_color_light);
+    let column_bg = image::Rgba([border[0], border[1], border[2], 0x60]);
+    let meta = image::Rgba([text[0], text[1], text[2], 0xa0]);
+
+    let mut img = image::RgbaImage::from_pixel(width, height, bg);
+    let mut cards = 0;
+    for (i, (name, color, todos)) in columns.iter().enumerate() {
+        let x = BOARD_GAP + i as u32 * (BOARD_COLUMN_WIDTH + BOARD_GAP);
+        fill_rect(&mut img, x, BOARD_GAP, BOARD_COLUMN_WIDTH, height - BOARD_GAP * 2, column_bg);
+        fill_rect(&mut img, x, BOARD_GAP, BOARD_COLUMN_WIDTH, 4, parse_hex_rgba(color));
+        let header = format!("{} ({})", name, todos.len());
+        let text_width = (BOARD_COLUMN_WIDTH - 24) as f32;
+        draw_text_line(&mut img, &faces, &header, (x + 12) as f32, (BOARD_GAP + 28) as f32, BOARD_TITLE_PX, text_width, text);
+        for (row, todo) in todos.iter().enumerate() {
+            let y = BOARD_GAP + BOARD_HEADER_HEIGHT + row as u32 * (BOARD_CARD_HEIGHT + BOARD_GAP / 2);
+            let (card_x, card_w) = (x + 8, BOARD_COLUMN_WIDTH - 16);
+            fill_rect(&mut img, card_x, y, card_w, BOARD_CARD_HEIGHT, border);
+            fill_rect(&mut img, card_x + 1, y + 1, card_w - 2, BOARD_CARD_HEIGHT - 2, bg);
+            let text_x = (card_x + 10) as f32;
+            let text_width = (card_w - 20) as f32;
+            draw_text_line(&mut img, &faces, &todo.title, text_x, (y + 22) as f32, BOARD_TITLE_PX, text_width, text);
+            if let Some(due) = todo.due_date.as_deref().filter(|d| !d.trim().is_empty()) {
+                let due = format!("截止：{}", due.trim());
+                draw_text_line(&mut img, &faces, &due, text_x, (y + 44) as f32, BOARD_META_PX, text_width, meta);
+            }
+            cards += 1;
+        }
+    }
+    let dest = PathBuf::from(expand_data_path(&dest_path));
+    img.save_with_format(&dest, image::ImageFormat::Png)
+        .map_err(|e| log_error("export_board_image", e))?;
+    Ok(cards)
+}
+
+/// 文本树中各状态前的符号
+fn status_glyph(status: &str) -> &'static str {
+    match status {
+        "backlog" => "○",
+        STATUS_PENDING => "☐",
+        "in_progress" => "◐",
+        STATUS_COMPLETED => "☑",
+        _ => "·",
+    }
+}
+
+/// 将待办按状态或标签（group_by 为 "status" / "tag"，默认按状态）输出为纯文本树，分组与看板图片相同，
+/// 每行带状态符号与截止日期，便于在终端中查看或由脚本处理
+#[tauri::command]
+fn export_tree_text(data_path: String, group_by: Option<String>) -> Result<String, String> {
+    let group_by = group_by.unwrap_or_else(|| "status".to_string());
+    let groups = board_groups(data_path, &group_by)?;
+    let mut out = String::new();
+    for (key, todos) in groups {
+        let name = if key.is_empty() { UNTAGGED_LABEL } else { key.as_str() };
+        out.push_str(&format!("{} ({})\n", name, todos.len()));
+        for (i, todo) in todos.iter().enumerate() {
+            let branch = if i + 1 == todos.len() { "└──" } else { "├──" };
+            let due = todo
+                .due_date
+                .as_deref()
+                .filter(|d| !d.trim().is_empty())
+                .map(|d| format!("  [截止 {}]", d.trim()))
+                .unwrap_or_default();
+            out.push_str(&format!("{} {} {}{}\n", branch, status_glyph(&todo.status), todo.title.replace(['\r', '\n'], " "), due));
+        }
+    }
+    Ok(out)
+}
+
+/// 导出为 NDJSON：每个待办一行 JSON 对象，附带 detail 字段（详情的纯文本），逐行写入不在内存中拼接整个文件，
+/// 便于 grep / jq 处理；返回写入的行数
+#[tauri::command]
+fn export_ndjson(data_path: String, dest_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let dest = PathBuf::from(expand_data_path(&dest_path));
+    let file = fs::File::create(&dest).map_err(|e| log_error("export_ndjson", e))?;
+    let mut out = std::io::BufWriter::new(file);
+    let mut lines = 0;
+    for todo in load_todos(&data_path) {
+        let detail = get_detail_plaintext(data_path.clone(), todo.folder_name.clone()).unwrap_or_default();
+        let mut value = serde_json::to_value(&todo).map_err(|e| e.to_string())?;
+        value["detail"] = serde_json::Value::String(detail);
+        serde_json::to_writer(&mut out, &value).map_err(|e| log_error("export_ndjson", e))?;
+        out.write_all(b"\n").map_err(|e| log_error("export_ndjson", e))?;
+        lines += 1;
+    }
+    out.flush().map_err(|e| log_error("export_ndjson", e))?;
+    Ok(lines)
+}
+
+/// 转义 iCalendar 文本值中的特殊字符
+fn escape_ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// 按 RFC 5545 将超过 75 字节的内容行折行（续行以空格开头），不拆分 UTF-8 字符
+fn fold_ical_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// 将有截止日期的待办导出为 .ics（每个待办一个 VTODO，重复待办附带 RRULE），返回导出的条数
+#[tauri::command]
+fn export_ical(data_path: String, dest_path: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//LoosePrince//Simple Todo//ZH".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    let mut count = 0;
+    for todo in load_todos(&data_path) {
+        let Some(due) = todo.due_date.as_deref().map(str::trim).filter(|d| !d.is_empty()) else { continue };
+        let due_line = if let Ok(t) = chrono::DateTime::parse_from_rfc3339(due) {
+            format!("DUE:{}", t.with_timezone(&chrono::Utc).format("%Y%m%dT%H%M%SZ"))
+        } else if let Some(d) = parse_due_date(due) {
+            format!("DUE;VALUE=DATE:{}", d.format("%Y%m%d"))
+        } else {
+            continue;
+        };
+        let status = match todo.status.as_str() {
+            STATUS_COMPLETED => "COMPLETED",
+            "in_progress" => "IN-PROCESS",
+            _ => "NEEDS-ACTION",
+        };
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:{}@simple-todo", todo.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("SUMMARY:{}", escape_ical_text(&todo.title)));
+        lines.push(due_line);
+        lines.push(format!("STATUS:{}", status));
+        if todo.priority > 0 {
+            // iCalendar 中 1 最高、9 最低
+            let priority = match todo.priority {
+                3.. => 1,
+                2 => 5,
+                _ => 9,
+            };
+            lines.push(format!("PRIORITY:{}", priority));
+        }
+        if !todo.tags.is_empty() {
+            let tags: Vec<String> = todo.tags.iter().map(|t| escape_ical_text(t)).collect();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        let freq = match todo.recurrence.as_deref() {
+            Some("daily") => Some("DAILY"),
+            Some("weekly") => Some("WEEKLY"),
+            Some("monthly") => Some("MONTHLY"),
+            Some("yearly") => Some("YEARLY"),
+            _ => None,
+        };
+        if let Some(freq) = freq {
+            lines.push(format!("RRULE:FREQ={}", freq));
+        }
+        lines.push("END:VTODO".to_string());
+        count += 1;
+    }
+    lines.push("END:VCALENDAR".to_string());
+    let mut out = String::new();
+    for line in &lines {
+        fold_ical_line(line, &mut out);
+    }
+    write_atomic(Path::new(&expand_data_path(&dest_path)), out.as_bytes()).map_err(|e| log_error("export_ical", e))?;
+    Ok(count)
+}
+
+/// 还原 iCalendar 文本值中的转义
+fn unescape_ical_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// 将 iCalendar 的日期或日期时间转为 due_date：VALUE=DATE 为 YYYY-MM-DD，带时间的转为本地时区的 RFC 3339
+fn ical_date_to_due(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Some(d.format("%Y-%m-%d").to_string());
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let t = chrono::NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(t.and_utc().with_timezone(&chrono::Local).to_rfc3339());
+    }
+    // 浮动时间与带 TZID 的时间按本地时间处理
+    let t = chrono::NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    t.and_local_timezone(chrono::Local).earliest().map(|t| t.to_rfc3339())
+}
+
+/// 解析 iCalendar 文本（含折行），返回每个 VTODO / VEVENT 的组件名与属性（属性名大写 -> 值）
+fn parse_ical_components(text: &str) -> Vec<(String, std::collections::HashMap<String, String>)> {
+    let mut unfolded: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !unfolded.is_empty() => unfolded.last_mut().unwrap().push_str(rest),
+            _ => unfolded.push(line.to_string()),
+        }
+    }
+    let mut components = Vec::new();
+    let mut current: Option<(String, std::collections::HashMap<String, String>)> = None;
+    for line in unfolded {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let name = key.split(';').next().unwrap_or("").to_uppercase();
+        match (name.as_str(), value.trim().to_uppercase().as_str()) {
+            ("BEGIN", kind @ ("VTODO" | "VEVENT")) => current = Some((kind.to_string(), Default::default())),
+            ("END", "VTODO" | "VEVENT") => components.extend(current.take()),
+            _ => {
+                if let Some((_, props)) = current.as_mut() {
+                    props.entry(name).or_insert_with(|| value.to_string());
+                }
+            }
+        }
+    }
+    components
+}
+
+#[derive(Serialize, Debug, Default)]
+struct IcalImportReport {
+    created: usize,
+    /// UID 已导入过且内容有变化而更新的待办
+    updated: usize,
+    /// 缺少标题，或 UID 已导入过且内容未变化的条目
+    skipped: usize,
+}
+
+/// 导入 .ics 中的 VTODO 与 VEVENT：SUMMARY 为标题，DUE（没有时取 DTSTART）为截止日期，DESCRIPTION 写入详情，
+/// RRULE 的 FREQ 转为 recurrence；UID 记入 meta.json，重新导入同一日历时更新已有待办而不重复创建
+#[tauri::command]
+fn import_ical(app: tauri::AppHandle, data_path: String, src_path: String) -> Result<IcalImportReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let bytes = fs::read(expand_data_path(&src_path)).map_err(|e| log_error("import_ical", e))?;
+    let (text, _) = decode_text(&bytes).ok_or_else(|| "无法识别日历文件的编码".to_string())?;
+    let default_status = get_app_config(app.clone()).default_status;
+    let mut todos = load_todos(&data_path);
+    let data_dir = Path::new(&data_path);
+    let mut by_uid: std::collections::HashMap<String, String> = todos
+        .iter()
+        .filter_map(|t| Some((read_todo_meta(&data_dir.join(&t.folder_name)).ical_uid?, t.folder_name.clone())))
+        .collect();
+    let mut report = IcalImportReport::default();
+    let mut changed = false;
+    for (kind, props) in parse_ical_components(&text) {
+        let title = props.get("SUMMARY").map(|s| unescape_ical_text(s).trim().to_string()).unwrap_or_default();
+        if title.is_empty() {
+            report.skipped += 1;
+            continue;
+        }
+        let due_date = props
+            .get("DUE")
+            .filter(|_| kind == "VTODO")
+            .or_else(|| props.get("DTSTART"))
+            .and_then(|v| ical_date_to_due(v));
+        let status = match props.get("STATUS").map(|s| s.trim().to_uppercase()).as_deref() {
+            Some("COMPLETED") => STATUS_COMPLETED.to_string(),
+            Some("IN-PROCESS") => "in_progress".to_string(),
+            Some("NEEDS-ACTION") => STATUS_PENDING.to_string(),
+            _ => default_status.clone(),
+        };
+        // iCalendar 中 1 最高、9 最低，0 为未定义
+        let priority = match props.get("PRIORITY").and_then(|p| p.trim().parse::<u8>().ok()) {
+            Some(1..=4) => 3,
+            Some(5) => 2,
+            Some(6..=9) => 1,
+            _ => 0,
+        };
+        let tags: Vec<String> = props
+            .get("CATEGORIES")
+            .map(|c| c.split(',').map(|t| unescape_ical_text(t).trim().to_string()).filter(|t| !t.is_empty()).collect())
+            .unwrap_or_default();
+        let recurrence = props.get("RRULE").and_then(|rule| {
+            let freq = rule.split(';').find_map(|part| part.trim().strip_prefix("FREQ="))?;
+            match freq.to_uppercase().as_str() {
+                "DAILY" => Some("daily".to_string()),
+                "WEEKLY" => Some("weekly".to_string()),
+                "MONTHLY" => Some("monthly".to_string()),
+                "YEARLY" => Some("yearly".to_string()),
+                _ => None,
+            }
+        });
+        let description = props.get("DESCRIPTION").map(|d| unescape_ical_text(d)).unwrap_or_default();
+        let content = serde_json::to_string(&text_to_detail_nodes(&description)).map_err(|e| e.to_string())?;
+        let uid = props.get("UID").map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+
+        if let Some(todo) = uid
+            .as_ref()
+            .and_then(|uid| by_uid.get(uid))
+            .and_then(|folder| todos.iter_mut().find(|t| t.folder_name == *folder))
+        {
+            let detail_path = data_dir.join(&todo.folder_name).join("content.json");
+            let content_changed = fs::read_to_string(&detail_path).map(|c| c != content).unwrap_or(true);
+            let before = todo.clone();
+            todo.title = title;
+            todo.due_date = due_date;
+            todo.status = status;
+            todo.priority = priority;
+            todo.tags = tags;
+            todo.recurrence = recurrence;
+            if *todo == before && !content_changed {
+                report.skipped += 1;
+                continue;
+            }
+            if content_changed {
+                write_atomic(&detail_path, content.as_bytes()).map_err(|e| log_error("import_ical", e))?;
+            }
+            report.updated += 1;
+            changed = true;
+            continue;
+        }
+
+        let folder_name = create_todo_folder(data_path.clone())?;
+        let folder_path = data_dir.join(&folder_name);
+        write_atomic(&folder_path.join("content.json"), content.as_bytes()).map_err(|e| log_error("import_ical", e))?;
+        if let Some(uid) = uid {
+            let meta = TodoMeta { ical_uid: Some(uid.clone()), ..read_todo_meta(&folder_path) };
+            write_todo_meta(&folder_path, &meta)?;
+            by_uid.insert(uid, folder_name.clone());
+        }
+        todos.push(TodoItem {
+            id: Uuid::new_v4().to_string(),
+            title,
+            status,
+            folder_name,
+            due_date,
+            priority,
+            tags,
+            recurrence,
+            ..Default::default()
+        });
+        report.created += 1;
+        changed = true;
+    }
+    if changed {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(report)
+}
+
+/// 转义 CSV 字段：含逗号、引号或换行时整体加引号，内部引号加倍
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// 导出相关日期落在 [start, end] 内的待办（markdown 或 csv），返回导出的条数。
+/// 相关日期依次取完成时间、修改时间、创建时间；end 为 YYYY-MM-DD 时包含当天全天
+#[tauri::command]
+fn export_by_date_range(data_path: String, start: String, end: String, dest_path: String, format: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let from = parse_timestamp(&start).ok_or_else(|| format!("无效的时间: {}", start))?;
+    let mut to = parse_timestamp(&end).ok_or_else(|| format!("无效的时间: {}", end))?;
+    if chrono::DateTime::parse_from_rfc3339(end.trim()).is_err() {
+        to += chrono::Duration::days(1) - chrono::Duration::nanoseconds(1);
+    }
+    if from > to {
+        return Err("开始时间不能晚于结束时间".to_string());
+    }
+    let todos: Vec<(TodoItem, chrono::DateTime<chrono::Local>)> = load_todos(&data_path)
+        .into_iter()
+        .filter_map(|t| {
+            let date = [&t.completed_at, &t.updated_at, &t.created_at]
+                .into_iter()
+                .find_map(|d| d.as_deref().and_then(parse_timestamp))?;
+            (from <= date && date <= to).then_some((t, date))
+        })
+        .collect();
+    let mut lines = Vec::new();
+    match format.as_str() {
+        "markdown" => {
+            lines.push(format!("# {} ~ {}", start.trim(), end.trim()));
+            lines.push(String::new());
+            for (todo, date) in &todos {
+                let mark = if todo.status == STATUS_COMPLETED { "x" } else { " " };
+                lines.push(format!("- [{}] {} ({})", mark, todo.title, date.format("%Y-%m-%d")));
+            }
+        }
+        "csv" => {
+            lines.push("title,status,folder_name,created_at,updated_at,completed_at".to_string());
+            for (todo, _) in &todos {
+                let fields = [
+                    todo.title.as_str(),
+                    todo.status.as_str(),
+                    todo.folder_name.as_str(),
+                    todo.created_at.as_deref().unwrap_or(""),
+                    todo.updated_at.as_deref().unwrap_or(""),
+                    todo.completed_at.as_deref().unwrap_or(""),
+                ];
+                let row: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+                lines.push(row.join(","));
+            }
+        }
+        other => return Err(format!("不支持的格式: {}", other)),
+    }
+    let content = lines.join("\n") + "\n";
+    write_atomic(Path::new(&expand_data_path(&dest_path)), content.as_bytes())
+        .map_err(|e| log_error("export_by_date_range", e))?;
+    Ok(todos.len())
+}
+
+/// 订阅源中最多包含的条目数
+const FEED_MAX_ITEMS: usize = 50;
+
+/// 将最近完成或新建的待办导出为订阅源：kind 为 "json"（JSON Feed 1.1）或 "rss"（RSS 2.0）。
+/// 条目日期取完成时间，未完成的取创建时间，按日期倒序，返回写入的条目数
+#[tauri::command]
+fn export_feed(data_path: String, dest_path: String, kind: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    if !matches!(kind.as_str(), "json" | "rss") {
+        return Err(format!("不支持的订阅源格式: {}", kind));
+    }
+    let mut entries: Vec<(TodoItem, chrono::DateTime<chrono::Local>)> = load_todos(&data_path)
+        .into_iter()
+        .filter_map(|t| {
+            let date = t.completed_at.as_deref().or(t.created_at.as_deref()).and_then(parse_timestamp)?;
+            Some((t, date))
+        })
+        .collect();
+    entries.sort_by_key(|(_, date)| std::cmp::Reverse(*date));
+    entries.truncate(FEED_MAX_ITEMS);
+    let detail_text = |todo: &TodoItem| {
+        let content = fs::read_to_string(Path::new(&data_path).join(&todo.folder_name).join("content.json")).unwrap_or_default();
+        let mut lines = Vec::new();
+        collect_block_text(&parse_detail_nodes(&content), &mut lines);
+        lines.join("\n").trim().to_string()
+    };
+    let out = if kind == "json" {
+        let items: Vec<serde_json::Value> = entries
+            .iter()
+            .map(|(todo, date)| {
+                serde_json::json!({
+                    "id": todo.id,
+                    "title": todo.title,
+                    "content_text": detail_text(todo),
+                    "date_published": date.to_rfc3339(),
+                    "tags": todo.tags,
+                })
+            })
+            .collect();
+        let feed = serde_json::json!({
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": "Simple Todo",
+            "items": items,
+        });
+        serde_json::to_string_pretty(&feed).map_err(|e| e.to_string())?
+    } else {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\"><channel>\
+<title>Simple Todo</title><link>https://github.com/LoosePrince/simple-todo</link><description>Simple Todo</description>\n",
+        );
+        for (todo, date) in &entries {
+            xml.push_str(&format!(
+                "<item><title>{}</title><description>{}</description><guid isPermaLink=\"false\">{}</guid><pubDate>{}</pubDate></item>\n",
+                escape_html(&todo.title),
+                escape_html(&detail_text(todo)),
+                escape_html(&todo.id),
+                date.to_rfc2822()
+            ));
+        }
+        xml.push_str("</channel></rss>\n");
+        xml
+    };
+    write_atomic(Path::new(&expand_data_path(&dest_path)), out.as_bytes()).map_err(|e| log_error("export_feed", e))?;
+    Ok(entries.len())
+}
+
+/// 将待办详情按 markdown / plain 格式转换后写入系统剪贴板
+#[tauri::command]
+fn copy_detail_to_clipboard(
+    app: tauri::AppHandle,
+    data_path: String,
+    folder_name: String,
+    format: String,
+) -> Result<(), String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let nodes = parse_detail_nodes(&get_todo_detail(data_path, folder_name)?);
+    let mut lines = Vec::new();
+    match format.as_str() {
+        "markdown" => detail_to_markdown(&nodes, &mut lines),
+        "plain" => collect_block_text(&nodes, &mut lines),
+        other => return Err(format!("不支持的格式: {}", other)),
+    }
+    let separator = if format == "markdown" { "\n\n" } else { "\n" };
+    let text = lines.join(separator).trim().to_string();
+    if text.is_empty() {
+        return Err("详情为空".to_string());
+    }
+    app.clipboard().write_text(text).map_err(|e| log_error("copy_detail_to_clipboard", e))
+}
+
+/// 将所选待办按选择顺序转为 Markdown 任务清单（详情缩进置于各项之下）并写入系统剪贴板，返回复制的字符数
+#[tauri::command]
+fn copy_todos_markdown(app: tauri::AppHandle, data_path: String, folder_names: Vec<String>) -> Result<usize, String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+    let data_path = expand_data_path(&data_path);
+    let todos = load_todos(&data_path);
+    let mut lines = Vec::new();
+    for folder_name in &folder_names {
+        let todo = todos
+            .iter()
+            .find(|t| t.folder_name == *folder_name)
+            .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+        let mark = if todo.status == STATUS_COMPLETED { "x" } else { " " };
+        lines.push(format!("- [{}] {}", mark, todo.title));
+        let mut body = Vec::new();
+        detail_to_markdown(&parse_detail_nodes(&get_todo_detail(data_path.clone(), todo.folder_name.clone())?), &mut body);
+        for block in body.iter().filter(|b| !b.trim().is_empty()) {
+            lines.extend(block.lines().map(|l| format!("  {}", l)));
+        }
+    }
+    if lines.is_empty() {
+        return Err("未选择待办".to_string());
+    }
+    let text = lines.join("\n");
+    let count = text.chars().count();
+    app.clipboard().write_text(text).map_err(|e| log_error("copy_todos_markdown", e))?;
+    Ok(count)
+}
+
+enum PdfBlock {
+    Heading(u8, String),
+    Text(String),
+    Image(PathBuf),
+}
+
+/// 将节点树展开为 PDF 排版用的块序列，图片按 assetPath 解析为文件路径
+fn collect_pdf_blocks(nodes: &[serde_json::Value], folder_path: &Path, out: &mut Vec<PdfBlock>) {
+    for node in nodes {
+        let children = node.get("children").and_then(|c| c.as_array());
+        match node.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "h1" | "h2" => {
+                let mut text = String::new();
+                node_inline_text(node, &mut text);
+                let level = if node["type"] == "h1" { 1 } else { 2 };
+                out.push(PdfBlock::Heading(level, text));
+            }
+            "ul" | "ol" | "taskList" => {
+                let ordered = node["type"] == "ol";
+                for (i, item) in children.into_iter().flatten().enumerate() {
+                    let mut text = String::new();
+                    node_inline_text(item, &mut text);
+                    let prefix = match item.get("checked").and_then(|c| c.as_bool()) {
+                        Some(true) => "[x] ".to_string(),
+                        Some(false) => "[ ] ".to_string(),
+                        None if ordered => format!("{}. ", i + 1),
+                        None => "• ".to_string(),
+                    };
+                    out.push(PdfBlock::Text(prefix + &text));
+                }
+            }
+            "fold" => {
+                if let Some(children) = children {
+                    collect_pdf_blocks(children, folder_path, out);
+                }
+            }
+            "code" => {
+                if let Some(content) = node.get("content").and_then(|c| c.as_str()) {
+                    out.extend(content.lines().map(|l| PdfBlock::Text(format!("    {}", l))));
+                }
+            }
+            "image" => {
+                if let Some(asset) = node.get("assetPath").and_then(|a| a.as_str()) {
+                    out.push(PdfBlock::Image(folder_path.join(asset)));
+                }
+            }
+            "file" => {
+                let name = node.get("fileName").and_then(|n| n.as_str()).unwrap_or("");
+                out.push(PdfBlock::Text(format!("[附件] {}", name)));
+            }
+            _ => {
+                let mut text = String::new();
+                node_inline_text(node, &mut text);
+                out.push(PdfBlock::Text(text));
+            }
+        }
+    }
+}
+
+/// 依次尝试常见的系统字体（优先支持中文的字体），都不存在时返回 None
+fn find_pdf_font() -> Option<Vec<u8>> {
+    const CANDIDATES: &[&str] = &[
+        "C:\\Windows\\Fonts\\msyh.ttc",
+        "C:\\Windows\\Fonts\\simhei.ttf",
+        "C:\\Windows\\Fonts\\simsun.ttc",
+        "/System/Library/Fonts/PingFang.ttc",
+        "/System/Library/Fonts/STHeiti Light.ttc",
+        "/Library/Fonts/Arial Unicode.ttf",
+        "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    ];
+    CANDIDATES.iter().find_map(|p| fs::read(p).ok())
+}
+
+/// 估算一段文字在给定字号下的宽度（毫米），中日韩等全角字符按一个字宽计算
+fn estimate_text_width_mm(text: &str, font_size: f32) -> f32 {
+    let pt_to_mm = 0.3528;
+    text.chars()
+        .map(|c| if (c as u32) >= 0x2E80 { 1.0 } else { 0.55 })
+        .sum::<f32>()
+        * font_size
+        * pt_to_mm
+}
+
+fn wrap_text_mm(text: &str, font_size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        current.push(c);
+        if estimate_text_width_mm(&current, font_size) > max_width {
+            current.pop();
+            lines.push(std::mem::take(&mut current));
+            current.push(c);
+        }
+    }
+    lines.push(current);
+    lines
+}
+
+/// 将待办详情（含 assets 中的图片）导出为 A4 PDF，标题作为页首，状态与截止日期作为元信息
+#[tauri::command]
+fn export_todo_pdf(data_path: String, folder_name: String, dest_path: String) -> Result<(), String> {
+    use printpdf::{BuiltinFont, Image, ImageTransform, Mm, PdfDocument};
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let todo = load_todos(&data_path)
+        .into_iter()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("待办不存在: {}", folder_name))?;
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    let nodes = parse_detail_nodes(&get_todo_detail(data_path.clone(), folder_name.clone())?);
+    let mut blocks = Vec::new();
+    collect_pdf_blocks(&nodes, &folder_path, &mut blocks);
+
+    const PAGE_W: f32 = 210.0;
+    const PAGE_H: f32 = 297.0;
+    const MARGIN: f32 = 20.0;
+    let content_w = PAGE_W - MARGIN * 2.0;
+
+    let mut meta = format!("状态: {}", todo.status);
+    if let Some(due) = &todo.due_date {
+        meta.push_str(&format!("    截止: {}", due));
+    }
+    let (doc, page, layer) = PdfDocument::new(todo.title.clone(), Mm(PAGE_W), Mm(PAGE_H), "content");
+    let doc = doc.with_subject(meta.clone());
+    let font = match find_pdf_font() {
+        Some(bytes) => doc
+            .add_external_font_with_subsetting(std::io::Cursor::new(bytes), true)
+            .map_err(|e| e.to_string())?,
+        None => doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?,
+    };
+
+    let mut layer = doc.get_page(page).get_layer(layer);
+    let mut y = PAGE_H - MARGIN;
+    let new_page_if_needed = |y: &mut f32, needed: f32, layer: &mut printpdf::PdfLayerReference| {
+        if *y - needed < MARGIN {
+            let (page, l) = doc.add_page(Mm(PAGE_W), Mm(PAGE_H), "content");
+            *layer = doc.get_page(page).get_layer(l);
+            *y = PAGE_H - MARGIN;
+        }
+    };
+
+    let write_lines = |text: &str, size: f32, y: &mut f32, layer: &mut printpdf::PdfLayerReference| {
+        let line_h = size * 0.3528 * 1.5;
+        for line in wrap_text_mm(text, size, content_w) {
+            new_page_if_needed(y, line_h, layer);
+            *y -= line_h;
+            layer.use_text(line, size, Mm(MARGIN), Mm(*y), &font);
+        }
+    };
+
+    write_lines(&todo.title, 18.0, &mut y, &mut layer);
+    write_lines(&meta, 9.0, &mut y, &mut layer);
+    y -= 4.0;
+    for block in blocks {
+        match block {
+            PdfBlock::Heading(level, text) => {
+                y -= 2.0;
+                write_lines(&text, if level == 1 { 15.0 } else { 13.0 }, &mut y, &mut layer);
+            }
+            PdfBlock::Text(text) => write_lines(&text, 11.0, &mut y, &mut layer),
+            PdfBlock::Image(path) => {
+                let img = match image::open(&path) {
+                    Ok(img) => img,
+                    Err(_) => {
+                        write_lines(&format!("[图片缺失] {}", path.display()), 9.0, &mut y, &mut layer);
+                        continue;
+                    }
+                };
+                let dpi = 96.0;
+                let natural_w = img.width() as f32 / dpi * 25.4;
+                let natural_h = img.height() as f32 / dpi * 25.4;
+                let scale = (content_w / natural_w).min((PAGE_H - MARGIN * 2.0) / natural_h).min(1.0);
+                let h = natural_h * scale;
+                new_page_if_needed(&mut y, h + 2.0, &mut layer);
+                y -= h + 2.0;
+                Image::from_dynamic_image(&img).add_to_layer(
+                    layer.clone(),
+                    ImageTransform {
+                        translate_x: Some(Mm(MARGIN)),
+                        translate_y: Some(Mm(y)),
+                        scale_x: Some(scale),
+                        scale_y: Some(scale),
+                        dpi: Some(dpi),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+
+    let file = fs::File::create(expand_data_path(&dest_path)).map_err(|e| log_error("export_todo_pdf", e))?;
+    doc.save(&mut std::io::BufWriter::new(file))
+        .map_err(|e| log_error("export_todo_pdf", e))
+}
+
+#[derive(Serialize, Debug)]
+struct BackupInfo {
+    name: String,
+    size: u64,
+    created_at: Option<String>,
+    /// 已压缩为 <name>.zip，使用时会先解压
+    compressed: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct BackupReport {
+    backup_name: String,
+    ok: bool,
+    issues: Vec<String>,
+}
+
+/// 备份根目录：配置了 backup_path 时使用它，否则为 data_path/backups
+fn backups_dir(app: &tauri::AppHandle, data_path: &str) -> PathBuf {
+    let backup_path = get_app_config(app.clone()).backup_path;
+    if backup_path.trim().is_empty() {
+        Path::new(data_path).join(BACKUPS_DIR)
+    } else {
+        PathBuf::from(expand_data_path(backup_path.trim()))
+    }
+}
+
+fn backup_dir(app: &tauri::AppHandle, data_path: &str, backup_name: &str) -> Result<PathBuf, String> {
+    if backup_name.is_empty() || backup_name.contains(['/', '\\']) || backup_name.contains("..") {
+        return Err(format!("无效的备份名称: {}", backup_name));
+    }
+    let dir = backups_dir(app, data_path).join(backup_name);
+    if !dir.is_dir() {
+        let archive = compressed_backup_path(&dir);
+        if !archive.is_file() {
+            return Err(format!("备份不存在: {}", backup_name));
+        }
+        extract_backup(&archive, &dir).map_err(|e| log_error("extract_backup", e))?;
+    }
+    Ok(dir)
+}
+
+/// 压缩后的备份文件：与备份文件夹同名加 .zip
+fn compressed_backup_path(dir: &Path) -> PathBuf {
+    let mut name = dir.as_os_str().to_owned();
+    name.push(".zip");
+    PathBuf::from(name)
+}
+
+/// 将压缩的备份解压回同名文件夹（先解压到临时文件夹再改名），成功后删除压缩包
+fn extract_backup(archive_path: &Path, dir: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("压缩的备份已损坏: {}", e))?;
+    let tmp = dir.with_extension("extracting");
+    if tmp.exists() {
+        remove_path(&tmp).map_err(|e| e.to_string())?;
+    }
+    archive.extract(&tmp).map_err(|e| format!("解压备份失败: {}", e))?;
+    fs::rename(&tmp, dir).map_err(|e| e.to_string())?;
+    fs::remove_file(archive_path).map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Debug, Default)]
+struct BackupCompressReport {
+    compressed: Vec<String>,
+    bytes_reclaimed: u64,
+}
+
+/// 将早于 compress_backups_after_days 天的备份文件夹分别压缩为 <name>.zip 并删除原文件夹，
+/// 最近一次备份保持未压缩以便快速恢复。压缩的备份在恢复等操作时会自动解压
+#[tauri::command]
+fn compress_old_backups(app: tauri::AppHandle, data_path: String) -> Result<BackupCompressReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let config = get_app_config(app.clone());
+    let max_age = std::time::Duration::from_secs(config.compress_backups_after_days as u64 * 86400);
+    let mut report = BackupCompressReport::default();
+    let Ok(entries) = fs::read_dir(backups_dir(&app, &data_path)) else { return Ok(report) };
+    let mut backups: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    backups.sort();
+    // 备份名含时间，按名称排序后最后一个为最近的备份
+    backups.pop();
+    let options = zip_options(config.export_compression.max(1));
+    for dir in backups {
+        let old = dir
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age >= max_age);
+        if !old {
+            continue;
+        }
+        let archive_path = compressed_backup_path(&dir);
+        let tmp = dir.with_extension("zip.tmp");
+        let write = || -> Result<(), String> {
+            let file = fs::File::create(&tmp).map_err(|e| e.to_string())?;
+            let mut zip = zip::ZipWriter::new(std::io::BufWriter::new(file));
+            zip_add_dir(&mut zip, &dir, &dir, options)?;
+            zip.finish().map_err(|e| e.to_string())?;
+            fs::rename(&tmp, &archive_path).map_err(|e| e.to_string())
+        };
+        if let Err(e) = write() {
+            let _ = fs::remove_file(&tmp);
+            return Err(log_error("compress_old_backups", e));
+        }
+        let original = calculate_dir_size(&dir);
+        fs::remove_dir_all(&dir).map_err(|e| log_error("compress_old_backups", e))?;
+        let archived = fs::metadata(&archive_path).map(|m| m.len()).unwrap_or(0);
+        report.bytes_reclaimed += original.saturating_sub(archived);
+        report.compressed.push(dir.file_name().unwrap_or_default().to_string_lossy().into_owned());
+    }
+    Ok(report)
+}
+
+/// 自动备份的名称后缀，用于与手动备份区分
+const AUTO_BACKUP_SUFFIX: &str = "-auto";
+
+/// 自动备份的创建时间：取名称 backup-YYYYmmdd-HHMMSS-auto（可带 .zip）中的本地时间，
+/// 不受复制或同步备份目录后修改时间变化的影响；名称不符合该格式时返回 None
+fn auto_backup_time(name: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let stamp = name
+        .strip_suffix(".zip")
+        .unwrap_or(name)
+        .strip_suffix(AUTO_BACKUP_SUFFIX)?
+        .strip_prefix("backup-")?;
+    chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S")
+        .ok()?
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+/// 所有自动备份（含已压缩的）的路径与创建时间，按时间从旧到新
+fn auto_backups(app: &tauri::AppHandle, data_path: &str) -> Vec<(PathBuf, chrono::DateTime<chrono::Utc>)> {
+    let mut backups: Vec<(PathBuf, chrono::DateTime<chrono::Utc>)> = fs::read_dir(backups_dir(app, data_path))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| if e.file_name().to_string_lossy().ends_with(".zip") { e.path().is_file() } else { e.path().is_dir() })
+                .filter_map(|e| Some((e.path(), auto_backup_time(&e.file_name().to_string_lossy())?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    backups.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    backups
+}
+
+/// 最近一次自动备份的时间（RFC 3339），尚未自动备份过时为空
+#[tauri::command]
+fn get_last_backup_time(app: tauri::AppHandle, data_path: String) -> Option<String> {
+    let data_path = expand_data_path(&data_path);
+    auto_backups(&app, &data_path)
+        .last()
+        .map(|(_, time)| time.to_rfc3339())
+}
+
+/// 将数据目录（除备份目录与排除项外）完整复制为一个新备份，返回备份名称
+#[tauri::command]
+fn backup_data(app: tauri::AppHandle, data_path: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    create_backup(&app, &data_path, "")
+}
+
+fn create_backup(app: &tauri::AppHandle, data_path: &str, suffix: &str) -> Result<String, String> {
+    let data_dir = Path::new(data_path);
+    if !data_dir.exists() {
+        return Err(format!("数据目录不存在: {}", data_path));
+    }
+    let exclude = get_app_config(app.clone()).move_exclude;
+    let root = backups_dir(app, data_path);
+    let name = format!("backup-{}{}", chrono::Local::now().format("%Y%m%d-%H%M%S"), suffix);
+    let dest = root.join(&name);
+    if dest.exists() {
+        return Err(format!("备份已存在: {}", name));
+    }
+    fs::create_dir_all(&dest).map_err(|e| log_error("backup_data", e))?;
+    for entry in fs::read_dir(data_dir).map_err(|e| log_error("backup_data", e))?.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let name_str = file_name.to_string_lossy();
+        // 备份目录可能位于数据目录内，跳过以免递归复制
+        if name_str == BACKUPS_DIR || name_str == LOCK_FILE || path == root || is_excluded(&name_str, &exclude) {
+            continue;
+        }
+        // 备份目录可能位于另一个文件系统，一律复制而非重命名
+        copy_path(&path, &dest.join(&file_name)).map_err(|e| log_error("backup_data", e))?;
+    }
+    Ok(name)
+}
+
+#[tauri::command]
+fn list_backups(app: tauri::AppHandle, data_path: String) -> Vec<BackupInfo> {
+    let data_path = expand_data_path(&data_path);
+    let mut backups = Vec::new();
+    if let Ok(entries) = fs::read_dir(backups_dir(&app, &data_path)) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let (name, size, compressed) = if path.is_dir() {
+                (file_name, calculate_dir_size(&path), false)
+            } else if let Some(name) = file_name.strip_suffix(".zip").filter(|_| path.is_file()) {
+                (name.to_string(), path.metadata().map(|m| m.len()).unwrap_or(0), true)
+            } else {
+                continue;
+            };
+            let created_at = path
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339());
+            backups.push(BackupInfo { name, size, created_at, compressed });
+        }
+    }
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    backups
+}
+
+/// 比较时允许的最大行数乘积，超出时拒绝以免占用过多内存
+const MAX_DIFF_CELLS: usize = 16_000_000;
+
+#[derive(Serialize, Debug)]
+struct DiffRange {
+    /// "added"、"removed" 或 "unchanged"
+    kind: String,
+    /// 在版本 A / 版本 B 中的起始行号（从 0 开始）；added 的 start_a 与 removed 的 start_b 为插入位置
+    start_a: usize,
+    start_b: usize,
+    lines: Vec<String>,
+}
+
+/// 基于最长公共子序列的逐行比较，相邻的同类行合并为一段
+fn diff_lines(a: &[&str], b: &[&str]) -> Result<Vec<DiffRange>, String> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    if (mid_a.len() + 1).saturating_mul(mid_b.len() + 1) > MAX_DIFF_CELLS {
+        return Err("内容过长，无法逐行比较".to_string());
+    }
+    // lcs[i][j] 为 mid_a[i..] 与 mid_b[j..] 的最长公共子序列长度
+    let width = mid_b.len() + 1;
+    let mut lcs = vec![0u32; (mid_a.len() + 1) * width];
+    for i in (0..mid_a.len()).rev() {
+        for j in (0..mid_b.len()).rev() {
+            lcs[i * width + j] = if mid_a[i] == mid_b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+    let mut ranges: Vec<DiffRange> = Vec::new();
+    let mut push = |kind: &str, ia: usize, ib: usize, line: &str| match ranges.last_mut() {
+        Some(last) if last.kind == kind => last.lines.push(line.to_string()),
+        _ => ranges.push(DiffRange { kind: kind.to_string(), start_a: ia, start_b: ib, lines: vec![line.to_string()] }),
+    };
+    for (k, line) in a[..prefix].iter().enumerate() {
+        push("unchanged", k, k, line);
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < mid_a.len() || j < mid_b.len() {
+        if i < mid_a.len() && j < mid_b.len() && mid_a[i] == mid_b[j] {
+            push("unchanged", prefix + i, prefix + j, mid_a[i]);
+            i += 1;
+            j += 1;
+        } else if j < mid_b.len() && (i == mid_a.len() || lcs[i * width + j + 1] >= lcs[(i + 1) * width + j]) {
+            push("added", prefix + i, prefix + j, mid_b[j]);
+            j += 1;
+        } else {
+            push("removed", prefix + i, prefix + j, mid_a[i]);
+            i += 1;
+        }
+    }
+    for k in 0..suffix {
+        push("unchanged", a.len() - suffix + k, b.len() - suffix + k, a[a.len() - suffix + k]);
+    }
+    Ok(ranges)
+}
+
+/// 读取待办某个版本的详情：version 为 "current" 时为当前内容，否则为同名备份中的内容（备份中没有该待办时返回错误）
+fn read_todo_version(app: &tauri::AppHandle, data_path: &str, folder_name: &str, version: &str) -> Result<String, String> {
+    if version == "current" {
+        return get_todo_detail(data_path.to_string(), folder_name.to_string());
+    }
+    let folder = backup_dir(app, data_path, version)?.join(folder_name);
+    if !folder.is_dir() {
+        return Err(format!("备份 {} 中没有该待办", version));
+    }
+    Ok(fs::read_to_string(folder.join("content.json")).unwrap_or_default())
+}
+
+/// 逐行比较待办两个版本的详情（按纯文本，每个块一行）；版本为 "current"（当前内容）或备份名称。
+/// 返回按顺序排列的新增 / 删除 / 未变行段，供界面渲染差异
+#[tauri::command]
+fn diff_todo_versions(
+    app: tauri::AppHandle,
+    data_path: String,
+    folder_name: String,
+    version_a: String,
+    version_b: String,
+) -> Result<Vec<DiffRange>, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let text_a = content_to_plaintext(read_todo_version(&app, &data_path, &folder_name, &version_a)?);
+    let text_b = content_to_plaintext(read_todo_version(&app, &data_path, &folder_name, &version_b)?);
+    let lines_a: Vec<&str> = text_a.lines().collect();
+    let lines_b: Vec<&str> = text_b.lines().collect();
+    diff_lines(&lines_a, &lines_b)
+}
+
+#[derive(Serialize, Debug, Default)]
+struct HistoryPurgeReport {
+    /// 含有该待办旧版本的备份数
+    backups_purged: usize,
+    bytes_reclaimed: u64,
+}
+
+/// 从所有备份中删除某个待办的旧版本（文件夹及其在备份 todos.json 中的条目），当前内容保持不变。
+/// 用于确保已从笔记中删去的敏感内容不再留存于备份
+#[tauri::command]
+fn purge_todo_history(app: tauri::AppHandle, data_path: String, folder_name: String) -> Result<HistoryPurgeReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let mut report = HistoryPurgeReport::default();
+    let Ok(entries) = fs::read_dir(backups_dir(&app, &data_path)) else { return Ok(report) };
+    let paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    // 压缩的备份无法原地修改，先解压（之后可再次 compress_old_backups）
+    for archive in paths.iter().filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "zip")) {
+        let dir = archive.with_extension("");
+        if !dir.exists() {
+            extract_backup(archive, &dir).map_err(|e| log_error("purge_todo_history", e))?;
+        }
+    }
+    let Ok(entries) = fs::read_dir(backups_dir(&app, &data_path)) else { return Ok(report) };
+    for backup in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        let mut purged = false;
+        let old_folder = backup.join(&folder_name);
+        if old_folder.is_dir() {
+            report.bytes_reclaimed += calculate_dir_size(&old_folder);
+            fs::remove_dir_all(&old_folder).map_err(|e| log_error("purge_todo_history", e))?;
+            purged = true;
+        }
+        let index_path = backup.join("todos.json");
+        if let Some(mut todos) = fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<Vec<TodoItem>>(strip_bom(&c)).ok())
+        {
+            let before = todos.len();
+            todos.retain(|t| t.folder_name != folder_name);
+            if todos.len() != before {
+                let content = serialize_index(&todos).map_err(|e| e.to_string())?;
+                let old_size = fs::metadata(&index_path).map(|m| m.len()).unwrap_or(0);
+                write_atomic(&index_path, content.as_bytes()).map_err(|e| log_error("purge_todo_history", e))?;
+                report.bytes_reclaimed += old_size.saturating_sub(content.len() as u64);
+                purged = true;
+            }
+        }
+        if purged {
+            report.backups_purged += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// 检查备份中的待办列表能否解析、引用的文件夹是否存在、各 content.json 是否为合法 JSON
+#[tauri::command]
+fn verify_backup(app: tauri::AppHandle, data_path: String, backup_name: String) -> Result<BackupReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let dir = backup_dir(&app, &data_path, &backup_name)?;
+    let mut issues = Vec::new();
+
+    let mut todos: Vec<TodoItem> = Vec::new();
+    let todos_path = dir.join("todos.json");
+    let has_db = dir.join(SQLITE_FILE).exists();
+    if todos_path.exists() {
+        match fs::read_to_string(&todos_path) {
+            Ok(content) => match serde_json::from_str(strip_bom(&content)) {
+                Ok(list) => todos = list,
+                Err(e) => issues.push(format!("todos.json 解析失败: {}", e)),
+            },
+            Err(e) => issues.push(format!("todos.json 读取失败: {}", e)),
+        }
+    }
+    if has_db {
+        match read_todos_sqlite(&dir) {
+            Ok(list) => {
+                if todos.is_empty() {
+                    todos = list;
+                }
+            }
+            Err(e) => issues.push(format!("{} 读取失败: {}", SQLITE_FILE, e)),
+        }
+    }
+    if !todos_path.exists() && !has_db {
+        issues.push("备份中缺少 todos.json".to_string());
+    }
+
+    for todo in &todos {
+        if !dir.join(&todo.folder_name).is_dir() {
+            issues.push(format!("缺少待办文件夹: {} ({})", todo.folder_name, todo.title));
+        }
+    }
+
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+        let detail_path = entry.path().join("content.json");
+        if !detail_path.is_file() {
+            continue;
+        }
+        let valid = fs::read_to_string(&detail_path)
+            .ok()
+            .map(|c| serde_json::from_str::<serde_json::Value>(strip_bom(&c)).is_ok())
+            .unwrap_or(false);
+        if !valid {
+            issues.push(format!(
+                "content.json 无效: {}",
+                entry.file_name().to_string_lossy()
+            ));
+        }
+    }
+
+    Ok(BackupReport {
+        backup_name,
+        ok: issues.is_empty(),
+        issues,
+    })
+}
+
+/// 用备份覆盖当前数据：先为当前数据再做一次备份，然后清空数据目录（保留备份目录、锁文件与排除项）
+/// 并复制备份内容，返回恢复前自动创建的备份名称
+#[tauri::command]
+fn restore_backup(app: tauri::AppHandle, data_path: String, backup_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let src = backup_dir(&app, &data_path, &backup_name)?;
+    let report = verify_backup(app.clone(), data_path.clone(), backup_name.clone())?;
+    if !report.ok {
+        return Err(format!("备份校验未通过: {}", report.issues.join("; ")));
+    }
+    let safety = backup_data(app.clone(), data_path.clone())?;
+
+    let data_dir = Path::new(&data_path);
+    let root = backups_dir(&app, &data_path);
+    let exclude = get_app_config(app.clone()).move_exclude;
+    for entry in fs::read_dir(data_dir).map_err(|e| log_error("restore_backup", e))?.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name == BACKUPS_DIR || name == LOCK_FILE || path == root || is_excluded(&name, &exclude) {
+            continue;
+        }
+        remove_path(&path).map_err(|e| log_error("restore_backup", e))?;
+    }
+    // 备份可能位于另一个文件系统，一律复制而非重命名
+    for entry in fs::read_dir(&src).map_err(|e| log_error("restore_backup", e))?.flatten() {
+        let path = entry.path();
+        let dest = data_dir.join(entry.file_name());
+        copy_path(&path, &dest).map_err(|e| log_error("restore_backup", e))?;
+    }
+    let _ = app.emit("todos-changed", ());
+    Ok(safety)
+}
+
+/// 读取备份中的待办列表（todos.json 或 todos.db）
+fn read_backup_todos(dir: &Path) -> Result<Vec<TodoItem>, String> {
+    let todos_path = dir.join("todos.json");
+    if todos_path.exists() {
+        let content = fs::read_to_string(&todos_path).map_err(|e| format!("todos.json 读取失败: {}", e))?;
+        return serde_json::from_str(strip_bom(&content)).map_err(|e| format!("todos.json 解析失败: {}", e));
+    }
+    if dir.join(SQLITE_FILE).exists() {
+        return read_todos_sqlite(dir).map_err(|e| format!("{} 读取失败: {}", SQLITE_FILE, e));
+    }
+    Err("备份中缺少 todos.json".to_string())
+}
+
+/// 只从备份中恢复单个待办：当前的同名文件夹先移入回收站，再复制备份中的文件夹（及其引用、当前缺失的共用资源），
+/// 并用备份中的索引项替换或追加当前索引项，其余数据不受影响。返回恢复后的待办
+#[tauri::command]
+fn restore_todo_from_backup(
+    app: tauri::AppHandle,
+    data_path: String,
+    backup_name: String,
+    folder_name: String,
+) -> Result<TodoItem, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let src = backup_dir(&app, &data_path, &backup_name)?;
+    let restored = read_backup_todos(&src)?
+        .into_iter()
+        .find(|t| t.folder_name == folder_name)
+        .ok_or_else(|| format!("备份 {} 中不存在该待办: {}", backup_name, folder_name))?;
+    let data_dir = Path::new(&data_path);
+    let src_folder = src.join(&folder_name);
+    if src_folder.is_dir() {
+        if data_dir.join(&folder_name).exists() {
+            delete_todo_folder_to_os_trash(data_path.clone(), folder_name.clone())?;
+        }
+        copy_dir_all(&src_folder, data_dir.join(&folder_name)).map_err(|e| log_error("restore_todo_from_backup", e))?;
+        for name in referenced_shared_assets(&src, [folder_name.as_str()]) {
+            let target = data_dir.join(SHARED_ASSETS_DIR).join(&name);
+            let source = src.join(SHARED_ASSETS_DIR).join(&name);
+            if !target.exists() && source.is_file() {
+                fs::create_dir_all(data_dir.join(SHARED_ASSETS_DIR)).map_err(|e| log_error("restore_todo_from_backup", e))?;
+                fs::copy(&source, &target).map_err(|e| log_error("restore_todo_from_backup", e))?;
+            }
+        }
+    }
+    let mut todos = load_todos(&data_path);
+    match todos.iter_mut().find(|t| t.folder_name == folder_name || t.id == restored.id) {
+        Some(todo) => *todo = restored.clone(),
+        None => todos.push(restored.clone()),
+    }
+    save_todos(app.clone(), data_path, todos)?;
+    #[derive(Clone, Serialize)]
+    struct Payload { folder_name: String }
+    let _ = app.emit("todo-detail-changed", Payload { folder_name });
+    Ok(restored)
+}
+
+/// 整段去除（连同内容）的标签
+const STRIPPED_HTML_ELEMENTS: &[&str] = &["script", "style", "iframe", "object", "embed"];
+/// 直接去除的单标签
+const STRIPPED_HTML_TAGS: &[&str] = &["link", "meta", "base"];
+
+/// 标签中是否含有事件处理属性（onclick= 等）或脚本 / 外部资源引用
+fn is_unsafe_html_tag(name: &str, tag: &str) -> bool {
+    let bytes = tag.as_bytes();
+    let has_handler = (1..bytes.len()).any(|i| {
+        let boundary = matches!(bytes[i - 1], b' ' | b'\t' | b'\n' | b'\r' | b'/' | b'"' | b'\'');
+        boundary && tag[i..].starts_with("on") && {
+            let rest = tag[i + 2..].trim_start_matches(|c: char| c.is_ascii_alphabetic());
+            rest.len() < tag.len() - i - 2 && rest.trim_start().starts_with('=')
+        }
+    });
+    let loads_resource = name != "a" && ["://", "=//", "=\"//", "='//"].iter().any(|p| tag.contains(p));
+    has_handler || loads_resource || ["javascript:", "vbscript:", "url("].iter().any(|p| tag.contains(p))
+}
+
+/// 去除文本中的 script/style 等元素、带事件处理属性或外部资源引用的标签；无改动时返回 None
+fn strip_unsafe_html(text: &str) -> Option<String> {
+    let lower = text.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut i = 0;
+    while let Some(rel) = lower[i..].find('<') {
+        let start = i + rel;
+        out.push_str(&text[i..start]);
+        let Some(end) = lower[start..].find('>').map(|e| start + e + 1) else {
+            i = start;
+            break;
+        };
+        let tag = lower[start + 1..end - 1].trim();
+        let name: String = tag.chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+        i = end;
+        if STRIPPED_HTML_ELEMENTS.contains(&name.as_str()) {
+            let close = format!("</{}", name);
+            i = lower[end..]
+                .find(&close)
+                .and_then(|c| lower[end + c..].find('>').map(|e| end + c + e + 1))
+                .unwrap_or(text.len());
+            changed = true;
+        } else if STRIPPED_HTML_TAGS.contains(&name.as_str()) || (!name.is_empty() && is_unsafe_html_tag(&name, tag)) {
+            changed = true;
+        } else {
+            out.push_str(&text[start..end]);
+        }
+    }
+    out.push_str(&text[i..]);
+    changed.then_some(out)
+}
+
+/// 清理导入的详情节点：文本中的危险 HTML、指向外部或脚本的资源 url、含样式注入的颜色值。返回是否有内容被去除
+fn sanitize_detail_nodes(nodes: &mut [serde_json::Value]) -> bool {
+    let mut stripped = false;
+    for node in nodes.iter_mut() {
+        if let Some(cleaned) = node.get("value").and_then(|v| v.as_str()).and_then(strip_unsafe_html) {
+            node["value"] = serde_json::Value::String(cleaned);
+            stripped = true;
+        }
+        if let Some(url) = node.get("url").and_then(|u| u.as_str()) {
+            let lower = url.trim().to_ascii_lowercase();
+            let local = lower.starts_with("asset:")
+                || lower.starts_with("http://asset.localhost/")
+                || lower.starts_with("https://asset.localhost/");
+            if !local {
+                node.as_object_mut().map(|o| o.remove("url"));
+                stripped = true;
+            }
+        }
+        if let Some(color) = node.get("color").and_then(|c| c.as_str()) {
+            if color.contains([';', '(', ')', '{', '}', '<', '>', '"', '\'']) {
+                node.as_object_mut().map(|o| o.remove("color"));
+                stripped = true;
+            }
+        }
+        if let Some(children) = node.get_mut("children").and_then(|c| c.as_array_mut()) {
+            stripped |= sanitize_detail_nodes(children);
+        }
+    }
+    stripped
+}
+
+#[derive(Serialize, Debug)]
+struct ConvertedFile {
+    path: String,
+    /// 检测到的原始编码
+    encoding: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct TextImportReport {
+    imported: usize,
+    skipped: usize,
+    /// 非 UTF-8、已转换编码的文件，建议导入后核对
+    converted: Vec<ConvertedFile>,
+    /// 是否有文件中的危险 HTML 被去除
+    sanitized: bool,
+}
+
+/// 解码文本文件：优先按 UTF-8（去除 BOM），其次按 UTF-16 BOM，最后自动检测常见编码；
+/// 返回文本与非 UTF-8 时的原编码名
+fn decode_text(bytes: &[u8]) -> Option<(String, Option<&'static str>)> {
+    if let Some((encoding, bom_len)) = encoding_rs::Encoding::for_bom(bytes) {
+        let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        if had_errors {
+            return None;
+        }
+        let label = (encoding != encoding_rs::UTF_8).then(|| encoding.name());
+        return Some((text.into_owned(), label));
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some((text.to_string(), None));
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, true);
+    let (text, had_errors) = encoding.decode_without_bom_handling(bytes);
+    if had_errors || text.contains('\0') {
+        return None;
+    }
+    Some((text.into_owned(), Some(encoding.name())))
+}
+
+/// 尝试还原 UTF-8 被误当作 Windows-1252 / Latin-1 解码后的乱码（如 "Ã©" -> "é"），
+/// 文本不能逆转换或逆转换后不是有效 UTF-8 时返回 None
+fn demojibake(text: &str) -> Option<String> {
+    if text.is_ascii() {
+        return None;
+    }
+    let (bytes, _, unmappable) = encoding_rs::WINDOWS_1252.encode(text);
+    if unmappable {
+        return None;
+    }
+    String::from_utf8(bytes.into_owned()).ok().filter(|fixed| fixed != text)
+}
+
+/// 还原结果的可信度：还原出的非 ASCII 字符越多、原文中典型的乱码字符越多越可信
+fn mojibake_confidence(original: &str, fixed: &str) -> f64 {
+    let recovered = fixed.chars().filter(|c| !c.is_ascii()).count();
+    let mut confidence = if recovered >= 2 { 0.8 } else { 0.5 };
+    if original.contains(['Ã', 'Â']) || original.contains("â€") {
+        confidence += 0.15;
+    }
+    confidence
+}
+
+/// 找出节点树中疑似乱码的文本，apply 为 true 时直接替换为还原结果；返回 (原文, 还原结果, 可信度)
+fn fix_mojibake_nodes(nodes: &mut [serde_json::Value], apply: bool, out: &mut Vec<(String, String, f64)>) {
+    for node in nodes.iter_mut() {
+        if let Some(value) = node.get("value").and_then(|v| v.as_str()) {
+            if let Some(fixed) = demojibake(value) {
+                out.push((value.to_string(), fixed.clone(), mojibake_confidence(value, &fixed)));
+                if apply {
+                    node["value"] = serde_json::Value::String(fixed);
+                }
+            }
+        }
+        if let Some(children) = node.get_mut("children").and_then(|c| c.as_array_mut()) {
+            fix_mojibake_nodes(children, apply, out);
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct MojibakeItem {
+    folder_name: String,
+    /// "title" 或 "content"
+    field: String,
+    /// 受影响的文本（详情中为第一处）及还原后的结果
+    original: String,
+    repaired: String,
+    /// 详情中疑似乱码的文本段数，标题为 1
+    occurrences: usize,
+    /// 0-1，详情中取各段的最低值
+    confidence: f64,
+}
+
+/// 启发式地找出标题与详情中疑似 UTF-8 被按 Latin-1 重复编码造成的乱码，并给出还原结果与可信度
+#[tauri::command]
+fn find_mojibake(data_path: String) -> Vec<MojibakeItem> {
+    let data_path = expand_data_path(&data_path);
+    let mut items = Vec::new();
+    for todo in load_todos(&data_path) {
+        if let Some(fixed) = demojibake(&todo.title) {
+            items.push(MojibakeItem {
+                folder_name: todo.folder_name.clone(),
+                field: "title".to_string(),
+                confidence: mojibake_confidence(&todo.title, &fixed),
+                original: todo.title.clone(),
+                repaired: fixed,
+                occurrences: 1,
+            });
+        }
+        let content = get_todo_detail(data_path.clone(), todo.folder_name.clone()).unwrap_or_default();
+        let mut found = Vec::new();
+        fix_mojibake_nodes(&mut parse_detail_nodes(&content), false, &mut found);
+        if let Some((original, repaired, _)) = found.first().cloned() {
+            items.push(MojibakeItem {
+                folder_name: todo.folder_name,
+                field: "content".to_string(),
+                original,
+                repaired,
+                occurrences: found.len(),
+                confidence: found.iter().map(|(_, _, c)| *c).fold(1.0, f64::min),
+            });
+        }
+    }
+    items
+}
+
+#[derive(Serialize, Debug, Default)]
+struct MojibakeRepairReport {
+    /// 修复前创建的完整备份名
+    backup: String,
+    titles: usize,
+    /// 修复了详情的待办数
+    contents: usize,
+}
+
+/// 对所列待办的标题与详情执行乱码还原；修复前先创建完整备份（名称以 -mojibake 结尾），可从备份恢复原文
+#[tauri::command]
+fn repair_mojibake(app: tauri::AppHandle, data_path: String, folder_names: Vec<String>) -> Result<MojibakeRepairReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let mut report = MojibakeRepairReport {
+        backup: create_backup(&app, &data_path, "-mojibake")?,
+        ..Default::default()
+    };
+    let mut todos = load_todos(&data_path);
+    for todo in todos.iter_mut().filter(|t| folder_names.contains(&t.folder_name)) {
+        if let Some(fixed) = demojibake(&todo.title) {
+            todo.title = fixed;
+            report.titles += 1;
+        }
+        let content = get_todo_detail(data_path.clone(), todo.folder_name.clone())?;
+        let mut nodes = parse_detail_nodes(&content);
+        let mut fixed = Vec::new();
+        fix_mojibake_nodes(&mut nodes, true, &mut fixed);
+        if !fixed.is_empty() {
+            let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+            save_todo_detail(app.clone(), data_path.clone(), todo.folder_name.clone(), content)?;
+            report.contents += 1;
+        }
+    }
+    if report.titles > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(report)
+}
+
+/// 将纯文本转为编辑器节点：Markdown 的一二级标题转为 h1/h2，其余每行一个段落
+fn text_to_detail_nodes(text: &str) -> Vec<serde_json::Value> {
+    text.lines()
+        .map(|line| {
+            let (kind, body) = if let Some(rest) = line.strip_prefix("## ") {
+                ("h2", rest)
+            } else if let Some(rest) = line.strip_prefix("# ") {
+                ("h1", rest)
+            } else {
+                ("p", line)
+            };
+            let children = if body.is_empty() {
+                serde_json::json!([])
+            } else {
+                serde_json::json!([{ "type": "text", "value": body }])
+            };
+            serde_json::json!({ "type": kind, "id": Uuid::new_v4().to_string(), "children": children })
+        })
+        .collect()
+}
+
+fn collect_text_files(root: &Path, dir: &Path, out: &mut Vec<(PathBuf, Option<String>)>, skipped: &mut usize) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_text_files(root, &path, out, skipped);
+            continue;
+        }
+        let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+        if !matches!(ext.as_deref(), Some("txt" | "md" | "markdown")) {
+            *skipped += 1;
+            continue;
+        }
+        // 子文件夹的相对路径作为标签
+        let tag = path
+            .parent()
+            .and_then(|p| p.strip_prefix(root).ok())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .filter(|t| !t.is_empty());
+        out.push((path, tag));
+    }
+}
+
+/// 将目录（递归）中的 .txt/.md 文件逐个导入为待办：文件名为标题，内容写入 content.json，子文件夹名作为标签
+#[tauri::command]
+fn import_text_folder(app: tauri::AppHandle, data_path: String, src_dir: String) -> Result<TextImportReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let src = PathBuf::from(expand_data_path(&src_dir));
+    if !src.is_dir() {
+        return Err(format!("目录不存在: {}", src.display()));
+    }
+    let mut report = TextImportReport::default();
+    let mut files = Vec::new();
+    collect_text_files(&src, &src, &mut files, &mut report.skipped);
+
+    let status = get_app_config(app.clone()).default_status;
+    let mut todos = load_todos(&data_path);
+    for (path, tag) in files {
+        let Some((text, encoding)) = fs::read(&path).ok().and_then(|b| decode_text(&b)) else {
+            report.skipped += 1;
+            continue;
+        };
+        if let Some(encoding) = encoding {
+            report.converted.push(ConvertedFile {
+                path: path.to_string_lossy().into_owned(),
+                encoding: encoding.to_string(),
+            });
+        }
+        let title = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let folder_name = create_todo_folder(data_path.clone())?;
+        let mut nodes = text_to_detail_nodes(&text);
+        report.sanitized |= sanitize_detail_nodes(&mut nodes);
+        let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+        write_atomic(&Path::new(&data_path).join(&folder_name).join("content.json"), content.as_bytes())
+            .map_err(|e| log_error("import_text_folder", e))?;
+        todos.push(TodoItem {
+            id: Uuid::new_v4().to_string(),
+            title,
+            status: status.clone(),
+            folder_name,
+            tags: tag.into_iter().collect(),
+            ..Default::default()
+        });
+        report.imported += 1;
+    }
+    if report.imported > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(report)
+}
+
+#[derive(Debug, Default)]
+struct FrontMatter {
+    status: Option<String>,
+    due: Option<String>,
+    tags: Vec<String>,
+}
+
+/// 拆出 Markdown 开头 --- 包围的 front matter，只识别 status、due、tags（[a, b]、a, b 或逐行 - a）
+fn split_front_matter(text: &str) -> (FrontMatter, &str) {
+    let mut meta = FrontMatter::default();
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return (meta, text);
+    };
+    let Some(end) = rest.find("\n---") else { return (meta, text) };
+    let body = rest[end + 4..].trim_start_matches(['\r', '\n']);
+    let mut in_tags = false;
+    for line in rest[..end].lines() {
+        let trimmed = line.trim();
+        if in_tags {
+            if let Some(tag) = trimmed.strip_prefix("- ") {
+                meta.tags.push(tag.trim().trim_matches(['"', '\'']).to_string());
+                continue;
+            }
+            in_tags = false;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else { continue };
+        let value = value.trim().trim_matches(['"', '\'']);
+        match key.trim() {
+            "status" if !value.is_empty() => meta.status = Some(value.to_string()),
+            "due" | "due_date" if !value.is_empty() => meta.due = Some(value.to_string()),
+            "tags" if value.is_empty() => in_tags = true,
+            "tags" => meta.tags.extend(
+                value
+                    .trim_matches(['[', ']'])
+                    .split(',')
+                    .map(|t| t.trim().trim_matches(['"', '\'']).to_string())
+                    .filter(|t| !t.is_empty()),
+            ),
+            _ => {}
+        }
+    }
+    meta.tags.retain(|t| !t.is_empty());
+    (meta, body)
+}
+
+#[derive(Serialize, Debug, Default)]
+struct MarkdownImportReport {
+    imported: usize,
+    skipped: usize,
+    /// 无法识别的 front matter 字段值等，导入仍继续
+    warnings: Vec<String>,
+}
+
+/// 递归导入目录中的 .md 文件：第一个标题行作为标题（没有时用文件名），其余内容写入详情，
+/// 相对目录路径作为以 / 分隔的层级标签；front matter 中的 status、due、tags 会被保留
+#[tauri::command]
+fn import_markdown_tree(app: tauri::AppHandle, data_path: String, src_dir: String) -> Result<MarkdownImportReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let src = PathBuf::from(expand_data_path(&src_dir));
+    if !src.is_dir() {
+        return Err(format!("目录不存在: {}", src.display()));
+    }
+    let mut report = MarkdownImportReport::default();
+    let mut files = Vec::new();
+    collect_text_files(&src, &src, &mut files, &mut report.skipped);
+    let default_status = get_app_config(app.clone()).default_status;
+    let mut todos = load_todos(&data_path);
+    for (path, tag) in files {
+        let rel = path.strip_prefix(&src).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        let is_markdown = path
+            .extension()
+            .is_some_and(|e| matches!(e.to_string_lossy().to_lowercase().as_str(), "md" | "markdown"));
+        let Some((text, _)) = fs::read(&path).ok().and_then(|b| decode_text(&b)).filter(|_| is_markdown) else {
+            report.skipped += 1;
+            continue;
+        };
+        let (meta, body) = split_front_matter(&text);
+        let mut lines: Vec<&str> = body.lines().collect();
+        let heading = lines.iter().position(|l| {
+            let l = l.trim_start();
+            let hashes = l.chars().take_while(|c| *c == '#').count();
+            (1..=6).contains(&hashes) && l[hashes..].starts_with(' ')
+        });
+        let title = match heading {
+            Some(i) => lines.remove(i).trim_start().trim_start_matches('#').trim().to_string(),
+            None => String::new(),
+        };
+        let title = if title.is_empty() {
+            path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+        } else {
+            title
+        };
+        // 其他笔记工具常用的 done / todo 对应到已完成 / 待处理
+        let status = match meta.status.map(|s| match s.to_lowercase().as_str() {
+            "done" => STATUS_COMPLETED.to_string(),
+            "todo" => STATUS_PENDING.to_string(),
+            _ => s,
+        }) {
+            Some(s) if KNOWN_STATUSES.contains(&s.as_str()) => s,
+            Some(s) => {
+                report.warnings.push(format!("{}: 未知的状态 {}，已改用 {}", rel, s, default_status));
+                default_status.clone()
+            }
+            None => default_status.clone(),
+        };
+        let due_date = match meta.due {
+            Some(d) if parse_due_date(&d).is_some() => Some(d),
+            Some(d) => {
+                report.warnings.push(format!("{}: 无法识别的截止日期 {}", rel, d));
+                None
+            }
+            None => None,
+        };
+        let mut tags: Vec<String> = Vec::new();
+        for t in tag.into_iter().chain(meta.tags) {
+            if !tags.contains(&t) {
+                tags.push(t);
+            }
+        }
+        let folder_name = create_todo_folder(data_path.clone())?;
+        let mut nodes = text_to_detail_nodes(lines.join("\n").trim());
+        if sanitize_detail_nodes(&mut nodes) {
+            report.warnings.push(format!("{}: 已去除不安全的 HTML", rel));
+        }
+        let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+        write_atomic(&Path::new(&data_path).join(&folder_name).join("content.json"), content.as_bytes())
+            .map_err(|e| log_error("import_markdown_tree", e))?;
+        todos.push(TodoItem {
+            id: Uuid::new_v4().to_string(),
+            title,
+            status,
+            folder_name,
+            due_date,
+            tags,
+            ..Default::default()
+        });
+        report.imported += 1;
+    }
+    if report.imported > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(report)
+}
+
+/// 还原 HTML 中常见的字符实体
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 解析 Netscape 格式的书签 HTML，返回（标题, URL, 所在文件夹路径）；文件夹路径以 "/" 连接
+fn parse_bookmarks(html: &str) -> Vec<(String, String, String)> {
+    let mut bookmarks = Vec::new();
+    let mut folders: Vec<String> = Vec::new();
+    let mut pending_folder: Option<String> = None;
+    for segment in html.split('<').skip(1) {
+        let (tag, text) = segment.split_once('>').unwrap_or((segment, ""));
+        let lower = tag.to_ascii_lowercase();
+        let text = unescape_html(text.trim());
+        if lower.starts_with("h3") {
+            pending_folder = Some(text);
+        } else if lower.starts_with("dl") {
+            folders.push(pending_folder.take().unwrap_or_default());
+        } else if lower.starts_with("/dl") {
+            folders.pop();
+        } else if lower.starts_with("a ") {
+            let Some(start) = lower.find("href=\"").map(|i| i + 6) else { continue };
+            let Some(len) = tag[start..].find('"') else { continue };
+            let url = unescape_html(&tag[start..start + len]);
+            let folder: Vec<&str> = folders.iter().map(String::as_str).filter(|f| !f.is_empty()).collect();
+            let title = if text.is_empty() { url.clone() } else { text };
+            bookmarks.push((title, url, folder.join("/")));
+        }
+    }
+    bookmarks
+}
+
+/// 导入浏览器导出的书签 HTML：每个书签一个待办，详情为书签 URL，书签所在文件夹作为标签；
+/// 已有待办详情中出现过的 URL 跳过。返回导入的条数
+#[tauri::command]
+fn import_bookmarks(app: tauri::AppHandle, data_path: String, src_html: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let html = fs::read(expand_data_path(&src_html))
+        .map_err(|e| log_error("import_bookmarks", e))
+        .and_then(|bytes| decode_text(&bytes).map(|(text, _)| text).ok_or_else(|| "无法识别书签文件的编码".to_string()))?;
+    let mut todos = load_todos(&data_path);
+    let mut known_urls = std::collections::HashSet::new();
+    for todo in &todos {
+        let content = fs::read_to_string(Path::new(&data_path).join(&todo.folder_name).join("content.json")).unwrap_or_default();
+        let mut lines = Vec::new();
+        collect_block_text(&parse_detail_nodes(&content), &mut lines);
+        known_urls.extend(lines.into_iter().map(|l| l.trim().to_string()));
+    }
+    let status = get_app_config(app.clone()).default_status;
+    let mut imported = 0;
+    for (title, url, folder) in parse_bookmarks(&html) {
+        if url.is_empty() || !known_urls.insert(url.clone()) {
+            continue;
+        }
+        let folder_name = create_todo_folder(data_path.clone())?;
+        let content = serde_json::to_string(&text_to_detail_nodes(&url)).map_err(|e| e.to_string())?;
+        write_atomic(&Path::new(&data_path).join(&folder_name).join("content.json"), content.as_bytes())
+            .map_err(|e| log_error("import_bookmarks", e))?;
+        todos.push(TodoItem {
+            id: Uuid::new_v4().to_string(),
+            title,
+            status: status.clone(),
+            folder_name,
+            tags: Some(folder).filter(|f| !f.is_empty()).into_iter().collect(),
+            ..Default::default()
+        });
+        imported += 1;
+    }
+    if imported > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(imported)
+}
+
+/// 与前端 encodeURIComponent 一致的编码，资源 url 中的路径以此形式出现
+fn encode_uri_component(value: &str) -> String {
+    let mut out = String::new();
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct ExportManifest {
+    /// 导出时的数据目录，导入时用于改写资源 url
+    source_data_path: String,
+    exported_at: String,
+}
+
+/// 按压缩级别（0-9）生成 zip 选项，0 表示仅存储不压缩
+fn zip_options(level: u8) -> zip::write::SimpleFileOptions {
+    let options = zip::write::SimpleFileOptions::default();
+    if level == 0 {
+        options.compression_method(zip::CompressionMethod::Stored)
+    } else {
+        options
+            .compression_method(zip::CompressionMethod::Deflated)
+            .compression_level(Some(level as i64))
+    }
+}
+
+fn zip_add_dir<W: Write + std::io::Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    root: &Path,
+    dir: &Path,
+    options: zip::write::SimpleFileOptions,
+) -> Result<(), String> {
+    let mut files = Vec::new();
+    collect_relative_files(root, dir, &mut files);
+    for rel in files {
+        let bytes = fs::read(root.join(&rel)).map_err(|e| e.to_string())?;
+        zip.start_file(rel, options).map_err(|e| e.to_string())?;
+        zip.write_all(&bytes).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Debug)]
+struct ExportReport {
+    todo_count: usize,
+    /// 生成的压缩包大小（字节）
+    archive_bytes: u64,
+}
+
+/// 将 todos 及其文件夹写入 dest_zip（含 export.json 与只含这些条目的 todos.json），可由 import_workspace 导入
+fn write_export_zip(data_path: &str, todos: &[TodoItem], dest_zip: &str, level: u8) -> Result<ExportReport, String> {
+    let dest = PathBuf::from(expand_data_path(dest_zip));
+    let file = fs::File::create(&dest).map_err(|e| e.to_string())?;
+    write_export_archive(file, data_path, todos, level)?;
+    Ok(ExportReport {
+        todo_count: todos.len(),
+        archive_bytes: fs::metadata(&dest).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+/// 将导出内容写入任意可定位的输出（文件或内存），返回写完的输出
+fn write_export_archive<W: Write + std::io::Seek>(writer: W, data_path: &str, todos: &[TodoItem], level: u8) -> Result<W, String> {
+    let data_dir = Path::new(data_path);
+    let mut zip = zip::ZipWriter::new(writer);
+    let options = zip_options(level);
+    let manifest = ExportManifest {
+        source_data_path: data_path.to_string(),
+        exported_at: utc_now_rfc3339(),
+    };
+    let entries = [
+        ("export.json", serde_json::to_vec(&manifest).map_err(|e| e.to_string())?),
+        ("todos.json", serde_json::to_vec(todos).map_err(|e| e.to_string())?),
+    ];
+    for (name, bytes) in entries {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&bytes).map_err(|e| e.to_string())?;
+    }
+    for todo in todos {
+        let folder_path = data_dir.join(&todo.folder_name);
+        if folder_path.is_dir() {
+            zip_add_dir(&mut zip, data_dir, &folder_path, options)?;
+        }
+    }
+    // 共用资源池中被这些待办引用的文件一并打包，导入时还原
+    for name in referenced_shared_assets(data_dir, todos.iter().map(|t| t.folder_name.as_str())) {
+        let path = data_dir.join(SHARED_ASSETS_DIR).join(&name);
+        if path.is_file() {
+            let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+            zip.start_file(format!("{}/{}", SHARED_ASSETS_DIR, name), options).map_err(|e| e.to_string())?;
+            zip.write_all(&bytes).map_err(|e| e.to_string())?;
+        }
+    }
+    zip.finish().map_err(|e| e.to_string())
+}
+
+/// 将选中的待办（content.json 与资源）及只含这些条目的 todos.json 打包为 zip
+#[tauri::command]
+fn export_selected(app: tauri::AppHandle, data_path: String, folder_names: Vec<String>, dest_zip: String) -> Result<ExportReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let todos: Vec<TodoItem> = load_todos(&data_path)
+        .into_iter()
+        .filter(|t| folder_names.contains(&t.folder_name))
+        .collect();
+    if let Some(missing) = folder_names
+        .iter()
+        .find(|f| !todos.iter().any(|t| t.folder_name == **f))
+    {
+        return Err(format!("待办不存在: {}", missing));
+    }
+    let level = get_app_config(app).export_compression;
+    write_export_zip(&data_path, &todos, &dest_zip, level).map_err(|e| log_error("export_selected", e))
+}
+
+/// 将整个工作区（全部待办）打包为 zip
+#[tauri::command]
+fn export_workspace(app: tauri::AppHandle, data_path: String, dest_zip: String) -> Result<ExportReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let level = get_app_config(app).export_compression;
+    write_export_zip(&data_path, &load_todos(&data_path), &dest_zip, level)
+        .map_err(|e| log_error("export_workspace", e))
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct ExportProgress {
+    files_done: usize,
+    files_total: usize,
+    bytes_done: u64,
+}
+
+/// 流式导出整个工作区：遍历时逐个文件以流的方式写入 zip，不把文件整体读入内存，
+/// 内存占用与工作区大小无关；导出过程中发送 export-progress 事件
+#[tauri::command]
+fn export_workspace_streaming(app: tauri::AppHandle, data_path: String, dest_path: String) -> Result<ExportReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let level = get_app_config(app.clone()).export_compression;
+    let data_dir = Path::new(&data_path);
+    let todos = load_todos(&data_path);
+    let mut files = Vec::new();
+    for todo in &todos {
+        let folder_path = data_dir.join(&todo.folder_name);
+        if folder_path.is_dir() {
+            collect_relative_files(data_dir, &folder_path, &mut files);
+        }
+    }
+    files.extend(
+        referenced_shared_assets(data_dir, todos.iter().map(|t| t.folder_name.as_str()))
+            .into_iter()
+            .filter(|name| data_dir.join(SHARED_ASSETS_DIR).join(name).is_file())
+            .map(|name| format!("{}/{}", SHARED_ASSETS_DIR, name)),
+    );
+    let dest = PathBuf::from(expand_data_path(&dest_path));
+    let file = fs::File::create(&dest).map_err(|e| log_error("export_workspace_streaming", e))?;
+    let mut zip = zip::ZipWriter::new(std::io::BufWriter::new(file));
+    let options = zip_options(level);
+    let manifest = ExportManifest {
+        source_data_path: data_path.clone(),
+        exported_at: utc_now_rfc3339(),
+    };
+    zip.start_file("export.json", options).map_err(|e| e.to_string())?;
+    serde_json::to_writer(&mut zip, &manifest).map_err(|e| e.to_string())?;
+    zip.start_file("todos.json", options).map_err(|e| e.to_string())?;
+    serde_json::to_writer(&mut zip, &todos).map_err(|e| e.to_string())?;
+    let mut progress = ExportProgress { files_done: 0, files_total: files.len(), bytes_done: 0 };
+    let _ = app.emit("export-progress", progress.clone());
+    for rel in files {
+        let mut src = fs::File::open(data_dir.join(&rel)).map_err(|e| log_error("export_workspace_streaming", e))?;
+        zip.start_file(rel, options).map_err(|e| e.to_string())?;
+        progress.bytes_done += std::io::copy(&mut src, &mut zip).map_err(|e| log_error("export_workspace_streaming", e))?;
+        progress.files_done += 1;
+        let _ = app.emit("export-progress", progress.clone());
+    }
+    zip.finish()
+        .and_then(|mut w| w.flush().map_err(Into::into))
+        .map_err(|e| log_error("export_workspace_streaming", e))?;
+    Ok(ExportReport {
+        todo_count: todos.len(),
+        archive_bytes: fs::metadata(&dest).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+/// 将单个待办打包为 zip
+#[tauri::command]
+fn export_todo_zip(app: tauri::AppHandle, data_path: String, folder_name: String, dest_zip: String) -> Result<ExportReport, String> {
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    export_selected(app, data_path, vec![folder_name], dest_zip)
+}
+
+/// 加密归档的文件头标识
+const ENCRYPTED_ARCHIVE_MAGIC: &[u8; 8] = b"STODOAE1";
+/// 文件头长度：标识 | Argon2id 的 m_cost、t_cost、p_cost | salt | 口令校验用 nonce 与标签 | 正文 nonce
+const ENCRYPTED_HEADER_LEN: usize = 8 + 12 + 16 + 24 + 16 + 24;
+/// 导入时接受的 Argon2id 参数上限，防止构造的文件头让密钥派生耗尽内存或长时间卡住
+const MAX_ARGON2_M_COST: u32 = 256 * 1024;
+const MAX_ARGON2_T_COST: u32 = 16;
+const MAX_ARGON2_P_COST: u32 = 8;
+
+/// 用 Argon2id 由口令派生 XChaCha20-Poly1305 的密钥
+fn derive_archive_key(passphrase: &str, salt: &[u8], params: argon2::Params) -> Result<chacha20poly1305::Key, String> {
+    let mut key = chacha20poly1305::Key::default();
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+/// 用口令加密 plain，返回 文件头 | 密文（XChaCha20-Poly1305，文件头作为附加数据一并认证）。
+/// 文件头中另存一个空消息的认证标签，解密时据此区分口令错误与文件损坏
+fn encrypt_archive(mut plain: Vec<u8>, passphrase: &str) -> Result<Vec<u8>, String> {
+    use chacha20poly1305::aead::{rand_core::RngCore, AeadCore, AeadInPlace, KeyInit, OsRng};
+    use chacha20poly1305::XChaCha20Poly1305;
+    let params = argon2::Params::default();
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = XChaCha20Poly1305::new(&derive_archive_key(passphrase, &salt, params.clone())?);
+    let check_nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let check_tag = cipher
+        .encrypt_in_place_detached(&check_nonce, ENCRYPTED_ARCHIVE_MAGIC, &mut [])
+        .map_err(|e| e.to_string())?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let mut header = Vec::with_capacity(ENCRYPTED_HEADER_LEN);
+    header.extend_from_slice(ENCRYPTED_ARCHIVE_MAGIC);
+    for cost in [params.m_cost(), params.t_cost(), params.p_cost()] {
+        header.extend_from_slice(&cost.to_le_bytes());
+    }
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&check_nonce);
+    header.extend_from_slice(&check_tag);
+    header.extend_from_slice(&nonce);
+    cipher.encrypt_in_place(&nonce, &header, &mut plain).map_err(|e| e.to_string())?;
+    header.append(&mut plain);
+    Ok(header)
+}
+
+/// 校验并解密 encrypt_archive 的产物：口令不对与文件损坏分别返回不同的错误，认证通过后才返回明文
+fn decrypt_archive(mut data: Vec<u8>, passphrase: &str) -> Result<Vec<u8>, String> {
+    use chacha20poly1305::aead::{AeadInPlace, KeyInit};
+    use chacha20poly1305::{Tag, XChaCha20Poly1305, XNonce};
+    if data.len() < ENCRYPTED_HEADER_LEN + 16 || &data[..8] != ENCRYPTED_ARCHIVE_MAGIC {
+        return Err("不是加密的工作区归档".to_string());
+    }
+    let mut body = data.split_off(ENCRYPTED_HEADER_LEN);
+    let header = data;
+    let cost = |i: usize| u32::from_le_bytes(header[8 + i * 4..12 + i * 4].try_into().unwrap_or_default());
+    let (m_cost, t_cost, p_cost) = (cost(0), cost(1), cost(2));
+    if m_cost > MAX_ARGON2_M_COST || t_cost > MAX_ARGON2_T_COST || p_cost > MAX_ARGON2_P_COST {
+        return Err("归档的密钥派生参数超出允许范围".to_string());
+    }
+    let params = argon2::Params::new(m_cost, t_cost, p_cost, None).map_err(|_| "归档的密钥派生参数无效".to_string())?;
+    let cipher = XChaCha20Poly1305::new(&derive_archive_key(passphrase, &header[20..36], params)?);
+    let check_nonce = XNonce::from_slice(&header[36..60]);
+    let check_tag = Tag::from_slice(&header[60..76]);
+    if cipher.decrypt_in_place_detached(check_nonce, ENCRYPTED_ARCHIVE_MAGIC, &mut [], check_tag).is_err() {
+        return Err("口令错误".to_string());
+    }
+    cipher
+        .decrypt_in_place(XNonce::from_slice(&header[76..]), &header, &mut body)
+        .map_err(|_| "归档已损坏或被修改".to_string())?;
+    Ok(body)
+}
+
+/// 将整个工作区打包后用口令加密写入 dest_path，可放在不受信任的网盘中，用 import_workspace_encrypted 恢复。
+/// 压缩包只在内存中生成并加密，明文不落盘
+#[tauri::command]
+fn export_workspace_encrypted(app: tauri::AppHandle, data_path: String, dest_path: String, passphrase: String) -> Result<ExportReport, String> {
+    if passphrase.is_empty() {
+        return Err("口令不能为空".to_string());
+    }
+    let data_path = expand_data_path(&data_path);
+    let level = get_app_config(app).export_compression;
+    let todos = load_todos(&data_path);
+    let dest = PathBuf::from(expand_data_path(&dest_path));
+    let result = write_export_archive(std::io::Cursor::new(Vec::new()), &data_path, &todos, level)
+        .and_then(|plain| encrypt_archive(plain.into_inner(), &passphrase))
+        .and_then(|encrypted| write_atomic(&dest, &encrypted).map_err(|e| e.to_string()));
+    result.map_err(|e| log_error("export_workspace_encrypted", e))?;
+    Ok(ExportReport {
+        todo_count: todos.len(),
+        archive_bytes: fs::metadata(&dest).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+/// 解密 export_workspace_encrypted 的产物并以合并方式导入（同 import_workspace）；口令错误时返回“口令错误”。
+/// 解密结果只保存在内存中
+#[tauri::command]
+fn import_workspace_encrypted(app: tauri::AppHandle, data_path: String, src_path: String, passphrase: String) -> Result<WorkspaceImportReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let encrypted = fs::read(expand_data_path(&src_path)).map_err(|e| log_error("import_workspace_encrypted", e))?;
+    let plain = decrypt_archive(encrypted, &passphrase)?;
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(plain)).map_err(|e| log_error("import_workspace_encrypted", e))?;
+    import_workspace_archive(app, data_path, &mut archive, Default::default())
+}
+
+#[derive(Serialize, Debug, Default)]
+struct WorkspaceImportReport {
+    imported: usize,
+    /// 是否有详情中的危险 HTML 或外部资源引用被去除
+    sanitized: bool,
+    /// 状态既不在 status_map 中、也不是已知状态，改用 default_status 的条数
+    status_fallbacks: usize,
+}
+
+/// 按 status_map 将导入来源的状态转换为本应用的状态；未映射的已知状态保持不变，
+/// 未知状态改用 default_status 并返回 false
+fn map_import_status(status: &str, status_map: &std::collections::HashMap<String, String>, default_status: &str) -> (String, bool) {
+    match status_map.get(status) {
+        Some(mapped) => (mapped.clone(), true),
+        None if KNOWN_STATUSES.contains(&status) => (status.to_string(), true),
+        None => (default_status.to_string(), false),
+    }
+}
+
+/// 以合并方式导入 zip（export_selected / export_workspace 的产物）：id 已存在的待办跳过，文件夹重名时换用新文件夹。
+/// 详情写入前会清理危险 HTML 与外部资源引用；待办状态按 status_map（来源状态 -> 本应用状态）转换
+#[tauri::command]
+fn import_workspace(
+    app: tauri::AppHandle,
+    data_path: String,
+    src_zip: String,
+    status_map: Option<std::collections::HashMap<String, String>>,
+) -> Result<WorkspaceImportReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let status_map = status_map.unwrap_or_default();
+    if let Some(unknown) = status_map.values().find(|s| !KNOWN_STATUSES.contains(&s.as_str())) {
+        return Err(format!("状态映射的目标为未知状态: {}", unknown));
+    }
+    let file = fs::File::open(expand_data_path(&src_zip)).map_err(|e| log_error("import_workspace", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| log_error("import_workspace", e))?;
+    import_workspace_archive(app, data_path, &mut archive, status_map)
+}
+
+/// import_workspace 的导入过程，zip 可来自文件或内存（加密归档解密后）
+fn import_workspace_archive<R: std::io::Read + std::io::Seek>(
+    app: tauri::AppHandle,
+    data_path: String,
+    archive: &mut zip::ZipArchive<R>,
+    status_map: std::collections::HashMap<String, String>,
+) -> Result<WorkspaceImportReport, String> {
+    let default_status = get_app_config(app.clone()).default_status;
+    let data_dir = Path::new(&data_path);
+
+    let read_entry = |archive: &mut zip::ZipArchive<R>, name: &str| -> Option<String> {
+        let mut entry = archive.by_name(name).ok()?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content).ok()?;
+        Some(content)
+    };
+    let manifest: ExportManifest = read_entry(archive, "export.json")
+        .and_then(|c| serde_json::from_str(strip_bom(&c)).ok())
+        .unwrap_or_default();
+    let imported: Vec<TodoItem> = read_entry(archive, "todos.json")
+        .ok_or("压缩包中缺少 todos.json")
+        .and_then(|c| serde_json::from_str(strip_bom(&c)).map_err(|_| "todos.json 解析失败"))?;
+
+    let mut todos = load_todos(&data_path);
+    let mut added = 0;
+    let mut sanitized = false;
+    let mut status_fallbacks = 0;
+    for mut todo in imported {
+        if todos.iter().any(|t| t.id == todo.id) {
+            continue;
+        }
+        let source_folder = todo.folder_name.clone();
+        if sanitize_folder_name(&source_folder).is_err() {
+            continue;
+        }
+        if data_dir.join(&source_folder).exists() || todos.iter().any(|t| t.folder_name == source_folder) {
+            todo.folder_name = Uuid::new_v4().to_string();
+        }
+        let dest_folder = data_dir.join(&todo.folder_name);
+        fs::create_dir_all(dest_folder.join(assets_dir_name())).map_err(|e| log_error("import_workspace", e))?;
+
+        let prefix = format!("{}/", source_folder);
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(|e| log_error("import_workspace", e))?;
+            let Some(rel) = entry.enclosed_name() else { continue };
+            let Ok(rel) = rel.strip_prefix(&source_folder) else { continue };
+            if !entry.name().starts_with(&prefix) || entry.is_dir() {
+                continue;
+            }
+            let target = dest_folder.join(rel);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| log_error("import_workspace", e))?;
+            }
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).map_err(|e| log_error("import_workspace", e))?;
+            write_atomic(&target, &bytes).map_err(|e| log_error("import_workspace", e))?;
+        }
+
+        // 资源 url 为绝对路径，需改写到当前数据目录与新文件夹
+        let detail_path = dest_folder.join("content.json");
+        if let Ok(content) = fs::read_to_string(&detail_path) {
+            let mut nodes = parse_detail_nodes(&content);
+            if !manifest.source_data_path.is_empty() {
+                retarget_data_path_urls(&mut nodes, &manifest.source_data_path, &data_path);
+            }
+            if todo.folder_name != source_folder {
+                retarget_folder_urls(&mut nodes, &source_folder, &todo.folder_name);
+            }
+            sanitized |= sanitize_detail_nodes(&mut nodes);
+            let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+            write_atomic(&detail_path, content.as_bytes()).map_err(|e| log_error("import_workspace", e))?;
+        }
+        let (status, mapped) = map_import_status(&todo.status, &status_map, &default_status);
+        if !mapped {
+            write_log(LogLevel::Info, "import_workspace", &format!("未映射的状态 {}，改用 {}", todo.status, status));
+            status_fallbacks += 1;
+        }
+        todo.status = status;
+        todos.push(todo);
+        added += 1;
+    }
+    // 还原导入的待办引用、而本地资源池中还没有的共用资源（按内容哈希命名，已存在的即为相同文件）
+    let imported_folders: Vec<&str> = todos[todos.len() - added..].iter().map(|t| t.folder_name.as_str()).collect();
+    for name in referenced_shared_assets(data_dir, imported_folders) {
+        let target = data_dir.join(SHARED_ASSETS_DIR).join(&name);
+        if target.exists() {
+            continue;
         }
+        let Ok(mut entry) = archive.by_name(&format!("{}/{}", SHARED_ASSETS_DIR, name)) else { continue };
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(|e| log_error("import_workspace", e))?;
+        fs::create_dir_all(data_dir.join(SHARED_ASSETS_DIR)).map_err(|e| log_error("import_workspace", e))?;
+        write_atomic(&target, &bytes).map_err(|e| log_error("import_workspace", e))?;
     }
+    if added > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    Ok(WorkspaceImportReport { imported: added, sanitized, status_fallbacks })
+}
 
-    Ok(orphan_folders)
+/// 是否为同步工具生成的 todos.json 冲突副本：Dropbox 的 "todos (… conflicted copy …).json"、
+/// Syncthing 的 "todos.sync-conflict-….json"、Google Drive / OneDrive 的 "todos (1).json" 等
+fn is_sync_conflict_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let Some(stem) = lower.strip_suffix(".json").and_then(|n| n.strip_prefix("todos")) else { return false };
+    if stem.is_empty() {
+        return false;
+    }
+    if stem.contains("conflict") {
+        return true;
+    }
+    stem.strip_prefix(" (")
+        .and_then(|n| n.strip_suffix(')'))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
 }
 
-#[tauri::command]
-fn save_todo_detail(app: tauri::AppHandle, data_path: String, folder_name: String, content: String) -> Result<(), String> {
-    let detail_path = Path::new(&data_path).join(&folder_name).join("content.json");
-    fs::write(detail_path, content).map_err(|e| e.to_string())?;
-    #[derive(Clone, Serialize)]
-    struct Payload { folder_name: String }
-    let _ = app.emit("todo-detail-changed", Payload { folder_name });
-    Ok(())
+#[derive(Serialize, Debug)]
+struct SyncConflictInfo {
+    file_name: String,
+    size: u64,
+    modified_at: Option<String>,
+    /// 副本中的待办数，无法解析时为空
+    todo_count: Option<usize>,
 }
 
-#[tauri::command]
-fn get_todo_detail(data_path: String, folder_name: String) -> Result<String, String> {
-    let detail_path = Path::new(&data_path).join(folder_name).join("content.json");
-    if detail_path.exists() {
-        fs::read_to_string(detail_path).map_err(|e| e.to_string())
-    } else {
-        Ok("{}".to_string())
-    }
+fn read_conflict_todos(path: &Path) -> Result<Vec<TodoItem>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(strip_bom(&content)).map_err(|e| format!("冲突副本解析失败: {}", e))
 }
 
+/// 查找数据目录中由同步工具生成的 todos.json 冲突副本
 #[tauri::command]
-fn move_data(app: tauri::AppHandle, old_path: String, new_path: String) -> Result<(), String> {
-    if old_path == new_path || old_path.is_empty() || new_path.is_empty() {
-        return Ok(());
-    }
+fn detect_sync_conflicts(data_path: String) -> Vec<SyncConflictInfo> {
+    let data_path = expand_data_path(&data_path);
+    let mut conflicts: Vec<SyncConflictInfo> = fs::read_dir(&data_path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_file() && is_sync_conflict_name(&e.file_name().to_string_lossy()))
+                .map(|e| {
+                    let path = e.path();
+                    let metadata = path.metadata().ok();
+                    SyncConflictInfo {
+                        file_name: e.file_name().to_string_lossy().into_owned(),
+                        size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                        modified_at: metadata
+                            .and_then(|m| m.modified().ok())
+                            .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339()),
+                        todo_count: read_conflict_todos(&path).ok().map(|t| t.len()),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    conflicts.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    conflicts
+}
 
-    let old_p = Path::new(&old_path);
-    let new_p = Path::new(&new_path);
-    
-    if !old_p.exists() {
-        return Ok(());
+#[derive(Serialize, Debug, Default)]
+struct SyncMergeReport {
+    /// 采用冲突副本版本的待办（含只存在于副本中的）
+    merged: usize,
+    /// 两边都有、保留当前版本的待办
+    kept: usize,
+    /// 被舍弃的副本条目（文件夹名无效或与其他待办冲突）
+    dropped: usize,
+}
+
+/// 将冲突副本按 id 合并进当前待办列表：只在一边的待办都保留；两边都有时按 strategy 取舍——
+/// "newest" 取 updated_at 较新的一方（相同或缺失时保留当前版本），"local" 始终保留当前版本，"conflict" 始终采用副本。
+/// 合并成功后冲突副本移入回收站
+#[tauri::command]
+fn merge_sync_conflict(
+    app: tauri::AppHandle,
+    data_path: String,
+    conflict_file: String,
+    strategy: String,
+) -> Result<SyncMergeReport, String> {
+    let data_path = expand_data_path(&data_path);
+    if !matches!(strategy.as_str(), "newest" | "local" | "conflict") {
+        return Err(format!("不支持的合并方式: {}", strategy));
     }
-    
-    if !new_p.exists() {
-        fs::create_dir_all(new_p).map_err(|e| format!("Failed to create new directory: {}", e))?;
+    let conflict_file = sanitize_folder_name(&conflict_file)?;
+    if !is_sync_conflict_name(&conflict_file) {
+        return Err(format!("不是同步冲突副本: {}", conflict_file));
     }
-    
-    // 遍历旧路径下的所有文件和文件夹（不迁移 config.json，其属于应用配置）
-    for entry in fs::read_dir(old_p).map_err(|e| format!("Failed to read old directory: {}", e))? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        let name = path.file_name().ok_or("Invalid file name")?;
-        if name == "config.json" {
+    let conflict_path = Path::new(&data_path).join(&conflict_file);
+    let incoming = read_conflict_todos(&conflict_path).map_err(|e| log_error("merge_sync_conflict", e))?;
+    let mut todos = load_todos(&data_path);
+    let mut report = SyncMergeReport::default();
+    let updated = |t: &TodoItem| t.updated_at.as_deref().and_then(parse_timestamp);
+    for todo in incoming {
+        if sanitize_folder_name(&todo.folder_name).is_err() {
+            report.dropped += 1;
             continue;
         }
-        let dest = new_p.join(name);
-        
-        if path.is_dir() {
-            // 递归移动文件夹
-            copy_dir_all(&path, &dest).map_err(|e| format!("Failed to copy directory: {}", e))?;
-            fs::remove_dir_all(&path).map_err(|e| format!("Failed to remove old directory: {}", e))?;
-        } else {
-            fs::copy(&path, &dest).map_err(|e| format!("Failed to copy file: {}", e))?;
-            fs::remove_file(&path).map_err(|e| format!("Failed to remove old file: {}", e))?;
+        match todos.iter().position(|t| t.id == todo.id) {
+            Some(index) => {
+                let take = match strategy.as_str() {
+                    "conflict" => true,
+                    "local" => false,
+                    _ => updated(&todo) > updated(&todos[index]),
+                };
+                if take && todo.folder_name != todos[index].folder_name {
+                    // 改成其他待办正在使用的文件夹会让两条待办指向同一份详情
+                    if todos.iter().any(|t| t.id != todo.id && t.folder_name == todo.folder_name) {
+                        report.dropped += 1;
+                        continue;
+                    }
+                }
+                if take && todos[index] != todo {
+                    todos[index] = todo;
+                    report.merged += 1;
+                } else {
+                    report.kept += 1;
+                }
+            }
+            None if todos.iter().any(|t| t.folder_name == todo.folder_name) => report.dropped += 1,
+            None => {
+                let _ = fs::create_dir_all(Path::new(&data_path).join(&todo.folder_name).join(assets_dir_name()));
+                todos.push(todo);
+                report.merged += 1;
+            }
         }
     }
-    let _ = app.emit("todos-changed", ());
-    Ok(())
+    if report.merged > 0 {
+        save_todos(app, data_path, todos)?;
+    }
+    trash::delete(&conflict_path).map_err(|e| log_error("merge_sync_conflict", e))?;
+    Ok(report)
 }
 
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
-    fs::create_dir_all(&dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        }
+/// 按扩展名（小写）缓存的文件类型图标，避免重复创建临时文件
+static ICON_CACHE: Mutex<std::collections::BTreeMap<String, String>> = Mutex::new(std::collections::BTreeMap::new());
+
+#[tauri::command]
+fn get_file_icon(extension: String) -> Result<String, String> {
+    let key = extension.trim().to_lowercase();
+    if let Some(icon) = ICON_CACHE.lock().ok().and_then(|cache| cache.get(&key).cloned()) {
+        return Ok(icon);
     }
-    Ok(())
+    let icon = load_file_icon(extension)?;
+    if let Ok(mut cache) = ICON_CACHE.lock() {
+        cache.insert(key, icon.clone());
+    }
+    Ok(icon)
 }
 
+/// 预先计算并缓存常见扩展名的文件图标（前端在启动时调用），返回缓存中的图标数
 #[tauri::command]
-fn get_file_icon(extension: String) -> Result<String, String> {
+async fn warm_icon_cache(extensions: Vec<String>) -> Result<usize, String> {
+    for extension in extensions {
+        get_file_icon(extension)?;
+    }
+    Ok(ICON_CACHE.lock().map(|cache| cache.len()).unwrap_or_default())
+}
+
+/// 获取文件类型图标时在临时目录创建的空文件的名称前缀（后接扩展名）
+const TEMP_ICON_PREFIX: &str = "tauri_icon_dummy.";
+
+/// 删除临时目录中残留的图标临时文件（获取图标途中崩溃时未被删除的），只删除一分钟前创建的，
+/// 以免误删正在使用的文件；启动时自动执行。返回删除的文件数
+#[tauri::command]
+fn cleanup_temp_icons() -> usize {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else { return 0 };
+    let stale = |path: &Path| {
+        path.metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age.as_secs() >= 60)
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with(TEMP_ICON_PREFIX))
+        .map(|e| e.path())
+        .filter(|path| path.is_file() && stale(path))
+        .filter(|path| fs::remove_file(path).is_ok())
+        .count()
+}
+
+fn load_file_icon(extension: String) -> Result<String, String> {
     #[cfg(windows)]
     {
         use std::env;
@@ -267,7 +9398,7 @@ fn get_file_icon(extension: String) -> Result<String, String> {
         if safe_ext.is_empty() {
             return Ok(String::new());
         }
-        let dummy_path = env::temp_dir().join(format!("tauri_icon_dummy.{}", safe_ext));
+        let dummy_path = env::temp_dir().join(format!("{}{}", TEMP_ICON_PREFIX, safe_ext));
         let path_str = dummy_path.to_str().unwrap_or("");
         let created = if !dummy_path.exists() {
             fs::File::create(&dummy_path).ok().map(|mut f| {
@@ -293,6 +9424,520 @@ fn get_file_icon(extension: String) -> Result<String, String> {
     }
 }
 
+/// 缩略图缓存目录，位于待办文件夹下
+const THUMBS_DIR: &str = ".thumbs";
+
+#[derive(Serialize, Debug)]
+struct AttachmentThumbnail {
+    /// "image" 为缩略图（PNG），"icon" 为文件类型图标（与 get_file_icon 相同，可能为空）
+    kind: String,
+    /// base64 编码的 PNG
+    data: String,
+}
+
+/// 返回附件预览：图片缩放到 max_size 以内并缓存到 .thumbs，源文件更新后重新生成；其他类型返回文件类型图标
+#[tauri::command]
+fn get_attachment_thumbnail(
+    data_path: String,
+    folder_name: String,
+    file_name: String,
+    max_size: u32,
+) -> Result<AttachmentThumbnail, String> {
+    use base64::Engine;
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let file_name = sanitize_folder_name(&file_name)?;
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    let source = folder_path.join(assets_dir_name()).join(&file_name);
+    if !source.is_file() {
+        return Err(format!("附件不存在: {}", file_name));
+    }
+    let ext = source.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let is_image = image::ImageFormat::from_extension(&ext).is_some_and(|f| f.can_read());
+    if !is_image {
+        return Ok(AttachmentThumbnail { kind: "icon".to_string(), data: get_file_icon(ext)? });
+    }
+
+    let max_size = max_size.clamp(16, 1024);
+    let cache = folder_path.join(THUMBS_DIR).join(format!("{}-{}.png", file_name, max_size));
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    let fresh = matches!((modified(&cache), modified(&source)), (Some(c), Some(s)) if c >= s);
+    let bytes = match fresh.then(|| fs::read(&cache).ok()).flatten() {
+        Some(bytes) => bytes,
+        None => {
+            let img = image::open(&source).map_err(|e| log_error("get_attachment_thumbnail", e))?;
+            let mut bytes = Vec::new();
+            img.thumbnail(max_size, max_size)
+                .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+                .map_err(|e| log_error("get_attachment_thumbnail", e))?;
+            if let Some(parent) = cache.parent() {
+                if fs::create_dir_all(parent).is_ok() {
+                    let _ = write_atomic(&cache, &bytes);
+                }
+            }
+            bytes
+        }
+    };
+    Ok(AttachmentThumbnail {
+        kind: "image".to_string(),
+        data: base64::engine::general_purpose::STANDARD.encode(bytes),
+    })
+}
+
+/// 浏览器能直接显示的图片格式
+const WEB_SAFE_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "avif", "ico"];
+
+#[derive(Serialize, Debug)]
+struct ConvertedAsset {
+    from: String,
+    to: String,
+}
+
+/// 将节点树中引用 assets/old_name 的节点改为引用 assets/new_name
+fn retarget_asset_file(nodes: &mut [serde_json::Value], old_name: &str, new_name: &str) {
+    let old_asset = format!("{}/{}", assets_dir_name(), old_name);
+    for node in nodes.iter_mut() {
+        if node.get("assetPath").and_then(|a| a.as_str()) == Some(old_asset.as_str()) {
+            node["assetPath"] = serde_json::Value::String(format!("{}/{}", assets_dir_name(), new_name));
+            if let Some(url) = node.get("url").and_then(|u| u.as_str()) {
+                let replaced = url
+                    .replace(&encode_uri_component(old_name), &encode_uri_component(new_name))
+                    .replace(old_name, new_name);
+                node["url"] = serde_json::Value::String(replaced);
+            }
+            if node.get("fileName").and_then(|f| f.as_str()) == Some(old_name) {
+                node["fileName"] = serde_json::Value::String(new_name.to_string());
+            }
+        }
+        if let Some(children) = node.get_mut("children").and_then(|c| c.as_array_mut()) {
+            retarget_asset_file(children, old_name, new_name);
+        }
+    }
+}
+
+/// 返回 dir 中不与现有文件冲突的文件名，冲突时依次尝试 "名称-1.扩展名"、"名称-2.扩展名"……
+fn unique_file_name(dir: &Path, name: &str) -> String {
+    if !dir.join(name).exists() {
+        return name.to_string();
+    }
+    let path = Path::new(name);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|i| format!("{}-{}{}", stem, i, ext))
+        .find(|candidate| !dir.join(candidate).exists())
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// 从节点树中取出引用 assets 下所列文件的资源节点
+fn take_asset_nodes(nodes: &mut Vec<serde_json::Value>, assets: &std::collections::HashSet<String>) -> Vec<serde_json::Value> {
+    let mut taken = Vec::new();
+    let mut kept = Vec::new();
+    for mut node in nodes.drain(..) {
+        let referenced = node.get("assetPath").and_then(|a| a.as_str()).is_some_and(|a| assets.contains(a));
+        if referenced {
+            taken.push(node);
+            continue;
+        }
+        if let Some(children) = node.get_mut("children").and_then(|c| c.as_array_mut()) {
+            taken.extend(take_asset_nodes(children, assets));
+        }
+        kept.push(node);
+    }
+    *nodes = kept;
+    taken
+}
+
+/// 将 source_folder 中所列附件移到 target_folder，重名时自动改名。update_references 为 true 时，
+/// 把引用这些附件的节点从源待办详情移到目标待办详情末尾。返回附件在目标中的最终文件名
+#[tauri::command]
+fn move_attachments(
+    app: tauri::AppHandle,
+    data_path: String,
+    source_folder: String,
+    target_folder: String,
+    file_names: Vec<String>,
+    update_references: bool,
+) -> Result<Vec<String>, String> {
+    let data_path = expand_data_path(&data_path);
+    let source_folder = sanitize_folder_name(&source_folder)?;
+    let target_folder = sanitize_folder_name(&target_folder)?;
+    if source_folder == target_folder {
+        return Err("源待办与目标待办相同".to_string());
+    }
+    let source_assets = Path::new(&data_path).join(&source_folder).join(assets_dir_name());
+    let target_assets = Path::new(&data_path).join(&target_folder).join(assets_dir_name());
+    if !target_assets.parent().is_some_and(|p| p.is_dir()) {
+        return Err(format!("待办文件夹不存在: {}", target_folder));
+    }
+    fs::create_dir_all(&target_assets).map_err(|e| log_error("move_attachments", e))?;
+    let mut moved = Vec::new();
+    for name in &file_names {
+        let name = sanitize_folder_name(name)?;
+        let from = source_assets.join(&name);
+        if !from.is_file() {
+            return Err(format!("附件不存在: {}", name));
+        }
+        let final_name = unique_file_name(&target_assets, &name);
+        let to = target_assets.join(&final_name);
+        if fs::rename(&from, &to).is_err() {
+            fs::copy(&from, &to).and_then(|_| fs::remove_file(&from)).map_err(|e| log_error("move_attachments", e))?;
+        }
+        moved.push((name, final_name));
+    }
+
+    if update_references && !moved.is_empty() {
+        let assets: std::collections::HashSet<String> = moved.iter().map(|(old, _)| format!("{}/{}", assets_dir_name(), old)).collect();
+        let mut source_nodes = parse_detail_nodes(&get_todo_detail(data_path.clone(), source_folder.clone())?);
+        let mut taken = take_asset_nodes(&mut source_nodes, &assets);
+        if !taken.is_empty() {
+            retarget_folder_urls(&mut taken, &source_folder, &target_folder);
+            for (old, new) in moved.iter().filter(|(old, new)| old != new) {
+                retarget_asset_file(&mut taken, old, new);
+            }
+            let mut target_nodes = parse_detail_nodes(&get_todo_detail(data_path.clone(), target_folder.clone())?);
+            target_nodes.extend(taken);
+            let target_content = serde_json::to_string(&target_nodes).map_err(|e| e.to_string())?;
+            let source_content = serde_json::to_string(&source_nodes).map_err(|e| e.to_string())?;
+            save_todo_detail(app.clone(), data_path.clone(), target_folder, target_content)?;
+            save_todo_detail(app, data_path, source_folder, source_content)?;
+        }
+    }
+    Ok(moved.into_iter().map(|(_, new)| new).collect())
+}
+
+/// 将待办中浏览器无法直接显示的图片（BMP、TIFF 等）转为 PNG，改写详情中的引用，原文件移入 assets/original。
+/// 返回转换过的文件
+#[tauri::command]
+fn convert_assets_web_safe(app: tauri::AppHandle, data_path: String, folder_name: String) -> Result<Vec<ConvertedAsset>, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let assets = Path::new(&data_path).join(&folder_name).join(assets_dir_name());
+    let originals = assets.join("original");
+    let mut converted = Vec::new();
+    let Ok(entries) = fs::read_dir(&assets) else { return Ok(converted) };
+    for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_file()) {
+        let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let readable = image::ImageFormat::from_extension(&ext).is_some_and(|f| f.can_read());
+        if !readable || WEB_SAFE_IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            continue;
+        }
+        let Some(old_name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else { continue };
+        let img = match image::open(&path) {
+            Ok(img) => img,
+            Err(e) => {
+                log_error("convert_assets_web_safe", format!("{}: {}", old_name, e));
+                continue;
+            }
+        };
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let mut new_name = format!("{}.png", stem);
+        if assets.join(&new_name).exists() {
+            new_name = format!("{}-{}.png", stem, ext);
+        }
+        let mut bytes = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .map_err(|e| log_error("convert_assets_web_safe", e))?;
+        write_atomic(&assets.join(&new_name), &bytes).map_err(|e| log_error("convert_assets_web_safe", e))?;
+        fs::create_dir_all(&originals).map_err(|e| log_error("convert_assets_web_safe", e))?;
+        fs::rename(&path, originals.join(&old_name)).map_err(|e| log_error("convert_assets_web_safe", e))?;
+        converted.push(ConvertedAsset { from: old_name, to: new_name });
+    }
+    if !converted.is_empty() {
+        let mut nodes = parse_detail_nodes(&get_todo_detail(data_path.clone(), folder_name.clone())?);
+        for asset in &converted {
+            retarget_asset_file(&mut nodes, &asset.from, &asset.to);
+        }
+        let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+        save_todo_detail(app, data_path, folder_name, content)?;
+    }
+    Ok(converted)
+}
+
+/// 将节点树中 assetPath 为 old_asset 的节点改为 new_asset，并同步修改 url 中相对数据目录的路径
+/// （old_rel / new_rel 为相对数据目录的路径分段，url 中可能以 / 或 \ 分隔且经过编码）
+fn retarget_asset_path(nodes: &mut [serde_json::Value], old_asset: &str, new_asset: &str, old_rel: &[&str], new_rel: &[&str]) {
+    for node in nodes.iter_mut() {
+        if node.get("assetPath").and_then(|a| a.as_str()) == Some(old_asset) {
+            node["assetPath"] = serde_json::Value::String(new_asset.to_string());
+            if let Some(url) = node.get("url").and_then(|u| u.as_str()) {
+                let mut replaced = url.to_string();
+                for (sep, encode) in [("%2F", true), ("%5C", true), ("/", false)] {
+                    let join = |parts: &[&str]| {
+                        parts
+                            .iter()
+                            .map(|p| if encode { encode_uri_component(p) } else { p.to_string() })
+                            .collect::<Vec<_>>()
+                            .join(sep)
+                    };
+                    let old_tail = join(old_rel);
+                    if replaced.contains(&old_tail) {
+                        replaced = replaced.replace(&old_tail, &join(new_rel));
+                        break;
+                    }
+                }
+                node["url"] = serde_json::Value::String(replaced);
+            }
+        }
+        if let Some(children) = node.get_mut("children").and_then(|c| c.as_array_mut()) {
+            retarget_asset_path(children, old_asset, new_asset, old_rel, new_rel);
+        }
+    }
+}
+
+/// 将 old_dir 中的文件合并进 new_dir（子目录逐级合并）：同名且内容相同的文件跳过，内容不同的换用不重名的文件名移入，
+/// 并以相对附件目录的路径（prefix 为当前子目录）记入 renamed
+fn merge_assets_dir(
+    old_dir: &Path,
+    new_dir: &Path,
+    prefix: &str,
+    renamed: &mut std::collections::HashMap<String, String>,
+) -> std::io::Result<()> {
+    fs::create_dir_all(new_dir)?;
+    for entry in fs::read_dir(old_dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let (source, target) = (entry.path(), new_dir.join(&name));
+        if !target.exists() {
+            fs::rename(&source, &target)?;
+        } else if source.is_dir() && target.is_dir() {
+            merge_assets_dir(&source, &target, &format!("{}{}/", prefix, name), renamed)?;
+        } else if !(source.is_file() && target.is_file() && hash_file(&source) == hash_file(&target)) {
+            let unique = unique_file_name(new_dir, &name);
+            fs::rename(&source, new_dir.join(&unique))?;
+            renamed.insert(format!("{}{}", prefix, name), format!("{}{}", prefix, unique));
+        }
+    }
+    Ok(())
+}
+
+/// 将各待办文件夹中名为 from 的附件目录改名为当前配置的 assets_dir_name，并改写详情中的引用；
+/// 目标目录已存在时合并（同名且内容相同的文件跳过，内容不同的改名移入）。返回迁移的待办数
+#[tauri::command]
+fn migrate_assets_dir(app: tauri::AppHandle, data_path: String, from: String) -> Result<usize, String> {
+    let data_path = expand_data_path(&data_path);
+    let from = sanitize_folder_name(from.trim())?;
+    let to = assets_dir_name();
+    if from == to {
+        return Ok(0);
+    }
+    let mut migrated = 0;
+    for todo in load_todos(&data_path) {
+        let folder_path = Path::new(&data_path).join(&todo.folder_name);
+        let old_dir = folder_path.join(&from);
+        if !old_dir.is_dir() {
+            continue;
+        }
+        let new_dir = folder_path.join(&to);
+        // 合并时与目标目录中同名但内容不同的文件改名移入：相对附件目录的旧路径 -> 新路径
+        let mut renamed = std::collections::HashMap::new();
+        if new_dir.exists() {
+            merge_assets_dir(&old_dir, &new_dir, "", &mut renamed).map_err(|e| log_error("migrate_assets_dir", e))?;
+            fs::remove_dir_all(&old_dir).map_err(|e| log_error("migrate_assets_dir", e))?;
+        } else {
+            fs::rename(&old_dir, &new_dir).map_err(|e| log_error("migrate_assets_dir", e))?;
+        }
+        let content = fs::read_to_string(folder_path.join("content.json")).unwrap_or_default();
+        let mut nodes = parse_detail_nodes(&content);
+        let mut assets = std::collections::HashSet::new();
+        collect_asset_paths(&nodes, &mut assets);
+        let old_prefix = format!("{}/", from);
+        let mut changed = false;
+        for asset in &assets {
+            let Some(rest) = asset.strip_prefix(&old_prefix) else { continue };
+            let new_rest = renamed.get(rest).map(String::as_str).unwrap_or(rest);
+            let mut old_rel = vec![todo.folder_name.as_str(), from.as_str()];
+            old_rel.extend(rest.split('/'));
+            let mut new_rel = vec![todo.folder_name.as_str(), to.as_str()];
+            new_rel.extend(new_rest.split('/'));
+            retarget_asset_path(&mut nodes, asset, &format!("{}/{}", to, new_rest), &old_rel, &new_rel);
+            changed = true;
+        }
+        if changed {
+            let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+            save_todo_detail(app.clone(), data_path.clone(), todo.folder_name.clone(), content)?;
+        }
+        migrated += 1;
+    }
+    Ok(migrated)
+}
+
+/// 隔离区目录（位于资源目录下），其中的附件即为尚未确认的附件
+const QUARANTINE_DIR: &str = "quarantine";
+
+/// 确认隔离区中的附件：从资源目录下的 quarantine 移入资源目录（重名且内容相同时直接复用已有文件），
+/// 详情中的引用随之改写；返回附件确认后的 assetPath
+#[tauri::command]
+fn approve_attachment(app: tauri::AppHandle, data_path: String, folder_name: String, file_name: String) -> Result<String, String> {
+    let data_path = expand_data_path(&data_path);
+    let folder_name = sanitize_folder_name(&folder_name)?;
+    let file_name = sanitize_folder_name(&file_name)?;
+    let folder_path = Path::new(&data_path).join(&folder_name);
+    let assets = assets_dir_name();
+    let assets_dir = folder_path.join(&assets);
+    let source = assets_dir.join(QUARANTINE_DIR).join(&file_name);
+    if !source.is_file() {
+        return Err(format!("隔离区中没有该附件: {}", file_name));
+    }
+    let existing = assets_dir.join(&file_name);
+    let target_name = if existing.is_file() && hash_file(&existing) == hash_file(&source) {
+        fs::remove_file(&source).map_err(|e| log_error("approve_attachment", e))?;
+        file_name.clone()
+    } else {
+        let name = unique_file_name(&assets_dir, &file_name);
+        fs::rename(&source, assets_dir.join(&name)).map_err(|e| log_error("approve_attachment", e))?;
+        name
+    };
+    let old_asset = format!("{}/{}/{}", assets, QUARANTINE_DIR, file_name);
+    let new_asset = format!("{}/{}", assets, target_name);
+    let content = fs::read_to_string(folder_path.join("content.json")).unwrap_or_default();
+    let mut nodes = parse_detail_nodes(&content);
+    retarget_asset_path(
+        &mut nodes,
+        &old_asset,
+        &new_asset,
+        &[&folder_name, &assets, QUARANTINE_DIR, &file_name],
+        &[&folder_name, &assets, &target_name],
+    );
+    let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+    save_todo_detail(app, data_path, folder_name, content)?;
+    Ok(new_asset)
+}
+
+/// 统计共用资源池中每个文件被哪些待办文件夹引用（扫描数据目录下所有非保留目录的详情，非法名称不计入）
+fn shared_asset_refs(root: &Path) -> std::collections::HashMap<String, Vec<String>> {
+    let mut refs: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+    let Ok(entries) = fs::read_dir(root) else { return refs };
+    for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+        let folder = entry.file_name().to_string_lossy().into_owned();
+        if is_reserved_dir(&folder) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(entry.path().join("content.json")) else { continue };
+        let mut assets = std::collections::HashSet::new();
+        collect_asset_paths(&parse_detail_nodes(&content), &mut assets);
+        for asset in assets {
+            if let Some(name) = asset.strip_prefix(SHARED_ASSET_PREFIX).filter(|name| sanitize_folder_name(name).is_ok()) {
+                refs.entry(name.to_string()).or_default().push(folder.clone());
+            }
+        }
+    }
+    refs
+}
+
+/// 删除待办前将其引用的共用资源复制回自身 assets 并改回本地引用，使移入回收站的文件夹可独立恢复；
+/// 之后不再被任何其他待办引用的共用资源从资源池中删除
+fn release_shared_assets(data_path: &str, folder_name: &str) -> Result<(), String> {
+    let root = Path::new(data_path);
+    let folder_path = root.join(folder_name);
+    let detail_path = folder_path.join("content.json");
+    let Ok(content) = fs::read_to_string(&detail_path) else { return Ok(()) };
+    let mut nodes = parse_detail_nodes(&content);
+    let shared = referenced_shared_assets(root, [folder_name]);
+    if shared.is_empty() {
+        return Ok(());
+    }
+    let pool = root.join(SHARED_ASSETS_DIR);
+    let assets_name = assets_dir_name();
+    let local_assets = folder_path.join(&assets_name);
+    fs::create_dir_all(&local_assets).map_err(|e| e.to_string())?;
+    for name in &shared {
+        let source = pool.join(name);
+        if !source.is_file() {
+            continue;
+        }
+        let local_name = unique_file_name(&local_assets, name);
+        fs::copy(&source, local_assets.join(&local_name)).map_err(|e| e.to_string())?;
+        retarget_asset_path(
+            &mut nodes,
+            &format!("{}{}", SHARED_ASSET_PREFIX, name),
+            &format!("{}/{}", assets_name, local_name),
+            &[SHARED_ASSETS_DIR, name],
+            &[folder_name, &assets_name, &local_name],
+        );
+    }
+    let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+    write_atomic(&detail_path, content.as_bytes()).map_err(|e| e.to_string())?;
+    let refs = shared_asset_refs(root);
+    for name in shared.iter().filter(|n| !refs.contains_key(n.as_str())) {
+        let _ = fs::remove_file(pool.join(name));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Debug, Default)]
+struct SharedAssetsReport {
+    /// 移入资源池的文件数
+    pooled: usize,
+    /// 与资源池中已有文件内容相同、直接删除的重复文件数
+    deduplicated: usize,
+    /// 删除重复文件腾出的字节数
+    bytes_reclaimed: u64,
+    /// 详情引用被改写的待办数
+    todos_updated: usize,
+}
+
+/// 将所有待办详情引用的资源移入数据目录下按内容哈希命名的 .assets 资源池，并把引用改为指向资源池；
+/// 内容相同的文件只保留一份。删除待办时只会删除不再被任何待办引用的共用资源
+#[tauri::command]
+fn migrate_to_shared_assets(app: tauri::AppHandle, data_path: String) -> Result<SharedAssetsReport, String> {
+    let data_path = expand_data_path(&data_path);
+    let root = Path::new(&data_path);
+    let pool = root.join(SHARED_ASSETS_DIR);
+    fs::create_dir_all(&pool).map_err(|e| log_error("migrate_to_shared_assets", e))?;
+    let mut report = SharedAssetsReport::default();
+    for todo in load_todos(&data_path) {
+        let folder_path = root.join(&todo.folder_name);
+        let Ok(content) = fs::read_to_string(folder_path.join("content.json")) else { continue };
+        let mut nodes = parse_detail_nodes(&content);
+        let mut assets = std::collections::HashSet::new();
+        collect_asset_paths(&nodes, &mut assets);
+        let prefix = format!("{}/", assets_dir_name());
+        let mut assets: Vec<String> = assets.into_iter().filter(|a| a.starts_with(&prefix)).collect();
+        assets.sort();
+        let mut pooled_here = Vec::new();
+        for asset in &assets {
+            let path = folder_path.join(asset);
+            if !path.is_file() {
+                continue;
+            }
+            let hash = hash_file(&path).ok_or_else(|| format!("读取文件失败: {}", path.display()))?;
+            let pool_name = match path.extension() {
+                Some(ext) => format!("{}.{}", hash, ext.to_string_lossy().to_lowercase()),
+                None => hash,
+            };
+            let target = pool.join(&pool_name);
+            if target.exists() {
+                report.deduplicated += 1;
+                report.bytes_reclaimed += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            } else {
+                fs::copy(&path, &target).map_err(|e| log_error("migrate_to_shared_assets", e))?;
+                report.pooled += 1;
+            }
+            let mut old_rel = vec![todo.folder_name.as_str()];
+            old_rel.extend(asset.split('/'));
+            retarget_asset_path(
+                &mut nodes,
+                asset,
+                &format!("{}{}", SHARED_ASSET_PREFIX, pool_name),
+                &old_rel,
+                &[SHARED_ASSETS_DIR, &pool_name],
+            );
+            pooled_here.push(path);
+        }
+        if pooled_here.is_empty() {
+            continue;
+        }
+        // 详情改写成功后再删除本地副本，避免中途失败导致引用失效
+        let content = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+        save_todo_detail(app.clone(), data_path.clone(), todo.folder_name.clone(), content)?;
+        for path in pooled_here {
+            let _ = fs::remove_file(path);
+        }
+        report.todos_updated += 1;
+    }
+    Ok(report)
+}
+
 #[tauri::command]
 fn create_new_window(app: tauri::AppHandle, url: String) -> Result<(), String> {
     let n = WINDOW_COUNTER.fetch_add(1, Ordering::Relaxed);
@@ -309,34 +9954,526 @@ fn create_new_window(app: tauri::AppHandle, url: String) -> Result<(), String> {
 }
 
 fn main() {
+    let handler: fn(tauri::ipc::Invoke) -> bool = tauri::generate_handler![
+        get_app_config,
+        save_app_config,
+        get_todos,
+        save_todos,
+        create_todo_folder,
+        delete_todo_folder,
+        save_todo_detail,
+        get_todo_detail,
+        move_data,
+        get_file_icon,
+        create_new_window,
+        find_orphan_todo_folders,
+        get_content_hash,
+        get_detail_preview,
+        rebuild_index,
+        get_log_path,
+        open_log_file,
+        get_upcoming,
+        migrate_json_to_sqlite,
+        backup_data,
+        list_backups,
+        verify_backup,
+        get_scratchpad,
+        save_scratchpad,
+        find_duplicate_titles,
+        merge_todos,
+        resolve_data_path,
+        record_daily_stats,
+        get_stats_history,
+        snooze_todo,
+        export_todo_pdf,
+        copy_detail_to_clipboard,
+        get_statuses,
+        export_selected,
+        import_workspace,
+        normalize_line_endings,
+        normalize_all_line_endings,
+        get_todos_grouped,
+        restore_backup,
+        optimize_folder,
+        is_autostart_supported,
+        import_text_folder,
+        delete_todos,
+        rename_folders_by_title,
+        watch_folder,
+        unwatch_folder,
+        set_app_lock,
+        verify_app_lock,
+        lock_app,
+        is_app_locked,
+        has_app_lock,
+        export_config_redacted,
+        get_workspace_text_stats,
+        cycle_status,
+        migrate_content_schema,
+        get_today,
+        global_dedupe_attachments,
+        delete_todo_folder_to_os_trash,
+        get_payload_diagnostics,
+        bulk_set_priority,
+        export_web_bundle,
+        reload_config,
+        set_todo_status,
+        list_trash,
+        restore_from_trash,
+        export_workspace,
+        export_todo_zip,
+        get_tag_color,
+        compact_index,
+        export_ical,
+        get_attachment_thumbnail,
+        list_todos_by_size,
+        rename_todo,
+        add_dependency,
+        remove_dependency,
+        get_blocked_todos,
+        export_by_date_range,
+        get_todos_since,
+        set_archived,
+        detect_stale_data_path,
+        repair_data_path,
+        warm_icon_cache,
+        forecast_completion,
+        list_system_fonts,
+        export_settings,
+        import_settings,
+        reconcile_titles,
+        get_weekly_digest,
+        convert_assets_web_safe,
+        scan_broken_assets,
+        set_language,
+        split_todo,
+        get_tag_tree,
+        purge_todo_history,
+        normalize_timestamps,
+        export_feed,
+        set_reminder,
+        check_due_reminders,
+        move_attachments,
+        find_empty_details,
+        save_config_preset,
+        apply_config_preset,
+        list_presets,
+        find_similar_images,
+        import_bookmarks,
+        get_tag_progress,
+        rename_tag,
+        delete_tag,
+        generate_manifest,
+        set_effort,
+        get_total_effort,
+        migrate_to_shared_assets,
+        get_free_space,
+        move_todo_to_board,
+        list_boards,
+        get_audit_log,
+        restore_todo_from_backup,
+        check_consistency,
+        get_detail_plaintext,
+        init_workspace,
+        copy_todos_markdown,
+        find_long_titles,
+        get_detail_outline,
+        get_last_backup_time,
+        find_unclassified,
+        get_todo_folder_path,
+        get_todo_content_path,
+        get_todo_assets_path,
+        suspend_autostart,
+        resume_autostart,
+        is_autostart_suspended,
+        get_todo_link,
+        take_pending_todo_link,
+        resolve_todo_link,
+        import_markdown_tree,
+        repair_permissions,
+        get_todos_by_urgency,
+        export_workspace_streaming,
+        auto_archive,
+        validate_asset_integrity,
+        export_cards,
+        get_system_accent_color,
+        export_ndjson,
+        approve_attachment,
+        create_todos_bulk,
+        repair_bom_files,
+        list_configs,
+        get_active_config,
+        set_active_config,
+        get_todos_timed,
+        export_dependency_graph,
+        migrate_assets_dir,
+        find_mojibake,
+        repair_mojibake,
+        export_workspace_encrypted,
+        import_workspace_encrypted,
+        get_status_colors,
+        set_status_color,
+        import_ical,
+        diff_todo_versions,
+        register_quick_capture_hotkey,
+        verify_directories,
+        compress_old_backups,
+        export_board_image,
+        detect_sync_conflicts,
+        merge_sync_conflict,
+        get_folder_size,
+        check_folder_quota,
+        export_tree_text,
+        get_editor_theme,
+        set_editor_theme,
+        normalize_tags,
+        save_query,
+        list_queries,
+        run_query,
+        export_query,
+        save_detail_draft,
+        has_detail_draft,
+        discard_draft,
+        commit_draft,
+        cleanup_temp_icons,
+        get_month_view,
+        save_config_partial,
+        get_access_stats,
+        resave_all,
+        reset_config_keep_data_path,
+        check_contrast,
+        migrate_archive_layout
+    ];
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
         ))
-        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
             if let Some(w) = app.get_webview_window("main") {
                 let _ = w.set_focus();
             }
+            if let Some(link) = find_todo_link(&argv) {
+                let _ = app.emit("open-todo-link", link);
+            }
         }))
-        .invoke_handler(tauri::generate_handler![
-            get_app_config,
-            save_app_config,
-            get_todos,
-            save_todos,
-            create_todo_folder,
-            delete_todo_folder,
-            save_todo_detail,
-            get_todo_detail,
-            move_data,
-            get_file_icon,
-            create_new_window,
-            find_orphan_todo_folders
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .setup(|app| {
+            init_logging(app.handle());
+            if let Ok(mut pending) = PENDING_TODO_LINK.lock() {
+                *pending = find_todo_link(&std::env::args().collect::<Vec<_>>());
+            }
+            if let Some(name) = find_config_arg(&std::env::args().collect::<Vec<_>>()) {
+                match normalize_config_name(&name) {
+                    Ok(name) => {
+                        if let Ok(mut active) = ACTIVE_CONFIG.lock() {
+                            *active = name;
+                        }
+                    }
+                    Err(e) => write_log(LogLevel::Error, "setup", &e),
+                }
+            }
+            let config = get_app_config(app.handle().clone());
+            apply_runtime_config(&config);
+            if let Err(e) = acquire_data_lock(&expand_data_path(&config.data_path)) {
+                use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+                write_log(LogLevel::Error, "setup", &e);
+                let handle = app.handle().clone();
+                app.dialog()
+                    .message(e)
+                    .kind(MessageDialogKind::Error)
+                    .show(move |_| handle.exit(1));
+                return Ok(());
+            }
+            if !config.quick_capture_hotkey.is_empty() {
+                if let Err(e) = register_global_hotkey(app.handle(), &config.quick_capture_hotkey) {
+                    write_log(LogLevel::Error, "setup", &e);
+                }
+            }
+            if config.auto_archive_days > 0 {
+                let _ = auto_archive(app.handle().clone(), config.data_path.clone(), config.auto_archive_days);
+            }
+            let _ = record_daily_stats(config.data_path);
+            std::thread::spawn(cleanup_temp_icons);
+            // 设置了应用锁时以锁定状态启动
+            LAST_ACTIVITY.store(unix_now(), Ordering::Relaxed);
+            if read_app_lock(app.handle()).is_some() {
+                APP_LOCKED.store(true, Ordering::SeqCst);
+            }
+            spawn_autolock_watcher(app.handle().clone());
+            spawn_auto_backup(app.handle().clone());
+            spawn_accent_watcher(app.handle().clone());
+            Ok(())
+        })
+        .invoke_handler(move |invoke| {
+            let command = invoke.message.command();
+            write_log(LogLevel::Info, command, "invoked");
+            if APP_LOCKED.load(Ordering::SeqCst) && MUTATING_COMMANDS.contains(&command) {
+                invoke.resolver.reject("应用已锁定，请先解锁");
+                return true;
+            }
+            LAST_ACTIVITY.store(unix_now(), Ordering::Relaxed);
+            if let tauri::ipc::InvokeBody::Json(args) = invoke.message.payload() {
+                if MUTATING_COMMANDS.contains(&command) {
+                    record_audit(command, args);
+                }
+                record_access(command, args);
+            }
+            handler(invoke)
+        })
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                release_data_lock();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_content_hash_ignores_file_order() {
+        let root = std::env::temp_dir().join(format!("simple-todo-hash-{}", Uuid::new_v4()));
+        let (first, second) = (root.join("first"), root.join("second"));
+        let files = [("content.json", "[]"), ("b.txt", "b"), ("sub/a.txt", "a"), ("c.png", "c")];
+        for (dir, order) in [(&first, [0, 1, 2, 3]), (&second, [3, 2, 1, 0])] {
+            for i in order {
+                let (rel, data) = files[i];
+                let path = if rel == "content.json" { dir.join(rel) } else { dir.join(assets_dir_name()).join(rel) };
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, data).unwrap();
+            }
+        }
+        let hash = compute_content_hash(&first).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, compute_content_hash(&second).unwrap());
+        fs::write(second.join(assets_dir_name()).join("b.txt"), "changed").unwrap();
+        assert_ne!(hash, compute_content_hash(&second).unwrap());
+        fs::rename(second.join(assets_dir_name()).join("b.txt"), second.join(assets_dir_name()).join("d.txt")).unwrap();
+        fs::write(second.join(assets_dir_name()).join("d.txt"), "b").unwrap();
+        assert_ne!(hash, compute_content_hash(&second).unwrap());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn expand_data_path_expands_home_and_variables() {
+        std::env::set_var("SIMPLE_TODO_TEST_DIR", "/tmp/simple-todo");
+        assert_eq!(expand_data_path("  $SIMPLE_TODO_TEST_DIR/data "), "/tmp/simple-todo/data");
+        assert_eq!(expand_data_path("${SIMPLE_TODO_TEST_DIR}/data"), "/tmp/simple-todo/data");
+        assert_eq!(expand_data_path("%SIMPLE_TODO_TEST_DIR%\\data"), "/tmp/simple-todo\\data");
+        assert_eq!(expand_data_path("$SIMPLE_TODO_UNDEFINED/data"), "$SIMPLE_TODO_UNDEFINED/data");
+        assert_eq!(expand_data_path("100%/data"), "100%/data");
+        if let Some(home) = home_dir() {
+            assert_eq!(expand_data_path("~/todos"), format!("{}/todos", home));
+            assert_eq!(expand_data_path("~"), home);
+        }
+        assert_eq!(expand_data_path("a~b"), "a~b");
+    }
+
+    #[test]
+    fn sanitize_folder_name_rejects_traversal() {
+        assert!(sanitize_folder_name("3f2a-todo").is_ok());
+        for name in ["", "..", "../x", "a/b", "a\\b", ".trash", "/abs", "a\nb"] {
+            assert!(sanitize_folder_name(name).is_err(), "{:?} 应被拒绝", name);
+        }
+    }
+
+    #[test]
+    fn fold_ical_line_folds_at_75_bytes_without_splitting_chars() {
+        let mut out = String::new();
+        fold_ical_line(&"a".repeat(100), &mut out);
+        assert_eq!(out, format!("{}\r\n {}\r\n", "a".repeat(75), "a".repeat(25)));
+
+        let mut out = String::new();
+        fold_ical_line(&format!("SUMMARY:{}", "中".repeat(40)), &mut out);
+        let physical: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(physical.len() > 1);
+        assert!(physical.iter().all(|l| l.len() <= 75));
+        let unfolded: String = physical.iter().enumerate().map(|(i, l)| if i == 0 { *l } else { &l[1..] }).collect();
+        assert_eq!(unfolded, format!("SUMMARY:{}", "中".repeat(40)));
+    }
+
+    #[test]
+    fn strip_unsafe_html_removes_scripts_handlers_and_external_resources() {
+        assert_eq!(strip_unsafe_html("<b>ok</b> <a href=\"https://example.com\">link</a>"), None);
+        assert_eq!(strip_unsafe_html("plain text"), None);
+        assert_eq!(strip_unsafe_html("a<script>alert(1)</script>b").as_deref(), Some("ab"));
+        assert_eq!(strip_unsafe_html("a<STYLE>body{}</STYLE>b").as_deref(), Some("ab"));
+        assert_eq!(strip_unsafe_html("<img src=x onerror=alert(1)>x").as_deref(), Some("x"));
+        assert_eq!(strip_unsafe_html("<img src=\"https://tracker.example/p.gif\">x").as_deref(), Some("x"));
+        assert_eq!(strip_unsafe_html("<a href=\"javascript:alert(1)\">x</a>").as_deref(), Some("x</a>"));
+        assert_eq!(strip_unsafe_html("<meta http-equiv=refresh>x").as_deref(), Some("x"));
+        assert_eq!(strip_unsafe_html("<span data-one=\"1\">x</span>"), None);
+        assert_eq!(strip_unsafe_html("a<iframe src=x>").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn split_front_matter_reads_status_due_and_tags() {
+        let (meta, body) = split_front_matter("---\nstatus: completed\ndue: \"2024-05-01\"\ntags: [work, 'home']\n---\n\n# Title\n");
+        assert_eq!(meta.status.as_deref(), Some("completed"));
+        assert_eq!(meta.due.as_deref(), Some("2024-05-01"));
+        assert_eq!(meta.tags, ["work", "home"]);
+        assert_eq!(body, "# Title\n");
+
+        let (meta, body) = split_front_matter("---\r\ndue_date: 2024-06-01\r\ntags:\r\n  - a\r\n  - b\r\nstatus: todo\r\n---\r\nBody");
+        assert_eq!(meta.due.as_deref(), Some("2024-06-01"));
+        assert_eq!(meta.tags, ["a", "b"]);
+        assert_eq!(meta.status.as_deref(), Some("todo"));
+        assert_eq!(body, "Body");
+
+        let (meta, body) = split_front_matter("# No front matter\n---\n");
+        assert!(meta.status.is_none() && meta.tags.is_empty());
+        assert_eq!(body, "# No front matter\n---\n");
+        assert_eq!(split_front_matter("---\nstatus: x\n").1, "---\nstatus: x\n");
+    }
+
+    #[test]
+    fn demojibake_reverses_latin1_decoding() {
+        assert_eq!(demojibake("cafÃ©").as_deref(), Some("café"));
+        let garbled: String = "中文".bytes().map(|b| encoding_rs::WINDOWS_1252.decode_without_bom_handling(&[b]).0.into_owned()).collect();
+        assert_eq!(demojibake(&garbled).as_deref(), Some("中文"));
+        assert_eq!(demojibake("plain ascii"), None);
+        assert_eq!(demojibake("café"), None);
+        assert_eq!(demojibake("中文"), None);
+    }
+
+    #[test]
+    fn encrypted_archive_round_trip() {
+        let plain = b"PK\x03\x04 workspace".to_vec();
+        let encrypted = encrypt_archive(plain.clone(), "correct horse").unwrap();
+        assert_eq!(&encrypted[..8], ENCRYPTED_ARCHIVE_MAGIC);
+        assert!(!encrypted.windows(plain.len()).any(|w| w == plain.as_slice()));
+        assert_eq!(decrypt_archive(encrypted.clone(), "correct horse").unwrap(), plain);
+        assert_eq!(decrypt_archive(encrypted.clone(), "wrong").unwrap_err(), "口令错误");
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(decrypt_archive(tampered, "correct horse").unwrap_err(), "归档已损坏或被修改");
+
+        let mut expensive = encrypted;
+        expensive[8..12].copy_from_slice(&(MAX_ARGON2_M_COST + 1).to_le_bytes());
+        assert_eq!(decrypt_archive(expensive, "correct horse").unwrap_err(), "归档的密钥派生参数超出允许范围");
+        assert_eq!(decrypt_archive(b"PK\x03\x04".to_vec(), "x").unwrap_err(), "不是加密的工作区归档");
+    }
+
+    #[test]
+    fn parse_ical_components_unfolds_and_strips_params() {
+        let text = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nSUMMARY:Buy milk\r\nDESCRIPTION:Line one\r\n  continued\r\n\
+                    DUE;VALUE=DATE:20240101\r\nSUMMARY:ignored\r\nEND:VTODO\r\nBEGIN:VEVENT\r\nsummary:Meet\r\n\
+                    END:VEVENT\r\nX-OUTSIDE:1\r\nEND:VCALENDAR\r\n";
+        let components = parse_ical_components(text);
+        assert_eq!(components.len(), 2);
+        let (kind, props) = &components[0];
+        assert_eq!(kind, "VTODO");
+        assert_eq!(props["SUMMARY"], "Buy milk");
+        assert_eq!(props["DESCRIPTION"], "Line one continued");
+        assert_eq!(props["DUE"], "20240101");
+        assert_eq!(components[1].0, "VEVENT");
+        assert_eq!(components[1].1["SUMMARY"], "Meet");
+        assert!(!components[1].1.contains_key("X-OUTSIDE"));
+
+        let mut folded = String::new();
+        fold_ical_line(&format!("DESCRIPTION:{}", "长".repeat(60)), &mut folded);
+        let round_trip = parse_ical_components(&format!("BEGIN:VTODO\r\n{}END:VTODO\r\n", folded));
+        assert_eq!(round_trip[0].1["DESCRIPTION"], "长".repeat(60));
+    }
+
+    #[test]
+    fn diff_lines_merges_adjacent_ranges() {
+        let ranges = diff_lines(&["a", "b", "c", "d"], &["a", "x", "y", "d"]).unwrap();
+        let kinds: Vec<&str> = ranges.iter().map(|r| r.kind.as_str()).collect();
+        assert_eq!(kinds, ["unchanged", "added", "removed", "unchanged"]);
+        assert_eq!(ranges[1].lines, ["x", "y"]);
+        assert_eq!((ranges[1].start_a, ranges[1].start_b), (1, 1));
+        assert_eq!(ranges[2].lines, ["b", "c"]);
+        assert_eq!(ranges[3].start_a, 3);
+
+        let same = diff_lines(&["a", "b"], &["a", "b"]).unwrap();
+        assert_eq!(same.len(), 1);
+        assert_eq!(same[0].lines, ["a", "b"]);
+        assert!(diff_lines(&[], &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diff_lines_rejects_oversized_input() {
+        let a: Vec<String> = (0..5000).map(|i| format!("a{}", i)).collect();
+        let b: Vec<String> = (0..5000).map(|i| format!("b{}", i)).collect();
+        let a: Vec<&str> = a.iter().map(String::as_str).collect();
+        let b: Vec<&str> = b.iter().map(String::as_str).collect();
+        assert!(diff_lines(&a, &b).is_err());
+    }
+
+    #[test]
+    fn is_sync_conflict_name_matches_sync_tool_copies() {
+        for name in [
+            "todos (Alice's conflicted copy 2024-01-01).json",
+            "todos.sync-conflict-20240101-120000-ABCDEFG.json",
+            "todos (1).json",
+            "Todos (12).JSON",
+        ] {
+            assert!(is_sync_conflict_name(name), "{:?} 应识别为冲突副本", name);
+        }
+        for name in ["todos.json", "todos (a).json", "todos ().json", "other (1).json", "todos (1).json.bak"] {
+            assert!(!is_sync_conflict_name(name), "{:?} 不应识别为冲突副本", name);
+        }
+    }
+
+    #[test]
+    fn sanitize_folder_name_accepts_year_archived_names() {
+        assert!(sanitize_folder_name("archive/2023/3f2a-todo").is_ok());
+        for name in ["archive/23/x", "archive/2023/", "archive/2023/../x", "archive/2023/.x", "archive/2023/a/b", "archive/x"] {
+            assert!(sanitize_folder_name(name).is_err(), "{:?} 应被拒绝", name);
+        }
+    }
+
+    #[test]
+    fn retarget_folder_urls_matches_encoded_folder_segments() {
+        let url = |folder: &str| format!("asset://localhost/%2Fdata%2F{}%2Fassets%2Fx.png", folder);
+        let mut nodes = vec![
+            serde_json::json!({ "type": "image", "url": url("001-%E6%A0%87%E9%A2%98") }),
+            serde_json::json!({ "type": "p", "children": [{ "type": "file", "url": url("001-ab") }] }),
+            serde_json::json!({ "type": "file", "url": "C:\\data\\001-ab\\assets\\y.pdf" }),
+        ];
+        assert!(retarget_folder_urls(&mut nodes, "001-标题", "archive/2024/001-标题"));
+        assert_eq!(nodes[0]["url"], url("archive%2F2024%2F001-%E6%A0%87%E9%A2%98"));
+        assert!(!retarget_folder_urls(&mut nodes, "001-a", "002-a"));
+        assert_eq!(nodes[1]["children"][0]["url"], url("001-ab"));
+        assert!(retarget_folder_urls(&mut nodes, "001-ab", "002-ab"));
+        assert_eq!(nodes[1]["children"][0]["url"], url("002-ab"));
+        assert_eq!(nodes[2]["url"], "C:\\data\\002-ab\\assets\\y.pdf");
+    }
+
+    #[test]
+    fn retarget_data_path_urls_replaces_only_the_root() {
+        let mut nodes = vec![
+            serde_json::json!({ "type": "image", "url": "asset://localhost/%2Fold%2Fdata%2Fa%2Fassets%2Fx.png" }),
+            serde_json::json!({ "type": "image", "url": "asset://localhost/%2Fold%2Fdata-2%2Fa%2Fassets%2Fx.png" }),
+        ];
+        assert!(retarget_data_path_urls(&mut nodes, "/old/data/", "/new/我的"));
+        assert_eq!(nodes[0]["url"], "asset://localhost/%2Fnew%2F%E6%88%91%E7%9A%84%2Fa%2Fassets%2Fx.png");
+        assert_eq!(nodes[1]["url"], "asset://localhost/%2Fold%2Fdata-2%2Fa%2Fassets%2Fx.png");
+    }
+
+    #[test]
+    fn auto_backup_time_reads_the_backup_name() {
+        let expected = chrono::NaiveDate::from_ymd_opt(2024, 1, 2)
+            .and_then(|d| d.and_hms_opt(3, 4, 5))
+            .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+            .map(|t| t.with_timezone(&chrono::Utc));
+        assert!(expected.is_some());
+        assert_eq!(auto_backup_time("backup-20240102-030405-auto"), expected);
+        assert_eq!(auto_backup_time("backup-20240102-030405-auto.zip"), expected);
+        for name in ["backup-20240102-030405", "backup-20240102-030405.zip", "backup-2024-auto", "notes-auto"] {
+            assert_eq!(auto_backup_time(name), None, "{:?} 不是自动备份", name);
+        }
+    }
 }
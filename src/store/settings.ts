@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia'
 import { invoke } from '@tauri-apps/api/core'
+import { ElMessage } from 'element-plus'
 import i18n from '../i18n'
 
 export interface AppConfig {
@@ -10,7 +11,49 @@ export interface AppConfig {
   font_size: number
   text_color_light: string
   text_color_dark: string
+  accent_color_light?: string
+  accent_color_dark?: string
+  bg_color_light?: string
+  bg_color_dark?: string
+  border_color_light?: string
+  border_color_dark?: string
   launch_at_login?: boolean
+  move_exclude?: string[]
+  log_level?: 'off' | 'error' | 'info'
+  storage_backend?: 'json' | 'sqlite'
+  default_status?: string
+  line_ending?: 'lf' | 'crlf'
+  backup_path?: string
+  confirm_delete_threshold?: number
+  autolock_minutes?: number
+  allowed_transitions?: Record<string, string[]> | null
+  export_compression?: number
+  tag_colors?: Record<string, string>
+  status_colors?: Record<string, string>
+  pretty_index?: boolean
+  write_retries?: number
+  post_save_hook?: string
+  verify_writes?: boolean
+  enable_audit?: boolean
+  max_title_length?: number
+  max_detail_bytes?: number
+  auto_archive_days?: number
+  cards_per_page?: number
+  use_system_accent?: boolean
+  quarantine_imports?: boolean
+  assets_dir_name?: string
+  auto_backup_interval_hours?: number
+  max_backups?: number
+  urgency_due_weight?: number
+  urgency_priority_weight?: number
+  urgency_age_weight?: number
+  quick_capture_hotkey?: string
+  compress_backups_after_days?: number
+  max_folder_bytes?: number
+  tag_case_policy?: 'preserve' | 'lower' | 'title'
+  wip_limit?: number
+  track_access?: boolean
+  archive_year_layout?: boolean
 }
 
 export const useSettingsStore = defineStore('settings', {
@@ -23,15 +66,66 @@ export const useSettingsStore = defineStore('settings', {
       font_size: 14,
       text_color_light: '#333333',
       text_color_dark: '#e5e5e5',
+      accent_color_light: '#409eff',
+      accent_color_dark: '#409eff',
+      bg_color_light: '#ffffff',
+      bg_color_dark: '#1a1a1a',
+      border_color_light: '#e6e6e6',
+      border_color_dark: '#444444',
       launch_at_login: false,
+      move_exclude: ['config.json', '.git'],
+      log_level: 'error',
+      storage_backend: 'json',
+      default_status: 'pending',
+      line_ending: 'lf',
+      backup_path: '',
+      confirm_delete_threshold: 10,
+      autolock_minutes: 0,
+      export_compression: 6,
+      tag_colors: {},
+      status_colors: { backlog: '#909399', pending: '#409eff', in_progress: '#e6a23c', completed: '#999999' },
+      pretty_index: false,
+      write_retries: 2,
+      post_save_hook: '',
+      verify_writes: false,
+      enable_audit: false,
+      max_title_length: 200,
+      max_detail_bytes: 0,
+      auto_archive_days: 0,
+      cards_per_page: 4,
+      use_system_accent: false,
+      quarantine_imports: false,
+      assets_dir_name: 'assets',
+      auto_backup_interval_hours: 0,
+      max_backups: 0,
+      urgency_due_weight: 12,
+      urgency_priority_weight: 6,
+      urgency_age_weight: 2,
+      quick_capture_hotkey: '',
+      compress_backups_after_days: 30,
+      max_folder_bytes: 0,
+      tag_case_policy: 'preserve',
+      wip_limit: 0,
+      track_access: false,
+      archive_year_layout: false,
     } as AppConfig,
+    /** 系统强调色，系统不提供或未开启跟随时为 null */
+    systemAccent: null as string | null,
   }),
   getters: {
     globalStyles: (state) => ({
       '--app-font-family': state.config.font_family,
       '--app-font-size': `${state.config.font_size}px`,
       '--app-text-color': state.config.theme === 'dark' ? state.config.text_color_dark : state.config.text_color_light,
-      '--app-bg-color': state.config.theme === 'dark' ? '#1a1a1a' : '#ffffff',
+      '--app-bg-color': state.config.theme === 'dark'
+        ? (state.config.bg_color_dark || '#1a1a1a')
+        : (state.config.bg_color_light || '#ffffff'),
+      '--app-border-color': state.config.theme === 'dark'
+        ? (state.config.border_color_dark || '#444444')
+        : (state.config.border_color_light || '#e6e6e6'),
+      '--el-color-primary': (state.config.use_system_accent && state.systemAccent) || (state.config.theme === 'dark'
+        ? (state.config.accent_color_dark || '#409eff')
+        : (state.config.accent_color_light || '#409eff')),
     }),
   },
   actions: {
@@ -41,9 +135,17 @@ export const useSettingsStore = defineStore('settings', {
       this.applyI18n()
       this.applyTheme()
       await this.syncAutostart()
+      await this.refreshSystemAccent()
+    },
+    async refreshSystemAccent() {
+      this.systemAccent = this.config.use_system_accent
+        ? await invoke<string | null>('get_system_accent_color').catch(() => null)
+        : null
     },
     async syncAutostart() {
       try {
+        // 本次运行中已临时暂停自启时不重新注册，保留配置中的设置
+        if (await invoke<boolean>('is_autostart_suspended')) return
         const { enable, disable, isEnabled } = await import('@tauri-apps/plugin-autostart')
         const enabled = await isEnabled()
         if (this.config.launch_at_login && !enabled) await enable()
@@ -51,13 +153,17 @@ export const useSettingsStore = defineStore('settings', {
       } catch (_) {}
     },
     async saveConfig() {
-      await invoke('save_app_config', { config: this.config })
+      // 对比度不足等警告不阻止保存，只提示用户
+      const warnings = await invoke<string[]>('save_app_config', { config: this.config })
+      for (const warning of warnings) ElMessage.warning(warning)
       this.applyI18n()
       this.applyTheme()
     },
+    /** 只保存修改的配置项，后端合并到磁盘上的配置，避免覆盖未改动的项 */
     async updateConfig(newConfig: Partial<AppConfig>) {
-      Object.assign(this.config, newConfig)
-      await this.saveConfig()
+      this.config = await invoke<AppConfig>('save_config_partial', { patch: newConfig })
+      this.applyI18n()
+      this.applyTheme()
     },
     applyI18n() {
       // @ts-ignore
@@ -1,11 +1,26 @@
 import { defineStore } from 'pinia'
 import { invoke } from '@tauri-apps/api/core'
+import { useSettingsStore } from './settings'
 
 export interface TodoItem {
   id: string
   title: string
-  status: 'pending' | 'completed'
+  status: 'backlog' | 'pending' | 'in_progress' | 'completed'
   folder_name: string
+  due_date?: string | null
+  priority?: number
+  created_at?: string | null
+  updated_at?: string | null
+  completed_at?: string | null
+  snoozed_until?: string | null
+  remind_at?: string | null
+  tags?: string[]
+  today?: boolean
+  recurrence?: 'daily' | 'weekly' | 'monthly' | 'yearly' | null
+  depends_on?: string[]
+  archived?: boolean
+  effort?: number | null
+  board?: string
 }
 
 export const useTodoStore = defineStore('todo', {
@@ -13,17 +28,25 @@ export const useTodoStore = defineStore('todo', {
     todos: [] as TodoItem[],
     dataPath: '',
   }),
+  getters: {
+    /** 列表中显示的待办（不含仍在推迟期内的和已归档的） */
+    visibleTodos: (state) => {
+      const now = Date.now()
+      return state.todos.filter(t => !t.archived && (!t.snoozed_until || new Date(t.snoozed_until).getTime() <= now))
+    },
+  },
   actions: {
     async loadTodos(dataPath: string) {
       this.dataPath = dataPath
-      this.todos = await invoke('get_todos', { dataPath })
+      // 保存时会整体写回列表，因此需要加载包括推迟项与归档项在内的全部待办
+      this.todos = await invoke('get_todos', { dataPath, includeSnoozed: true, includeArchived: true })
     },
     async addTodo(title: string) {
       const folder_name = await invoke<string>('create_todo_folder', { dataPath: this.dataPath })
       const newTodo: TodoItem = {
         id: crypto.randomUUID(),
         title,
-        status: 'pending',
+        status: (useSettingsStore().config.default_status || 'pending') as TodoItem['status'],
         folder_name,
       }
       this.todos.push(newTodo)
@@ -39,9 +62,9 @@ export const useTodoStore = defineStore('todo', {
     async deleteTodo(id: string) {
       const todo = this.todos.find(t => t.id === id)
       if (todo) {
-        // 先删除对应的文件夹
+        // 先将对应的文件夹移入回收站（记入最近删除，可恢复）
         try {
-          await invoke('delete_todo_folder', {
+          await invoke('delete_todo_folder_to_os_trash', {
             dataPath: this.dataPath,
             folderName: todo.folder_name
           })
@@ -53,6 +76,12 @@ export const useTodoStore = defineStore('todo', {
       this.todos = this.todos.filter(t => t.id !== id)
       await this.saveTodos()
     },
+    /** 批量删除；数量超过免确认上限时需传 confirm，否则后端会拒绝 */
+    async deleteTodos(ids: string[], confirm: boolean) {
+      const folderNames = this.todos.filter(t => ids.includes(t.id)).map(t => t.folder_name)
+      await invoke('delete_todos', { dataPath: this.dataPath, folderNames, confirm })
+      this.todos = this.todos.filter(t => !ids.includes(t.id))
+    },
     async saveTodos() {
       await invoke('save_todos', { dataPath: this.dataPath, todos: this.todos })
     }